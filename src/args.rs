@@ -5,9 +5,48 @@
 /// Automatically converts all ingresses to
 /// gateway-api compatible resources.
 pub struct I2GArgs {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// YAML file with settings which supersede arguments: `defaultGatewayName`,
+    /// `defaultGatewayNamespace`, `skipByDefault` and `compatProvider`,
+    /// and with default gateways by namespace in `namespaceGateways`.
+    /// It's re-read every `--resync-interval`, so settings can be changed without a restart.
+    #[arg(long, env = "I2G_CONFIG")]
+    pub config: Option<std::path::PathBuf>,
+
     // Default gateway name
     #[arg(long, env = "I2G_DEFAULT_GATEWAY_NAME")]
-    pub default_gateway_name: String,
+    pub default_gateway_name: Option<String>,
+
+    /// Label selector of gateways routes are attached to, e.g. `app=edge`.
+    /// Gateways are looked up in all namespaces. Takes precedence over `--default-gateway-name`.
+    #[arg(long, env = "I2G_GATEWAY_SELECTOR")]
+    pub gateway_selector: Option<crate::utils::LabelSelector>,
+
+    /// Create and maintain a Gateway in every namespace with translated Ingresses.
+    /// Its listeners are derived from hosts and TLS secrets of the Ingresses.
+    /// Routes are attached to it instead of `--default-gateway-name`.
+    #[arg(long, env = "I2G_PROVISION_GATEWAYS", default_value_t = false)]
+    pub provision_gateways: bool,
+
+    /// GatewayClass of provisioned gateways. Required with `--provision-gateways`.
+    #[arg(long, env = "I2G_GATEWAY_CLASS")]
+    pub gateway_class: Option<String>,
+
+    /// Name of provisioned gateways.
+    #[arg(
+        long,
+        env = "I2G_PROVISIONED_GATEWAY_NAME",
+        default_value = "i2g-gateway"
+    )]
+    pub provisioned_gateway_name: String,
+
+    /// Provision a single Gateway in this namespace for Ingresses of all namespaces
+    /// instead of a Gateway per namespace. Its listeners reference TLS secrets
+    /// in Ingress namespaces, ReferenceGrants allowing it are created there.
+    #[arg(long, env = "I2G_PROVISIONED_GATEWAY_NAMESPACE")]
+    pub provisioned_gateway_namespace: Option<String>,
 
     /// Default gateway's namespace if default_gateway_name is present
     #[arg(long, default_value = "default", env = "I2G_DEFAULT_GATEWAY_NAMESPACE")]
@@ -27,8 +66,554 @@ pub struct I2GArgs {
     #[arg(long, env = "I2G_LOG_LEVEL", default_value_t = tracing::level_filters::LevelFilter::INFO)]
     pub log_level: tracing::level_filters::LevelFilter,
 
+    /// OTLP/gRPC endpoint of an OpenTelemetry collector, e.g. `http://otel-collector:4317`.
+    /// Reconcile and apply spans are exported to it. Traces aren't exported by default.
+    #[arg(long, env = "I2G_OTLP_ENDPOINT")]
+    pub otlp_endpoint: Option<String>,
+
     /// Whether to skip ingresses by default unless they have the annotation
     /// `i2g-operator/translate: "true"`
     #[arg(long, env = "I2G_SKIP_BY_DEFAULT", default_value_t = false)]
     pub skip_by_default: bool,
+
+    /// What happens to routes generated from an Ingress once its translation is disabled
+    /// with `i2g-operator/translate` or `--skip-by-default`.
+    #[arg(long, env = "I2G_DISABLED_ROUTES", value_enum, default_value_t = DisabledRoutes::Delete)]
+    pub disabled_routes: DisabledRoutes,
+
+    /// Only translate Ingresses from namespaces matching this label selector,
+    /// e.g. `i2g-operator/enabled=true`. Ingresses from all namespaces are
+    /// translated if it's not set.
+    #[arg(long, env = "I2G_NAMESPACE_LABEL_SELECTOR")]
+    pub namespace_label_selector: Option<String>,
+
+    /// Only translate Ingresses of this class. Can be specified multiple times.
+    /// Ingresses of all classes are translated by default.
+    #[arg(
+        long = "ingress-class",
+        env = "I2G_INGRESS_CLASSES",
+        value_delimiter = ','
+    )]
+    pub ingress_classes: Vec<String>,
+
+    /// Gateway of Ingresses with the class, in format `class=name` or `class=namespace/name`,
+    /// e.g. `nginx=infra/edge,internal=infra/internal-gw`.
+    /// Used unless the Ingress sets gateway name or selector annotations.
+    #[arg(long, env = "I2G_CLASS_GATEWAY_MAP", value_delimiter = ',')]
+    pub class_gateway_map: Vec<crate::utils::ClassGateway>,
+
+    /// Read gateway, section and provider of Ingresses from I2GClassConfig objects
+    /// referenced by `spec.parameters` of their IngressClass. Requires the CRD
+    /// printed by the `crd` subcommand and permissions to watch IngressClasses.
+    #[arg(long, env = "I2G_INGRESS_CLASS_PARAMETERS", default_value_t = false)]
+    pub ingress_class_parameters: bool,
+
+    /// Only translate Ingresses from these namespaces. Can be specified multiple times.
+    #[arg(
+        long = "watch-namespace",
+        env = "I2G_WATCH_NAMESPACES",
+        value_delimiter = ','
+    )]
+    pub watch_namespaces: Vec<String>,
+
+    /// Don't watch Ingresses in these namespaces. Can be specified multiple times.
+    #[arg(
+        long = "exclude-namespace",
+        env = "I2G_EXCLUDE_NAMESPACES",
+        value_delimiter = ','
+    )]
+    pub exclude_namespaces: Vec<String>,
+
+    /// Default timeout for requests of generated HTTPRoute rules (e.g. `30s`).
+    #[arg(long, env = "I2G_DEFAULT_REQUEST_TIMEOUT")]
+    pub default_request_timeout: Option<gateway_api::Duration>,
+
+    /// Default timeout for requests from the gateway to backends
+    /// of generated HTTPRoute rules (e.g. `10s`).
+    #[arg(long, env = "I2G_DEFAULT_BACKEND_REQUEST_TIMEOUT")]
+    pub default_backend_request_timeout: Option<gateway_api::Duration>,
+
+    /// Take ownership of existing routes with the same names as generated ones,
+    /// unless they're managed or owned by someone else.
+    #[arg(long, env = "I2G_ADOPT_EXISTING", default_value_t = false)]
+    pub adopt_existing: bool,
+
+    /// Apply generated routes whose names are taken by routes which don't belong
+    /// to the operator under alternative names with a hash suffix.
+    #[arg(long, env = "I2G_RENAME_CONFLICTING_ROUTES", default_value_t = false)]
+    pub rename_conflicting_routes: bool,
+
+    /// Annotation added to all generated routes, in format `key=template`.
+    /// Template can use `{ingress}`, `{namespace}`, `{host}` and `{kind}` placeholders.
+    /// Can be specified multiple times.
+    #[arg(
+        long = "route-annotation",
+        env = "I2G_ROUTE_ANNOTATIONS",
+        value_delimiter = ','
+    )]
+    pub route_annotations: Vec<crate::templates::MetadataTemplate>,
+
+    /// Request headers removed by every generated HTTPRoute rule.
+    #[arg(
+        long = "strip-request-headers",
+        env = "I2G_STRIP_REQUEST_HEADERS",
+        value_delimiter = ','
+    )]
+    pub strip_request_headers: Vec<String>,
+
+    /// Request header set to the original Ingress path by rules with a URL rewrite.
+    #[arg(long = "original-path-header", env = "I2G_ORIGINAL_PATH_HEADER")]
+    pub original_path_header: Option<String>,
+
+    /// Ingress labels copied to generated routes.
+    /// Globs (`team*`) or regular expressions between slashes (`/^app\..+$/`).
+    #[arg(
+        long = "propagate-labels",
+        env = "I2G_PROPAGATE_LABELS",
+        value_delimiter = ','
+    )]
+    pub propagate_labels: Vec<crate::utils::KeyPattern>,
+
+    /// Ingress annotations copied to generated routes, same format as `--propagate-labels`.
+    #[arg(
+        long = "propagate-annotations",
+        env = "I2G_PROPAGATE_ANNOTATIONS",
+        value_delimiter = ','
+    )]
+    pub propagate_annotations: Vec<crate::utils::KeyPattern>,
+
+    /// Interval between reconciles of successfully translated Ingresses (e.g. `10s`).
+    /// Skipped Ingresses and instances which are not leaders recheck with the same interval.
+    #[arg(long, env = "I2G_RESYNC_INTERVAL", default_value = "10s")]
+    pub resync_interval: gateway_api::Duration,
+
+    /// Delay before the first retry of a failed reconcile. It doubles
+    /// with every consecutive failure up to `--error-backoff-max`.
+    #[arg(long, env = "I2G_ERROR_BACKOFF_BASE", default_value = "5s")]
+    pub error_backoff_base: gateway_api::Duration,
+
+    /// Maximum delay between retries of failed reconciles.
+    /// Requests throttled by the API server are always retried after it.
+    #[arg(long, env = "I2G_ERROR_BACKOFF_MAX", default_value = "5m")]
+    pub error_backoff_max: gateway_api::Duration,
+
+    /// Number of consecutive reconcile failures after which an Ingress is reported
+    /// as stuck. 0 disables dead-letter reporting.
+    #[arg(long, env = "I2G_DEAD_LETTER_THRESHOLD", default_value_t = 5)]
+    pub dead_letter_threshold: u32,
+
+    /// Match hostnames with a `Host` header matcher on every rule
+    /// instead of the `hostnames` field of generated HTTPRoutes.
+    #[arg(long, env = "I2G_HOST_AS_HEADER_MATCH", default_value_t = false)]
+    pub host_as_header_match: bool,
+
+    /// Reconcile Ingresses when TLS Secrets referenced in their `spec.tls` change.
+    /// Requires permissions to list and watch Secrets.
+    #[arg(long, env = "I2G_WATCH_TLS_SECRETS", default_value_t = false)]
+    pub watch_tls_secrets: bool,
+
+    /// Kinds of `resource` backends which gateways are known to support,
+    /// as `Kind.group`, e.g. `Backend.gateway.envoyproxy.io`.
+    /// Other resource backends are translated with a warning.
+    #[arg(long, env = "I2G_RESOURCE_BACKEND_KINDS", value_delimiter = ',')]
+    pub resource_backend_kinds: Vec<String>,
+
+    /// Ingress controller whose annotations are translated.
+    #[arg(long, env = "I2G_COMPAT_PROVIDER", value_enum, default_value_t = CompatProvider::Nginx)]
+    pub compat_provider: CompatProvider,
+
+    /// How backends pointing at ExternalName Services are translated.
+    #[arg(long, env = "I2G_EXTERNAL_NAME_BACKENDS", value_enum, default_value_t = ExternalNameBackends::Service)]
+    pub external_name_backends: ExternalNameBackends,
+
+    /// How to normalize paths of Ingress rules before using them in path matches.
+    #[arg(long, env = "I2G_PATH_NORMALIZATION", value_enum, default_value_t = PathNormalization::None)]
+    pub path_normalization: PathNormalization,
+
+    /// Which secret is used for a host listed in several TLS sections
+    /// of an Ingress with different secrets.
+    #[arg(long, env = "I2G_TLS_SECRET_CONFLICTS", value_enum, default_value_t = TlsSecretConflicts::Skip)]
+    pub tls_secret_conflicts: TlsSecretConflicts,
+
+    /// Seconds to wait after acquiring leadership before starting to reconcile,
+    /// so caches can sync after a failover.
+    #[arg(long, env = "I2G_LEADER_WARMUP_SECS", default_value_t = 0)]
+    pub leader_warmup_secs: u64,
+
+    /// Name of the Lease used for leader election. Instances with different
+    /// scopes, e.g. watched namespaces, must use different leases.
+    #[arg(long, env = "I2G_LEASE_NAME", default_value = "i2g-operator-lock")]
+    pub lease_name: String,
+
+    /// Namespace of the leader election Lease.
+    /// Defaults to the namespace of the client, e.g. the one the operator runs in.
+    #[arg(long, env = "I2G_LEASE_NAMESPACE")]
+    pub lease_namespace: Option<String>,
+
+    /// Time after which the Lease of a leader which stopped renewing it can be taken over.
+    #[arg(long, env = "I2G_LEASE_TTL", default_value = "15s")]
+    pub lease_ttl: gateway_api::Duration,
+
+    /// Interval between attempts to acquire or renew the Lease.
+    /// Must be less than `--lease-ttl`.
+    #[arg(long, env = "I2G_LEASE_RENEW_INTERVAL", default_value = "5s")]
+    pub lease_renew_interval: gateway_api::Duration,
+
+    /// Split namespaces into this number of shards, each reconciled by its own
+    /// instances with a separate leader election Lease.
+    /// All namespaces are reconciled by a single leader by default.
+    #[arg(long, env = "I2G_SHARD_COUNT")]
+    pub shard_count: Option<u32>,
+
+    /// Shard reconciled by this instance, from 0 to `--shard-count` - 1.
+    /// Defaults to the ordinal suffix of the hostname, e.g. 2 for `i2g-operator-2`
+    /// in a StatefulSet.
+    #[arg(long, env = "I2G_SHARD_INDEX")]
+    pub shard_index: Option<u32>,
+
+    /// Attach generated TCPRoutes and UDPRoutes to default Gateways
+    /// instead of the explicit parent gateway.
+    #[arg(long, env = "I2G_TCP_USE_DEFAULT_GATEWAYS", default_value_t = false)]
+    pub tcp_use_default_gateways: bool,
+
+    /// Translate catch-all paths like `/api/*` and `*`
+    /// into prefix matches on `/api` and `/`.
+    #[arg(long, env = "I2G_TRANSLATE_WILDCARD_PATHS", default_value_t = false)]
+    pub translate_wildcard_paths: bool,
+
+    /// Don't overwrite routes last reconciled by a newer operator version.
+    #[arg(long, env = "I2G_REFUSE_DOWNGRADE", default_value_t = false)]
+    pub refuse_downgrade: bool,
+
+    /// Maximum average number of requests per second to the API server.
+    /// Requests aren't limited by default.
+    #[arg(long, env = "I2G_CLIENT_QPS")]
+    pub client_qps: Option<f64>,
+
+    /// Maximum number of requests to the API server sent in a burst.
+    /// Only used together with `--client-qps`.
+    #[arg(long, env = "I2G_CLIENT_BURST", default_value_t = 10)]
+    pub client_burst: u64,
+
+    /// Fail translation of a host if its backend references a service without ports,
+    /// instead of skipping the backend.
+    #[arg(long, env = "I2G_STRICT_SERVICE_PORTS", default_value_t = false)]
+    pub strict_service_ports: bool,
+
+    /// Maximum number of concurrent reconciles per namespace.
+    /// Reconciles over the limit are postponed, so other namespaces aren't starved.
+    #[arg(long, env = "I2G_NAMESPACE_CONCURRENCY")]
+    pub namespace_concurrency: Option<usize>,
+
+    /// Maximum number of concurrent reconciles across all namespaces.
+    /// Not limited by default.
+    #[arg(long, env = "I2G_MAX_CONCURRENT_RECONCILES")]
+    pub max_concurrent_reconciles: Option<u16>,
+
+    /// Share `--max-concurrent-reconciles` slots between namespaces in round-robin order
+    /// instead of starting reconciles in the order Ingresses change.
+    /// Reconciles over `--namespace-concurrency` wait in the queue instead of being postponed.
+    #[arg(long, env = "I2G_FAIR_NAMESPACE_QUEUE", default_value_t = false)]
+    pub fair_namespace_queue: bool,
+
+    /// Gateway listener for HTTPRoutes of hosts without TLS.
+    #[arg(long, env = "I2G_DEFAULT_SECTION_HTTP")]
+    pub default_section_http: Option<String>,
+
+    /// Gateway listener for HTTPRoutes of hosts listed in Ingress `spec.tls`.
+    #[arg(long, env = "I2G_DEFAULT_SECTION_HTTPS")]
+    pub default_section_https: Option<String>,
+
+    /// Gateway listener for TCPRoutes.
+    #[arg(long, env = "I2G_DEFAULT_SECTION_TCP")]
+    pub default_section_tcp: Option<String>,
+
+    /// Port of the gateway's HTTPS listener. If set, HTTPRoutes of hosts listed in
+    /// Ingress `spec.tls` are attached to it, unless a section name is configured.
+    #[arg(long, env = "I2G_HTTPS_LISTENER_PORT")]
+    pub https_listener_port: Option<i32>,
+
+    /// Create ReferenceGrants in gateway namespaces allowing routes
+    /// from Ingress namespaces to reference the gateway.
+    #[arg(long, env = "I2G_CREATE_REFERENCE_GRANTS", default_value_t = false)]
+    pub create_reference_grants: bool,
+
+    /// Copy addresses of the gateway to `status.loadBalancer` of translated Ingresses.
+    #[arg(long, env = "I2G_UPDATE_INGRESS_STATUS", default_value_t = false)]
+    pub update_ingress_status: bool,
+
+    /// Record translation results of every Ingress in an IngressTranslation
+    /// with the same name. The CRD printed by the `crd` subcommand must be installed.
+    #[arg(long, env = "I2G_TRANSLATION_STATUS", default_value_t = false)]
+    pub translation_status: bool,
+
+    /// Address of the HTTP server exposing Prometheus metrics on `/metrics`
+    /// and `/healthz` and `/readyz` probes.
+    #[arg(long, env = "I2G_METRICS_ADDR", default_value = "0.0.0.0:8080")]
+    pub metrics_addr: std::net::SocketAddr,
+
+    /// Report the instance as ready only while it holds the leadership lease.
+    #[arg(long, env = "I2G_READY_REQUIRES_LEADER", default_value_t = false)]
+    pub ready_requires_leader: bool,
+
+    /// Prefix of route names in shadow mode. If set, routes are created
+    /// under prefixed names and attached to the shadow gateway.
+    #[arg(long, env = "I2G_SHADOW_PREFIX", requires = "shadow_gateway_name")]
+    pub shadow_prefix: Option<String>,
+
+    /// Name of the gateway shadow routes are attached to.
+    #[arg(long, env = "I2G_SHADOW_GATEWAY_NAME")]
+    pub shadow_gateway_name: Option<String>,
+
+    /// Namespace of the shadow gateway. Defaults to the default gateway namespace.
+    #[arg(long, env = "I2G_SHADOW_GATEWAY_NAMESPACE")]
+    pub shadow_gateway_namespace: Option<String>,
+
+    /// Attach routes to the listener of the gateway best matching the host
+    /// and protocol, unless a section name is set by an annotation or default section flags.
+    #[arg(long, env = "I2G_AUTO_SELECT_SECTIONS", default_value_t = false)]
+    pub auto_select_sections: bool,
+
+    /// Skip Ingress rules without a host instead of translating them
+    /// to routes matching all hostnames.
+    #[arg(long, env = "I2G_SKIP_HOSTLESS_RULES", default_value_t = false)]
+    pub skip_hostless_rules: bool,
+
+    /// Maximum number of header, query and method matcher combinations per Ingress path.
+    /// Combinations over the limit are dropped with a warning.
+    #[arg(long, env = "I2G_MAX_GENERATED_MATCHES", default_value_t = 64)]
+    pub max_generated_matches: usize,
+}
+
+#[derive(clap::Subcommand, Debug, Clone)]
+pub enum Command {
+    /// List all Ingresses and report how they would be translated.
+    ///
+    /// Doesn't create or modify any resources.
+    Audit {
+        /// Format of the report.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        output: OutputFormat,
+    },
+    /// Report migration readiness of all Ingresses: ready, partially translatable
+    /// with reasons, blocked or skipped.
+    ///
+    /// Doesn't create or modify any resources.
+    Report {
+        /// Format of the report.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        output: OutputFormat,
+    },
+    /// Print all routes managed by the operator as a multi-document YAML.
+    ///
+    /// Server-populated fields are removed, so the output can be used with `kubectl apply`.
+    Snapshot,
+    /// Print CustomResourceDefinitions of the operator as a multi-document YAML.
+    Crd,
+    /// Translate Ingresses from a file without connecting to a cluster
+    /// and print generated routes as a multi-document YAML.
+    Convert {
+        /// File with Ingress manifests, `-` to read from stdin.
+        #[arg(short, long)]
+        file: std::path::PathBuf,
+        /// Number of a named service port, in format `service:name=number`.
+        /// Can be specified multiple times.
+        #[arg(long = "port", value_delimiter = ',')]
+        ports: Vec<crate::backends::NamedPort>,
+    },
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Table,
+    Json,
+}
+
+/// Ingress controller whose annotations are translated.
+#[derive(
+    clap::ValueEnum,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    serde::Serialize,
+    serde::Deserialize,
+    schemars::JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum CompatProvider {
+    /// ingress-nginx.
+    Nginx,
+    /// Traefik Kubernetes Ingress provider.
+    Traefik,
+    /// HAProxy Kubernetes Ingress Controller.
+    Haproxy,
+}
+
+/// How backends pointing at ExternalName Services are translated.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExternalNameBackends {
+    /// Reference the Service, which not all gateway implementations support.
+    Service,
+    /// Reference an Envoy Gateway `Backend` with the external hostname.
+    EnvoyBackend,
+}
+
+/// What happens to generated routes when translation of their Ingress is disabled.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisabledRoutes {
+    /// Delete the routes.
+    Delete,
+    /// Keep the routes, but stop managing them: they're no longer updated or deleted.
+    Orphan,
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathNormalization {
+    /// Use paths as they are.
+    None,
+    /// Collapse repeated slashes: `//foo` becomes `/foo`.
+    Collapse,
+    /// Collapse repeated slashes and remove trailing slash: `/foo/` becomes `/foo`.
+    CollapseTrim,
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TlsSecretConflicts {
+    /// Don't use TLS for the host, since it's unclear which certificate is expected.
+    Skip,
+    /// Use the secret of the first TLS section listing the host.
+    First,
+    /// Use the secret of the last TLS section listing the host.
+    Last,
+}
+
+impl I2GArgs {
+    /// `--resync-interval` as a standard duration.
+    pub fn resync(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.resync_interval.as_millis() as u64)
+    }
+    /// Whether Ingresses of the namespace should be translated
+    /// according to `--watch-namespace` and `--exclude-namespace`.
+    pub fn is_namespace_watched(&self, namespace: &str) -> bool {
+        (self.watch_namespaces.is_empty() || self.watch_namespaces.iter().any(|ns| ns == namespace))
+            && !self.exclude_namespaces.iter().any(|ns| ns == namespace)
+    }
+
+    /// Field selector excluding namespaces from `--exclude-namespace` in watches.
+    pub fn excluded_namespaces_selector(&self) -> String {
+        self.exclude_namespaces
+            .iter()
+            .map(|namespace| format!("metadata.namespace!={namespace}"))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// Check constraints between arguments which clap can't express.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.resync_interval.is_zero() || self.error_backoff_base.is_zero() {
+            anyhow::bail!("--resync-interval and --error-backoff-base must be positive");
+        }
+        if self.error_backoff_max.as_millis() < self.error_backoff_base.as_millis() {
+            anyhow::bail!("--error-backoff-max must not be less than --error-backoff-base");
+        }
+        // Lease duration is stored in whole seconds.
+        if self.lease_ttl.as_millis() < 1000 {
+            anyhow::bail!("--lease-ttl must be at least 1s");
+        }
+        if self.lease_renew_interval.is_zero()
+            || self.lease_renew_interval.as_millis() >= self.lease_ttl.as_millis()
+        {
+            anyhow::bail!("--lease-renew-interval must be positive and less than --lease-ttl");
+        }
+        if self.provision_gateways && self.gateway_class.is_none() {
+            anyhow::bail!("--gateway-class must be set to provision gateways");
+        }
+        if let (Some(request), Some(backend_request)) = (
+            self.default_request_timeout,
+            self.default_backend_request_timeout,
+        ) && backend_request.as_millis() > request.as_millis()
+        {
+            anyhow::bail!(
+                "Default backend request timeout ({backend_request}) must not be greater than default request timeout ({request})"
+            );
+        }
+        if let Some(qps) = self.client_qps
+            && !(qps.is_finite() && qps > 0.0)
+        {
+            anyhow::bail!("Client QPS must be a positive number, got {qps}");
+        }
+        if self.client_burst == 0 {
+            anyhow::bail!("Client burst must be greater than 0");
+        }
+        if self
+            .shadow_prefix
+            .as_ref()
+            .is_some_and(|prefix| prefix.is_empty())
+        {
+            anyhow::bail!("Shadow prefix must not be empty");
+        }
+        if let Some(port) = self.https_listener_port
+            && !(1..=65535).contains(&port)
+        {
+            anyhow::bail!("HTTPS listener port must be between 1 and 65535, got {port}");
+        }
+        if self.max_generated_matches == 0 {
+            anyhow::bail!("Maximum number of generated matches must be positive");
+        }
+        if self.shard_count == Some(0) {
+            anyhow::bail!("--shard-count must be greater than 0");
+        }
+        if self.namespace_concurrency == Some(0) {
+            anyhow::bail!("Namespace concurrency must be greater than 0");
+        }
+        if self.max_concurrent_reconciles == Some(0) {
+            anyhow::bail!("Maximum number of concurrent reconciles must be greater than 0");
+        }
+        if self.fair_namespace_queue && self.max_concurrent_reconciles.is_none() {
+            anyhow::bail!(
+                "--max-concurrent-reconciles must be set to use the fair namespace queue"
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use rstest::rstest;
+
+    use super::*;
+
+    fn parse(args: &[&str]) -> I2GArgs {
+        I2GArgs::parse_from(
+            ["i2g-operator", "--default-gateway-name", "gw"]
+                .into_iter()
+                .chain(args.iter().copied()),
+        )
+    }
+
+    #[rstest]
+    #[case(&["--default-request-timeout", "10s", "--default-backend-request-timeout", "10s"], true)]
+    #[case(&["--default-request-timeout", "10s", "--default-backend-request-timeout", "1m"], false)]
+    #[case(&["--default-backend-request-timeout", "1m"], true)]
+    fn default_timeouts(#[case] args: &[&str], #[case] valid: bool) {
+        assert_eq!(parse(args).validate().is_ok(), valid);
+    }
+
+    #[rstest]
+    #[case(&["--shadow-prefix", "shadow-", "--shadow-gateway-name", "gw"], true)]
+    #[case(&["--shadow-prefix", "", "--shadow-gateway-name", "gw"], false)]
+    fn shadow_prefix(#[case] args: &[&str], #[case] valid: bool) {
+        assert_eq!(parse(args).validate().is_ok(), valid);
+    }
+
+    #[rstest]
+    #[case(&["--fair-namespace-queue", "--max-concurrent-reconciles", "8"], true)]
+    #[case(&["--fair-namespace-queue"], false)]
+    fn fair_namespace_queue(#[case] args: &[&str], #[case] valid: bool) {
+        assert_eq!(parse(args).validate().is_ok(), valid);
+    }
 }
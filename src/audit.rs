@@ -0,0 +1,104 @@
+use std::sync::Arc;
+
+use k8s_openapi::api::networking::v1::Ingress;
+use kube::{Api, ResourceExt, api::ListParams};
+
+use i2g_operator::providers::unsupported_annotations;
+
+use crate::{args::OutputFormat, ctx::Context};
+
+/// Translation report for a single Ingress.
+#[derive(Debug, serde::Serialize)]
+pub struct AuditEntry {
+    pub namespace: String,
+    pub name: String,
+    pub translatable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skip_reason: Option<String>,
+    pub route_count: usize,
+    pub warnings: Vec<String>,
+    pub unsupported_annotations: Vec<String>,
+}
+
+async fn audit_ingress(ingress: &Ingress, ctx: Arc<Context>) -> AuditEntry {
+    let mut entry = AuditEntry {
+        namespace: ingress.namespace().unwrap_or_default(),
+        name: ingress.name_any(),
+        translatable: false,
+        skip_reason: None,
+        route_count: 0,
+        warnings: vec![],
+        unsupported_annotations: unsupported_annotations(ctx.provider(ingress), ingress),
+    };
+    match crate::skip_reason(ingress, &ctx).await {
+        Ok(Some((_, reason))) => {
+            entry.skip_reason = Some(reason);
+            return entry;
+        }
+        Ok(None) => {}
+        Err(err) => {
+            entry.skip_reason = Some(err.to_string());
+            return entry;
+        }
+    }
+    match crate::translate(ingress, ctx).await {
+        Ok(translation) => {
+            entry.route_count = translation.route_count();
+            entry.translatable = entry.route_count > 0;
+            entry.warnings = translation.warnings;
+        }
+        Err(err) => {
+            entry.skip_reason = Some(err.to_string());
+        }
+    }
+    entry
+}
+
+fn print_table(entries: &[AuditEntry]) {
+    println!(
+        "{:<20} {:<40} {:<12} {:<6} {:<8} {:<11} DETAILS",
+        "NAMESPACE", "NAME", "TRANSLATE", "ROUTES", "WARNINGS", "UNSUPPORTED"
+    );
+    for entry in entries {
+        let details = entry
+            .skip_reason
+            .iter()
+            .chain(entry.warnings.iter())
+            .chain(entry.unsupported_annotations.iter())
+            .cloned()
+            .collect::<Vec<_>>()
+            .join("; ");
+        println!(
+            "{:<20} {:<40} {:<12} {:<6} {:<8} {:<11} {}",
+            entry.namespace,
+            entry.name,
+            if entry.translatable { "yes" } else { "no" },
+            entry.route_count,
+            entry.warnings.len(),
+            entry.unsupported_annotations.len(),
+            details
+        );
+    }
+}
+
+/// Print translation report for all Ingresses in the cluster.
+///
+/// This is read-only: generated routes are never applied.
+pub async fn run(ctx: Arc<Context>, output: OutputFormat) -> anyhow::Result<()> {
+    let mut ingresses = Api::<Ingress>::all(ctx.client.clone())
+        .list(&ListParams::default())
+        .await?
+        .items;
+    ingresses.sort_by_key(|ingress| (ingress.namespace(), ingress.name_any()));
+
+    let mut entries = vec![];
+    for ingress in &ingresses {
+        entries.push(audit_ingress(ingress, ctx.clone()).await);
+    }
+
+    match output {
+        OutputFormat::Table => print_table(&entries),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&entries)?),
+    }
+    Ok(())
+}
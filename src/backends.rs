@@ -0,0 +1,213 @@
+use std::str::FromStr;
+
+use k8s_openapi::api::networking::v1::ServiceBackendPort;
+
+use crate::err::I2GError;
+
+/// Maximum weight of a backendRef allowed by gateway-api.
+pub const MAX_BACKEND_WEIGHT: i32 = 1_000_000;
+
+/// Parse a port number, rejecting values outside of `1..=65535`.
+/// Returns `None` if the value isn't numeric at all.
+fn parse_port_number(raw: &str) -> Option<Result<i32, I2GError>> {
+    let digits = raw.strip_prefix(['-', '+']).unwrap_or(raw);
+    if digits.is_empty() || !digits.bytes().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    match raw.parse::<i32>() {
+        Ok(number) if (1..=65535).contains(&number) => Some(Ok(number)),
+        _ => Some(Err(I2GError::ParseError(format!(
+            "Port {raw} must be between 1 and 65535"
+        )))),
+    }
+}
+
+/// Backend reference parsed from an annotation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackendSpec {
+    pub name: String,
+    pub port: ServiceBackendPort,
+    pub weight: Option<i32>,
+}
+
+/// Parse backend from string.
+/// The string should be in the following format:
+/// `service:port[@weight]`, where port is either a number or a port name.
+impl FromStr for BackendSpec {
+    type Err = I2GError;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let raw = raw.trim();
+        let (backend, weight) = match raw.split_once('@') {
+            Some((backend, weight)) => {
+                let weight = weight.trim().parse::<i32>().map_err(|_| {
+                    I2GError::ParseError(format!("Invalid weight in backend '{raw}'"))
+                })?;
+                if !(0..=MAX_BACKEND_WEIGHT).contains(&weight) {
+                    return Err(I2GError::ParseError(format!(
+                        "Weight of backend '{raw}' must be between 0 and {MAX_BACKEND_WEIGHT}"
+                    )));
+                }
+                (backend, Some(weight))
+            }
+            None => (raw, None),
+        };
+        let Some((name, port)) = backend.split_once(':') else {
+            return Err(I2GError::ParseError(format!(
+                "Backend '{raw}' should be in format service:port[@weight]"
+            )));
+        };
+        if name.is_empty() || port.is_empty() {
+            return Err(I2GError::ParseError(format!(
+                "Backend '{raw}' should be in format service:port[@weight]"
+            )));
+        }
+        let port = match parse_port_number(port).transpose()? {
+            Some(number) => ServiceBackendPort {
+                number: Some(number),
+                name: None,
+            },
+            None => ServiceBackendPort {
+                number: None,
+                name: Some(port.to_string()),
+            },
+        };
+        Ok(BackendSpec {
+            name: name.to_string(),
+            port,
+            weight,
+        })
+    }
+}
+
+/// Parse a comma-separated list of backends.
+pub fn parse_backend_list(raw: &str) -> Result<Vec<BackendSpec>, I2GError> {
+    let backends = raw
+        .split(',')
+        .filter(|item| !item.trim().is_empty())
+        .map(BackendSpec::from_str)
+        .collect::<Result<Vec<_>, _>>()?;
+    if backends.is_empty() {
+        return Err(I2GError::ParseError(format!(
+            "No backends found in '{raw}'"
+        )));
+    }
+    if backends.iter().all(|backend| backend.weight == Some(0)) {
+        return Err(I2GError::ParseError(format!(
+            "All backends in '{raw}' have zero weight"
+        )));
+    }
+    Ok(backends)
+}
+
+/// Named service port used for offline translation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedPort {
+    pub service: String,
+    pub name: String,
+    pub number: i32,
+}
+
+/// Parse named port from string.
+/// The string should be in the following format: `service:name=number`.
+impl FromStr for NamedPort {
+    type Err = I2GError;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            I2GError::ParseError(format!(
+                "Port '{raw}' should be in format service:name=number"
+            ))
+        };
+        let (service_port, number) = raw.trim().split_once('=').ok_or_else(invalid)?;
+        let (service, name) = service_port.split_once(':').ok_or_else(invalid)?;
+        if service.is_empty() || name.is_empty() {
+            return Err(invalid());
+        }
+        let number = parse_port_number(number).ok_or_else(invalid)??;
+        Ok(NamedPort {
+            service: service.to_string(),
+            name: name.to_string(),
+            number,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn numbered(name: &str, number: i32, weight: Option<i32>) -> BackendSpec {
+        BackendSpec {
+            name: name.to_string(),
+            port: ServiceBackendPort {
+                number: Some(number),
+                name: None,
+            },
+            weight,
+        }
+    }
+
+    #[rstest]
+    #[case("svc:80", numbered("svc", 80, None))]
+    #[case(" svc:8080@30 ", numbered("svc", 8080, Some(30)))]
+    #[case("svc:1@0", numbered("svc", 1, Some(0)))]
+    #[case("svc:65535", numbered("svc", 65535, None))]
+    #[case(
+        "svc:http@5",
+        BackendSpec {
+            name: "svc".to_string(),
+            port: ServiceBackendPort { number: None, name: Some("http".to_string()) },
+            weight: Some(5),
+        }
+    )]
+    fn backend_spec_valid(#[case] raw: &str, #[case] expected: BackendSpec) {
+        assert_eq!(BackendSpec::from_str(raw).unwrap(), expected);
+    }
+
+    #[rstest]
+    #[case("svc")]
+    #[case(":80")]
+    #[case("svc:")]
+    #[case("svc:0")]
+    #[case("svc:-1")]
+    #[case("svc:65536")]
+    #[case("svc:99999999999")]
+    #[case("svc:80@abc")]
+    #[case("svc:80@-1")]
+    #[case("svc:80@1000001")]
+    fn backend_spec_invalid(#[case] raw: &str) {
+        assert!(BackendSpec::from_str(raw).is_err());
+    }
+
+    #[rstest]
+    #[case("a:80,b:81@2", 2)]
+    #[case("a:80,,", 1)]
+    fn backend_list_valid(#[case] raw: &str, #[case] len: usize) {
+        assert_eq!(parse_backend_list(raw).unwrap().len(), len);
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case(" , ")]
+    #[case("a:80@0,b:81@0")]
+    #[case("a:80,b:0")]
+    fn backend_list_invalid(#[case] raw: &str) {
+        assert!(parse_backend_list(raw).is_err());
+    }
+
+    #[rstest]
+    #[case("svc:http=8080", Some(8080))]
+    #[case("svc:http=65535", Some(65535))]
+    #[case("svc:http=0", None)]
+    #[case("svc:http=-80", None)]
+    #[case("svc:http=65536", None)]
+    #[case("svc:http=abc", None)]
+    #[case("svc=8080", None)]
+    #[case(":http=8080", None)]
+    fn named_port(#[case] raw: &str, #[case] expected: Option<i32>) {
+        let parsed = NamedPort::from_str(raw).ok().map(|port| port.number);
+        assert_eq!(parsed, expected);
+    }
+}
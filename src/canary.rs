@@ -0,0 +1,131 @@
+use std::collections::HashSet;
+
+use k8s_openapi::api::networking::v1::{Ingress, IngressBackend};
+use kube::{
+    ResourceExt,
+    runtime::reflector::{ObjectRef, Store},
+};
+
+use crate::consts;
+
+/// Default total weight of nginx canary backends.
+const DEFAULT_WEIGHT_TOTAL: i32 = 100;
+/// Header value routing requests to the canary if `canary-by-header-value` isn't set.
+const ALWAYS: &str = "always";
+
+/// Backend of a canary Ingress for a path of the primary Ingress.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Canary {
+    /// Name of the canary Ingress.
+    pub ingress_name: String,
+    pub path: Option<String>,
+    pub backend: IngressBackend,
+    /// Weight of the canary backend out of `weight_total`.
+    pub weight: Option<i32>,
+    pub weight_total: i32,
+    /// Header name and value routing requests to the canary backend.
+    pub header: Option<(String, String)>,
+}
+
+/// Whether the Ingress is an nginx canary of another Ingress.
+pub fn is_canary(ingress: &Ingress) -> bool {
+    ingress
+        .annotations()
+        .get(consts::NGINX_CANARY)
+        .is_some_and(|v| v.to_lowercase() == "true")
+}
+
+fn hosts(ingress: &Ingress) -> HashSet<&str> {
+    ingress
+        .spec
+        .iter()
+        .flat_map(|spec| spec.rules.iter().flatten())
+        .map(|rule| rule.host.as_deref().unwrap_or_default())
+        .collect()
+}
+
+fn parse_weight(ingress: &Ingress, key: &str, warnings: &mut Vec<String>) -> Option<i32> {
+    let raw = ingress.annotations().get(key)?;
+    match raw.parse::<i32>() {
+        Ok(weight) if weight >= 0 => Some(weight),
+        _ => {
+            warnings.push(format!(
+                "Ignoring invalid {key} annotation of canary Ingress {}: {raw}",
+                ingress.name_any()
+            ));
+            None
+        }
+    }
+}
+
+/// Find canary backends for the host of the primary Ingress.
+/// Invalid canary annotations are ignored, the reason is added to `warnings`.
+pub fn canaries_for_host(
+    ingresses: &Store<Ingress>,
+    primary: &Ingress,
+    host: &str,
+    warnings: &mut Vec<String>,
+) -> Vec<Canary> {
+    let mut canaries = vec![];
+    let mut candidates = ingresses
+        .state()
+        .into_iter()
+        .filter(|ingress| ingress.namespace() == primary.namespace())
+        .filter(|ingress| ingress.name_any() != primary.name_any() && is_canary(ingress))
+        .collect::<Vec<_>>();
+    candidates.sort_by_key(|ingress| ingress.name_any());
+    for ingress in candidates {
+        let annotations = ingress.annotations();
+        let weight = parse_weight(&ingress, consts::NGINX_CANARY_WEIGHT, warnings);
+        let weight_total = parse_weight(&ingress, consts::NGINX_CANARY_WEIGHT_TOTAL, warnings)
+            .filter(|total| *total > 0)
+            .unwrap_or(DEFAULT_WEIGHT_TOTAL);
+        let header = annotations.get(consts::NGINX_CANARY_BY_HEADER).map(|name| {
+            let value = annotations
+                .get(consts::NGINX_CANARY_BY_HEADER_VALUE)
+                .cloned()
+                .unwrap_or_else(|| ALWAYS.to_string());
+            (name.clone(), value)
+        });
+        let paths = ingress
+            .spec
+            .iter()
+            .flat_map(|spec| spec.rules.iter().flatten())
+            .filter(|rule| rule.host.as_deref().unwrap_or_default() == host)
+            .filter_map(|rule| rule.http.as_ref())
+            .flat_map(|http| &http.paths);
+        for path in paths {
+            canaries.push(Canary {
+                ingress_name: ingress.name_any(),
+                path: path.path.clone(),
+                backend: path.backend.clone(),
+                weight: weight.map(|weight| weight.min(weight_total)),
+                weight_total,
+                header: header.clone(),
+            });
+        }
+    }
+    canaries
+}
+
+/// Find primary Ingresses sharing hosts with the canary Ingress.
+pub fn primaries_for_canary(
+    ingresses: &Store<Ingress>,
+    canary: &Ingress,
+) -> Vec<ObjectRef<Ingress>> {
+    if !is_canary(canary) {
+        return vec![];
+    }
+    let canary_hosts = hosts(canary);
+    ingresses
+        .state()
+        .into_iter()
+        .filter(|ingress| ingress.namespace() == canary.namespace() && !is_canary(ingress))
+        .filter(|ingress| {
+            hosts(ingress)
+                .iter()
+                .any(|host| canary_hosts.contains(host))
+        })
+        .map(|ingress| ObjectRef::from_obj(ingress.as_ref()))
+        .collect()
+}
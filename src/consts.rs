@@ -1,18 +1,223 @@
 /// This annotation will split ingress rules to a new HTTPSerice for each rule.
 /// Of the ingress. It's usefull because HTTPRoute resource can only have up to 16
 /// rules.
-pub const SPLIT_ROUTES: &'static str = "i2g-operator/split-paths";
+pub const SPLIT_ROUTES: &str = "i2g-operator/split-paths";
 /// This annotation will mark an ingress to be translated by the operator.
 /// If it's false the operator will skip the ingress in any way.
-pub const TRANSLATE_INGRESS: &'static str = "i2g-operator/translate";
+pub const TRANSLATE_INGRESS: &str = "i2g-operator/translate";
+
+/// Merge HTTPRoutes of hosts with identical paths and backends
+/// into a single route with several hostnames.
+pub const CONSOLIDATE_HOSTS: &str = "i2g-operator/consolidate-hosts";
 
 /// Override gateway name annotation.
-pub const GATEWAY_NAME: &'static str = "i2g-operator/gateway-name";
+/// Comma-separated list of `name` or `namespace/name` attaches routes to several gateways.
+pub const GATEWAY_NAME: &str = "i2g-operator/gateway-name";
+/// Label selector of gateways routes are attached to, e.g. `app=edge`.
+/// Used unless the gateway name annotation is set.
+pub const GATEWAY_SELECTOR: &str = "i2g-operator/gateway-selector";
+/// Namespace annotation with default gateways of Ingresses in the namespace,
+/// in the same format as the gateway name annotation, e.g. `infra/edge-gw`.
+/// Used instead of operator defaults unless the Ingress sets gateway name or selector.
+pub const DEFAULT_GATEWAY: &str = "i2g-operator/default-gateway";
+/// Namespace of Services referenced by the Ingress, for shared services in another namespace.
+/// Sets the namespace of all backendRefs of generated routes.
+pub const BACKEND_NAMESPACE: &str = "i2g-operator/backend-namespace";
 /// Override gateway namespace annotation.
-pub const GATEWAY_NAMESPACE: &'static str = "i2g-operator/gateway-namespace";
+pub const GATEWAY_NAMESPACE: &str = "i2g-operator/gateway-namespace";
+
+/// Template of generated route names, e.g. `{ingress}-{host}-{index}`.
+/// Placeholders: `{ingress}`, `{namespace}`, `{host}`, `{kind}` and `{index}`.
+pub const ROUTE_NAME_TEMPLATE: &str = "i2g-operator/route-name-template";
 
 /// What section to use for resulting Routes.
-pub const DESIRED_SECTION: &'static str = "i2g-operator/section-name";
+pub const DESIRED_SECTION: &str = "i2g-operator/section-name";
+
+pub const HEADER_FILTERS_PREFIX: &str = "i2g-operator-matches-header/";
+pub const QUERY_FILTERS_PREFIX: &str = "i2g-operator-matches-query/";
+/// Prefix of annotations with HTTP methods to match, e.g. `i2g-operator/method-filters/1: GET|POST`.
+pub const METHOD_FILTERS_PREFIX: &str = "i2g-operator/method-filters/";
+
+/// Override path match types for particular paths.
+/// Format: `/path=Type,/other=Type`, where type is `Exact`, `Prefix` or `RegularExpression`.
+/// Entries may be separated by newlines instead, commas in paths are kept either way.
+/// Paths are listed in the value, because annotation keys can't contain them.
+pub const PATH_TYPES: &str = "i2g-operator/path-type";
+
+/// Comma-separated list of weighted backends for generated TCPRoutes.
+/// Format: `service:port[@weight],service2:port[@weight]`.
+/// Overrides the Ingress' defaultBackend.
+pub const TCP_BACKENDS: &str = "i2g-operator/tcp-backends";
+
+/// Protocol of non-HTTP Ingress rules: `tcp` (default) generates TCPRoutes,
+/// `udp` generates UDPRoutes.
+pub const PROTOCOL: &str = "i2g-operator/protocol";
+
+/// Port of the gateway listener generated TCPRoutes and UDPRoutes are attached to.
+/// Replaces the default TCP section, unless the section name annotation is set.
+pub const LISTENER_PORT: &str = "i2g-operator/listener-port";
+
+/// Attach generated TCPRoutes and UDPRoutes to default Gateways instead of an explicit parent.
+/// Can't be combined with gateway or section annotations.
+pub const TCP_USE_DEFAULT_GATEWAYS: &str = "i2g-operator/tcp-use-default-gateways";
+
+/// If true, all paths of the Ingress are routed to the maintenance backend.
+pub const MAINTENANCE: &str = "i2g-operator/maintenance";
+/// Backend used in maintenance mode. Format: `service:port`.
+pub const MAINTENANCE_BACKEND: &str = "i2g-operator/maintenance-backend";
+
+/// Backend receiving copies of requests of every generated HTTPRoute rule.
+/// Format: `service:port[@percent]`, all requests are mirrored if percent is not set.
+pub const MIRROR_BACKEND: &str = "i2g-operator/mirror-backend";
+
+/// JSON maps of labels and annotations added to all routes generated from the Ingress,
+/// e.g. `{"external-dns.alpha.kubernetes.io/ttl": "60"}`.
+pub const ROUTE_LABELS: &str = "i2g-operator/route-labels";
+pub const ROUTE_ANNOTATIONS: &str = "i2g-operator/route-annotations";
+
+/// Preview the translation: generated objects are validated with a server-side
+/// dry-run apply and the result is reported as Events. Nothing is persisted,
+/// routes generated before the annotation was set are kept.
+pub const DRY_RUN: &str = "i2g-operator/dry-run";
+
+/// Comma-separated list of ExtensionRef filters attached to every generated HTTPRoute rule.
+/// Format: `group/kind/name,group2/kind2/name2`.
+pub const EXTENSION_FILTERS: &str = "i2g-operator/extension-filter";
+
+/// Deprecated annotation specifying the ingress class, used if `spec.ingressClassName` is not set.
+pub const LEGACY_INGRESS_CLASS: &str = "kubernetes.io/ingress.class";
+
+/// TLS passthrough of the Ingress. Such Ingresses are translated to TLSRoutes.
+pub const SSL_PASSTHROUGH: &str = "i2g-operator/ssl-passthrough";
+/// ingress-nginx counterpart of [`SSL_PASSTHROUGH`]. The native annotation takes precedence.
+pub const NGINX_SSL_PASSTHROUGH: &str = "nginx.ingress.kubernetes.io/ssl-passthrough";
+
+/// Redirect plain HTTP requests to HTTPS for TLS hosts of the Ingress.
+/// Generates an additional HTTPRoute attached to the HTTP listener.
+pub const SSL_REDIRECT: &str = "i2g-operator/ssl-redirect";
+/// ingress-nginx counterpart of [`SSL_REDIRECT`]. The native annotation takes precedence.
+pub const NGINX_SSL_REDIRECT: &str = "nginx.ingress.kubernetes.io/ssl-redirect";
+/// Port of the HTTPS redirect location (e.g. `8443`).
+/// The default port of the https scheme is used if it's not set.
+pub const REDIRECT_PORT: &str = "i2g-operator/redirect-port";
+
+/// Replace the matched path prefix of every HTTP rule before forwarding requests.
+/// Exact paths are replaced completely. Regular expression paths are not supported.
+pub const REWRITE_PREFIX: &str = "i2g-operator/rewrite-prefix";
+/// ingress-nginx rewrite annotation. Static targets replace the full path.
+/// Capture groups are only supported in the `/path(/|$)(.*)` => `/$2` form,
+/// which strips the path prefix. The native annotation takes precedence.
+pub const NGINX_REWRITE_TARGET: &str = "nginx.ingress.kubernetes.io/rewrite-target";
+
+/// Timeout for requests of generated HTTPRoute rules (e.g. `30s`).
+/// Overrides `--default-request-timeout`.
+pub const REQUEST_TIMEOUT: &str = "i2g-operator/request-timeout";
+/// ingress-nginx upstream timeouts in seconds. The greater of them
+/// is used as the backend request timeout of generated HTTPRoute rules.
+pub const NGINX_PROXY_READ_TIMEOUT: &str = "nginx.ingress.kubernetes.io/proxy-read-timeout";
+pub const NGINX_PROXY_SEND_TIMEOUT: &str = "nginx.ingress.kubernetes.io/proxy-send-timeout";
+
+/// Retry policy of generated HTTPRoute rules (experimental only).
+/// Format: `attempts=3,backoff=200ms,codes=502,503`. All keys are optional.
+pub const RETRIES: &str = "i2g-operator/retries";
+
+/// Protocol of backends of the Ingress. For `HTTPS` and `GRPCS`, a BackendTLSPolicy
+/// is generated for every Service of the Ingress, so gateways use TLS to reach them.
+pub const BACKEND_PROTOCOL: &str = "i2g-operator/backend-protocol";
+/// ingress-nginx counterpart of [`BACKEND_PROTOCOL`]. The native annotation takes precedence.
+pub const NGINX_BACKEND_PROTOCOL: &str = "nginx.ingress.kubernetes.io/backend-protocol";
+/// Hostname backend certificates are validated against.
+/// Defaults to `{service}.{namespace}.svc`.
+pub const BACKEND_TLS_HOSTNAME: &str = "i2g-operator/backend-tls-hostname";
+/// Name of the ConfigMap with CA certificates (`ca.crt`) backend certificates
+/// are validated with. System CA certificates are used by default.
+pub const BACKEND_TLS_CA: &str = "i2g-operator/backend-tls-ca";
+
+/// ingress-nginx session affinity annotations. Cookie affinity is translated to
+/// `sessionPersistence` of HTTPRoute rules (experimental only).
+pub const NGINX_AFFINITY: &str = "nginx.ingress.kubernetes.io/affinity";
+pub const NGINX_SESSION_COOKIE_NAME: &str = "nginx.ingress.kubernetes.io/session-cookie-name";
+pub const NGINX_SESSION_COOKIE_MAX_AGE: &str = "nginx.ingress.kubernetes.io/session-cookie-max-age";
+
+/// Traefik sticky session annotations, translated like [`NGINX_AFFINITY`].
+pub const TRAEFIK_STICKY_COOKIE: &str = "traefik.ingress.kubernetes.io/service.sticky.cookie";
+pub const TRAEFIK_STICKY_COOKIE_NAME: &str =
+    "traefik.ingress.kubernetes.io/service.sticky.cookie.name";
+pub const TRAEFIK_STICKY_COOKIE_MAX_AGE: &str =
+    "traefik.ingress.kubernetes.io/service.sticky.cookie.maxage";
+
+/// HAProxy counterparts of [`SSL_PASSTHROUGH`] and [`SSL_REDIRECT`].
+pub const HAPROXY_SSL_PASSTHROUGH: &str = "haproxy.org/ssl-passthrough";
+pub const HAPROXY_SSL_REDIRECT: &str = "haproxy.org/ssl-redirect";
+/// HAProxy backend timeout (e.g. `30s`), translated to the backend request timeout.
+pub const HAPROXY_TIMEOUT_SERVER: &str = "haproxy.org/timeout-server";
+/// HAProxy cookie persistence, the value is the name of the cookie.
+pub const HAPROXY_COOKIE_PERSISTENCE: &str = "haproxy.org/cookie-persistence";
+
+/// ingress-nginx canary annotations. Canary Ingresses are not translated on their own,
+/// their backends are merged into HTTPRoutes of primary Ingresses with the same hosts.
+pub const NGINX_CANARY: &str = "nginx.ingress.kubernetes.io/canary";
+pub const NGINX_CANARY_WEIGHT: &str = "nginx.ingress.kubernetes.io/canary-weight";
+pub const NGINX_CANARY_WEIGHT_TOTAL: &str = "nginx.ingress.kubernetes.io/canary-weight-total";
+pub const NGINX_CANARY_BY_HEADER: &str = "nginx.ingress.kubernetes.io/canary-by-header";
+pub const NGINX_CANARY_BY_HEADER_VALUE: &str = "nginx.ingress.kubernetes.io/canary-by-header-value";
+
+/// Port of HTTP listeners used for redirects when `--default-section-http` isn't set.
+pub const HTTP_LISTENER_PORT: i32 = 80;
+
+/// Prefixes of controller-specific annotations which the operator doesn't translate.
+pub const VENDOR_ANNOTATION_PREFIXES: &[&str] = &[
+    "nginx.ingress.kubernetes.io/",
+    "traefik.ingress.kubernetes.io/",
+    "haproxy.org/",
+    "haproxy.router.openshift.io/",
+    "alb.ingress.kubernetes.io/",
+    "konghq.com/",
+];
+
+/// Canary annotations, translated regardless of `--compat-provider`.
+pub const CANARY_ANNOTATIONS: &[&str] = &[
+    NGINX_CANARY,
+    NGINX_CANARY_WEIGHT,
+    NGINX_CANARY_WEIGHT_TOTAL,
+    NGINX_CANARY_BY_HEADER,
+    NGINX_CANARY_BY_HEADER_VALUE,
+];
+
+/// Comma-separated list of controller annotations of the Ingress which can't be translated.
+/// Set by the operator, removed once all annotations are translated.
+pub const UNTRANSLATED_ANNOTATIONS: &str = "i2g-operator/untranslated-annotations";
+/// Comma-separated sorted list of routes generated from the Ingress.
+/// Set by the operator after routes are applied, removed once the Ingress has no routes.
+pub const GENERATED_ROUTES: &str = "i2g-operator/generated-routes";
+
+/// Hash of the object as it was last applied by the operator.
+/// Patches are skipped while it matches and the live object wasn't changed.
+pub const APPLIED_HASH: &str = "i2g-operator/applied-hash";
+
+/// Version of the operator which last reconciled a route.
+pub const OPERATOR_VERSION: &str = "i2g-operator/version";
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Field manager used for server-side apply of generated routes.
+pub const FIELD_MANAGER: &str = "ingress-to-gateway-controller";
+
+/// Label marking routes managed by the operator.
+pub const MANAGED_BY_LABEL: &str = "app.kubernetes.io/managed-by";
+pub const MANAGED_BY_VALUE: &str = "i2g-operator";
+
+/// Name of the Ingress a route was generated from.
+/// Used to find orphaned routes when Ingress rules change.
+pub const OWNER_INGRESS: &str = "i2g-operator/owner-ingress";
+/// Comma-separated list of Ingresses needing an object shared by Ingresses,
+/// like a ReferenceGrant, as `namespace/name`.
+pub const OWNER_INGRESSES: &str = "i2g-operator/owner-ingresses";
+/// Comma-separated sorted list of shared objects owned by the Ingress, as `Kind:namespace/name`.
+/// Set by the operator before they're applied, so unused ones are released without listing them.
+pub const SHARED_OBJECTS: &str = "i2g-operator/shared-objects";
+
+/// Finalizer removing generated routes when `--link-to-ingress` is disabled.
+pub const CLEANUP_FINALIZER: &str = "i2g-operator/cleanup";
 
-pub const HEADER_FILTERS_PREFIX: &'static str = "i2g-operator-matches-header/";
-pub const QUERY_FILTERS_PREFIX: &'static str = "i2g-operator-matches-query/";
+/// Label marking routes created in shadow mode.
+pub const SHADOW_LABEL: &str = "i2g-operator/shadow";
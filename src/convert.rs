@@ -0,0 +1,92 @@
+use std::{io::Read, path::Path, sync::Arc};
+
+use k8s_openapi::api::networking::v1::Ingress;
+use kube::{
+    ResourceExt,
+    runtime::{reflector, watcher},
+};
+use serde::Deserialize;
+
+use crate::{canary, ctx::Context, snapshot::clean};
+
+/// Read Ingresses from a multi-document YAML. Other kinds are ignored.
+fn read_ingresses(raw: &str) -> anyhow::Result<Vec<Ingress>> {
+    let mut ingresses = vec![];
+    for document in serde_yaml::Deserializer::from_str(raw) {
+        let value = serde_yaml::Value::deserialize(document)?;
+        if value.get("kind").and_then(|kind| kind.as_str()) != Some("Ingress") {
+            continue;
+        }
+        let mut ingress: Ingress = serde_yaml::from_value(value)?;
+        if ingress.namespace().is_none() {
+            ingress.metadata.namespace = Some("default".to_string());
+        }
+        ingresses.push(ingress);
+    }
+    Ok(ingresses)
+}
+
+/// Translate Ingresses from the file and print generated routes.
+///
+/// Named service ports are resolved with `--port` mappings,
+/// so no cluster connection is needed.
+pub async fn run(ctx: Arc<Context>, file: &Path) -> anyhow::Result<()> {
+    let raw = if file == Path::new("-") {
+        let mut raw = String::new();
+        std::io::stdin().read_to_string(&mut raw)?;
+        raw
+    } else {
+        std::fs::read_to_string(file)?
+    };
+
+    let ingresses = read_ingresses(&raw)?;
+    // Canaries of translated Ingresses are looked up in the same file.
+    let (store, mut writer) = reflector::store();
+    for ingress in &ingresses {
+        writer.apply_watcher_event(&watcher::Event::Apply(ingress.clone()));
+    }
+    let _ = ctx.ingresses.set(store);
+
+    let mut documents = vec![];
+    for ingress in ingresses {
+        if canary::is_canary(&ingress) {
+            continue;
+        }
+        let translation = crate::translate(&ingress, ctx.clone()).await?;
+        for warning in &translation.warnings {
+            eprintln!(
+                "{}/{}: {warning}",
+                ingress.namespace().unwrap_or_default(),
+                ingress.name_any()
+            );
+        }
+        // Owner references can't point to Ingresses which don't exist in the cluster.
+        for mut route in translation.http_routes {
+            clean(&mut route);
+            documents.push(serde_yaml::to_string(&route)?);
+        }
+        for mut route in translation.tcp_routes {
+            clean(&mut route);
+            documents.push(serde_yaml::to_string(&route)?);
+        }
+        for mut route in translation.udp_routes {
+            clean(&mut route);
+            documents.push(serde_yaml::to_string(&route)?);
+        }
+        for mut route in translation.tls_routes {
+            clean(&mut route);
+            documents.push(serde_yaml::to_string(&route)?);
+        }
+        for grant in translation.reference_grants {
+            documents.push(serde_yaml::to_string(&grant)?);
+        }
+        for policy in translation.backend_tls_policies {
+            documents.push(serde_yaml::to_string(&policy)?);
+        }
+        for backend in translation.envoy_backends {
+            documents.push(serde_yaml::to_string(&backend)?);
+        }
+    }
+    print!("{}", documents.join("---\n"));
+    Ok(())
+}
@@ -0,0 +1,97 @@
+//! Custom resources written by the operator.
+
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Time;
+use kube::CustomResource;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::args::CompatProvider;
+
+/// Translation state of the Ingress with the same name,
+/// so migration progress can be followed with `kubectl get ingresstranslations`.
+#[derive(CustomResource, Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[kube(
+    group = "i2g.intree.com",
+    version = "v1alpha1",
+    kind = "IngressTranslation",
+    shortname = "itr",
+    namespaced,
+    status = "IngressTranslationStatus",
+    printcolumn = r#"{"name": "Ingress", "type": "string", "jsonPath": ".spec.ingress"}"#,
+    printcolumn = r#"{"name": "Routes", "type": "integer", "jsonPath": ".status.routeCount"}"#,
+    printcolumn = r#"{"name": "Last Sync", "type": "date", "jsonPath": ".status.lastSyncTime"}"#
+)]
+#[serde(rename_all = "camelCase")]
+pub struct IngressTranslationSpec {
+    /// Name of the translated Ingress.
+    pub ingress: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IngressTranslationStatus {
+    /// Names of routes generated from the Ingress.
+    #[serde(default)]
+    pub routes: Vec<String>,
+    /// Number of generated routes.
+    #[serde(default)]
+    pub route_count: usize,
+    /// When the routes were last applied.
+    pub last_sync_time: Option<Time>,
+    /// Rules which were skipped and objects which couldn't be applied.
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    /// Annotations of other ingress controllers which can't be translated.
+    #[serde(default)]
+    pub untranslated_annotations: Vec<String>,
+}
+
+/// Translation settings of Ingresses with an IngressClass whose `spec.parameters`
+/// reference this object. Unset fields fall back to operator settings.
+#[derive(CustomResource, Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[kube(
+    group = "i2g.intree.com",
+    version = "v1alpha1",
+    kind = "I2GClassConfig",
+    shortname = "i2gcc"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct I2GClassConfigSpec {
+    /// Gateway routes are attached to, unless the Ingress sets gateway annotations.
+    pub gateway_name: Option<String>,
+    /// Namespace of the gateway. Defaults to the default gateway namespace.
+    pub gateway_namespace: Option<String>,
+    /// Gateway listener of all routes, unless the Ingress sets the section name annotation.
+    pub section_name: Option<String>,
+    /// Ingress controller whose annotations are translated.
+    pub compat_provider: Option<CompatProvider>,
+}
+
+/// Envoy Gateway `Backend` routing traffic to a hostname outside the cluster.
+/// Generated for ExternalName Services with `--external-name-backends envoy-backend`.
+/// Only the fields used by the operator are declared.
+#[derive(CustomResource, Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[kube(
+    group = "gateway.envoyproxy.io",
+    version = "v1alpha1",
+    kind = "Backend",
+    root = "EnvoyBackend",
+    namespaced
+)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvoyBackendSpec {
+    pub endpoints: Vec<EnvoyBackendEndpoint>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvoyBackendEndpoint {
+    pub fqdn: Option<EnvoyBackendFqdn>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvoyBackendFqdn {
+    pub hostname: String,
+    pub port: i32,
+}
@@ -1,33 +1,523 @@
-use std::sync::{Arc, atomic::AtomicBool};
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex, OnceLock, RwLock,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
 
 use clap::Parser;
+use futures::StreamExt;
+use gateway_api::apis::{
+    experimental::{
+        httproutes::HTTPRoute, tcproutes::TCPRoute, tlsroutes::TLSRoute, udproutes::UDPRoute,
+    },
+    standard::gateways::Gateway,
+};
+use k8s_openapi::api::{
+    core::v1::{Namespace, Service},
+    networking::v1::{Ingress, IngressClass},
+};
+use kube::{
+    Api, Resource,
+    api::{ApiResource, DynamicObject, ListParams},
+    runtime::{
+        WatchStreamExt,
+        events::{Event, EventType, Recorder, Reporter},
+        reflector,
+        reflector::ObjectRef,
+        watcher,
+    },
+};
 use rand::distr::{Alphanumeric, SampleString};
+use tokio::sync::Semaphore;
+use tokio::time::Instant;
+use tower::limit::RateLimitLayer;
 
-use crate::args::I2GArgs;
+use crate::{
+    args::{Command, I2GArgs},
+    backends::NamedPort,
+    consts,
+    crd::I2GClassConfig,
+    fair_queue::FairQueue,
+    metrics::Metrics,
+    naming,
+    providers::AnnotationProvider,
+    settings::{ConfigFile, Settings},
+    utils,
+};
 
 #[derive(Clone)]
 pub struct Context {
     pub args: I2GArgs,
     pub client: kube::Client,
     pub is_leader: Arc<AtomicBool>,
+    /// When the leadership was acquired.
+    pub leader_since: Arc<Mutex<Option<Instant>>>,
     pub hostname: String,
+    /// Cache of namespaces matching `--namespace-label-selector`.
+    pub namespaces: Option<reflector::Store<Namespace>>,
+    /// Cache of services used to resolve named ports. Not available offline.
+    pub services: Option<reflector::Store<Service>>,
+    /// Cache of gateways used to resolve gateway selectors. Not available offline.
+    pub gateways: Option<reflector::Store<Gateway>>,
+    /// Caches of IngressClasses and their I2GClassConfig parameters,
+    /// used with `--ingress-class-parameters`.
+    pub ingress_classes: Option<reflector::Store<IngressClass>>,
+    pub class_configs: Option<reflector::Store<I2GClassConfig>>,
+    /// Caches of routes managed by the operator by kind, used to skip
+    /// patches which don't change anything. Empty offline.
+    pub managed_routes: HashMap<String, reflector::Store<DynamicObject>>,
+    /// Number of consecutive reconcile failures per Ingress.
+    pub failures: Arc<Mutex<HashMap<ObjectRef<Ingress>, u32>>>,
+    pub metrics: Metrics,
+    /// Reconcile permits per namespace, used with `--namespace-concurrency`.
+    pub namespace_semaphores: Arc<Mutex<HashMap<String, Arc<Semaphore>>>>,
+    /// Reconcile slots shared between namespaces, used with `--fair-namespace-queue`.
+    pub fair_queue: Option<FairQueue>,
+    /// Recorder of Events about translation results.
+    pub recorder: Recorder,
+    /// Named service ports used instead of API lookups by the `convert` subcommand.
+    pub offline_ports: Option<Vec<NamedPort>>,
+    /// Ingresses used to find canaries of translated Ingresses.
+    /// Set to the controller's store once it's started.
+    pub ingresses: Arc<OnceLock<reflector::Store<Ingress>>>,
+    /// Shard of namespaces reconciled by this instance, used with `--shard-count`.
+    pub shard_index: Option<u32>,
+    /// Settings which can be changed at runtime with `--config`.
+    pub settings: Arc<RwLock<Settings>>,
 }
 
 impl Context {
     pub async fn new() -> anyhow::Result<Self> {
         let args = I2GArgs::parse();
-        let client = kube::Client::try_default().await?;
+        args.validate()?;
+        if let Some(Command::Convert { ports, .. }) = &args.command {
+            let ports = ports.clone();
+            let ctx = Self::offline(args, ports)?;
+            ctx.settings().validate(&ctx.args)?;
+            return Ok(ctx);
+        }
+        if let Some(Command::Crd) = &args.command {
+            return Self::offline(args, vec![]);
+        }
+        let client = create_client(&args).await?;
+        let namespaces = match &args.namespace_label_selector {
+            Some(selector) => Some(watch_namespaces(client.clone(), selector).await?),
+            None => None,
+        };
+        let services = watch_services(client.clone(), &args);
+        let gateways = watch_gateways(client.clone());
+        let mut managed_routes = HashMap::from([watch_managed::<HTTPRoute>(client.clone())]);
+        if args.experimental {
+            managed_routes.extend([
+                watch_managed::<TCPRoute>(client.clone()),
+                watch_managed::<UDPRoute>(client.clone()),
+                watch_managed::<TLSRoute>(client.clone()),
+            ]);
+        }
+        let mut ctx = Self::with_client(args, client, namespaces, Some(services), None)?;
+        ctx.settings().validate(&ctx.args)?;
+        ctx.gateways = Some(gateways);
+        if ctx.args.ingress_class_parameters {
+            ctx.ingress_classes = Some(watch_cluster(ctx.client.clone(), "IngressClass"));
+            ctx.class_configs = Some(watch_cluster(ctx.client.clone(), "I2GClassConfig"));
+        }
+        ctx.managed_routes = managed_routes;
+        Ok(ctx)
+    }
+
+    /// Context for translation without a cluster connection.
+    /// Named service ports are resolved with the given port numbers.
+    pub fn offline(args: I2GArgs, ports: Vec<NamedPort>) -> anyhow::Result<Self> {
+        // The client is never used in offline mode.
+        let client = kube::Client::try_from(kube::Config::new("http://127.0.0.1:1".parse()?))?;
+        Self::with_client(args, client, None, None, Some(ports))
+    }
+
+    fn with_client(
+        args: I2GArgs,
+        client: kube::Client,
+        namespaces: Option<reflector::Store<Namespace>>,
+        services: Option<reflector::Store<Service>>,
+        offline_ports: Option<Vec<NamedPort>>,
+    ) -> anyhow::Result<Self> {
+        let config = match &args.config {
+            Some(path) => ConfigFile::load(path)?,
+            None => ConfigFile::default(),
+        };
+        let settings = Arc::new(RwLock::new(Settings::new(&args, config)));
         let is_leader = Arc::new(AtomicBool::new(false));
         let mut rng = rand::rng();
         let prefix = Alphanumeric.sample_string(&mut rng, 12);
         let hostname = std::env::var("HOSTNAME")
             .or_else(|_| std::env::var("HOST"))
             .unwrap_or_else(|_| format!("i2g-operator-{prefix}"));
+        let shard_index = match args.shard_count {
+            Some(count) => Some(resolve_shard_index(&args, &hostname, count)?),
+            None => None,
+        };
+        let recorder = Recorder::new(
+            client.clone(),
+            Reporter {
+                controller: consts::MANAGED_BY_VALUE.to_string(),
+                instance: Some(hostname.clone()),
+            },
+        );
+        let fair_queue = args
+            .max_concurrent_reconciles
+            .filter(|_| args.fair_namespace_queue)
+            .map(|capacity| FairQueue::new(capacity.into(), args.namespace_concurrency));
         Ok(Context {
             args,
             client,
+            recorder,
             is_leader,
+            leader_since: Arc::default(),
             hostname,
+            namespaces,
+            services,
+            gateways: None,
+            ingress_classes: None,
+            class_configs: None,
+            managed_routes: HashMap::new(),
+            failures: Arc::default(),
+            metrics: Metrics::new()?,
+            namespace_semaphores: Arc::default(),
+            fair_queue,
+            offline_ports,
+            ingresses: Arc::default(),
+            shard_index,
+            settings,
         })
     }
+
+    /// Current values of reloadable settings.
+    pub fn settings(&self) -> Settings {
+        self.settings.read().unwrap().clone()
+    }
+
+    /// I2GClassConfig referenced by parameters of the Ingress class.
+    pub fn class_config(&self, ingress: &Ingress) -> Option<Arc<I2GClassConfig>> {
+        let (Some(classes), Some(configs)) = (&self.ingress_classes, &self.class_configs) else {
+            return None;
+        };
+        let class = classes.get(&ObjectRef::new(utils::ingress_class(ingress)?))?;
+        let parameters = class.spec.as_ref()?.parameters.as_ref()?;
+        let is_config = parameters.api_group.as_deref() == Some(&I2GClassConfig::group(&()))
+            && parameters.kind == I2GClassConfig::kind(&());
+        if !is_config {
+            return None;
+        }
+        configs.get(&ObjectRef::new(&parameters.name))
+    }
+
+    /// Annotation provider of the Ingress: from its class parameters or operator settings.
+    pub fn provider(&self, ingress: &Ingress) -> &'static dyn AnnotationProvider {
+        self.class_config(ingress)
+            .and_then(|config| config.spec.compat_provider)
+            .unwrap_or(self.settings().compat_provider)
+            .provider()
+    }
+
+    /// Whether Ingresses of the namespace are reconciled by this instance.
+    /// Namespaces are assigned to shards by the stable hash of their names.
+    pub fn is_namespace_in_shard(&self, namespace: &str) -> bool {
+        let (Some(count), Some(index)) = (self.args.shard_count, self.shard_index) else {
+            return true;
+        };
+        let hash = u64::from_str_radix(&naming::stable_hash(&[namespace]), 16).unwrap_or_default();
+        hash % u64::from(count) == u64::from(index)
+    }
+
+    pub fn is_leader(&self) -> bool {
+        self.is_leader.load(Ordering::Relaxed)
+    }
+
+    /// Update leadership status. Returns true if leadership was just acquired.
+    pub fn set_leader(&self, is_leader: bool) -> bool {
+        let was_leader = self.is_leader.swap(is_leader, Ordering::Relaxed);
+        self.metrics.is_leader.set(i64::from(is_leader));
+        let mut leader_since = self.leader_since.lock().unwrap();
+        if !is_leader {
+            *leader_since = None;
+            return false;
+        }
+        if !was_leader || leader_since.is_none() {
+            *leader_since = Some(Instant::now());
+            return true;
+        }
+        false
+    }
+
+    /// Time left until reconciles can start after a fresh leadership acquisition.
+    pub fn leader_warmup_remaining(&self) -> Option<Duration> {
+        let warmup = Duration::from_secs(self.args.leader_warmup_secs);
+        let leader_since = (*self.leader_since.lock().unwrap())?;
+        warmup
+            .checked_sub(leader_since.elapsed())
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    /// Increase the number of consecutive failures and return it.
+    pub fn record_failure(&self, ingress: &ObjectRef<Ingress>) -> u32 {
+        let mut failures = self.failures.lock().unwrap();
+        let count = failures.entry(ingress.clone()).or_default();
+        *count += 1;
+        if *count == self.args.dead_letter_threshold {
+            self.metrics.dead_letter_ingresses.inc();
+        }
+        *count
+    }
+
+    pub fn reset_failures(&self, ingress: &ObjectRef<Ingress>) {
+        let threshold = self.args.dead_letter_threshold;
+        let removed = self.failures.lock().unwrap().remove(ingress);
+        if threshold > 0 && removed.is_some_and(|count| count >= threshold) {
+            self.metrics.dead_letter_ingresses.dec();
+        }
+    }
+
+    /// Publish an Event on the Ingress. Failures are only logged.
+    pub async fn publish_event(
+        &self,
+        ingress: &Ingress,
+        type_: EventType,
+        reason: &str,
+        note: String,
+    ) {
+        let event = Event {
+            type_,
+            reason: reason.to_string(),
+            note: Some(note),
+            action: "Translate".to_string(),
+            secondary: None,
+        };
+        if let Err(err) = self
+            .recorder
+            .publish(&event, &ingress.object_ref(&()))
+            .await
+        {
+            tracing::warn!("Failed to publish event: {err}");
+        }
+    }
+
+    /// Semaphore limiting concurrent reconciles in the namespace.
+    pub fn namespace_semaphore(&self, namespace: &str, limit: usize) -> Arc<Semaphore> {
+        self.namespace_semaphores
+            .lock()
+            .unwrap()
+            .entry(namespace.to_string())
+            .or_insert_with(|| Arc::new(Semaphore::new(limit)))
+            .clone()
+    }
+}
+
+/// Shard from `--shard-index` or the ordinal suffix of the hostname.
+fn resolve_shard_index(args: &I2GArgs, hostname: &str, count: u32) -> anyhow::Result<u32> {
+    let index = match args.shard_index {
+        Some(index) => index,
+        None => hostname
+            .rsplit_once('-')
+            .and_then(|(_, ordinal)| ordinal.parse().ok())
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "--shard-index must be set, hostname {hostname} doesn't end with an ordinal"
+                )
+            })?,
+    };
+    if index >= count {
+        anyhow::bail!("Shard index {index} must be less than --shard-count {count}");
+    }
+    Ok(index)
+}
+
+/// Create a client used for all API calls.
+/// If `--client-qps` is set, requests are rate limited:
+/// at most `burst` requests are sent per `burst / qps` seconds.
+async fn create_client(args: &I2GArgs) -> anyhow::Result<kube::Client> {
+    let config = kube::Config::infer().await?;
+    let builder = kube::client::ClientBuilder::try_from(config)?;
+    let Some(qps) = args.client_qps else {
+        return Ok(builder.build());
+    };
+    let period = Duration::from_secs_f64(args.client_burst as f64 / qps);
+    Ok(builder
+        .with_layer(&RateLimitLayer::new(args.client_burst, period))
+        .build())
+}
+
+/// Start a reflector for services in watched namespaces.
+fn watch_services(client: kube::Client, args: &I2GArgs) -> reflector::Store<Service> {
+    let api = match args.watch_namespaces.as_slice() {
+        [namespace] => Api::<Service>::namespaced(client, namespace),
+        _ => Api::<Service>::all(client),
+    };
+    let config = watcher::Config::default().fields(&args.excluded_namespaces_selector());
+    let (reader, writer) = reflector::store();
+    let stream = reflector(writer, watcher(api, config))
+        .default_backoff()
+        .touched_objects();
+    tokio::spawn(stream.for_each(|event| {
+        if let Err(err) = event {
+            tracing::warn!("Service watcher error: {err}");
+        }
+        futures::future::ready(())
+    }));
+    reader
+}
+
+/// Start a reflector for gateways in all namespaces.
+fn watch_gateways(client: kube::Client) -> reflector::Store<Gateway> {
+    let (reader, writer) = reflector::store();
+    let stream = reflector(
+        writer,
+        watcher(Api::<Gateway>::all(client), watcher::Config::default()),
+    )
+    .default_backoff()
+    .touched_objects();
+    tokio::spawn(stream.for_each(|event| {
+        if let Err(err) = event {
+            tracing::warn!("Gateway watcher error: {err}");
+        }
+        futures::future::ready(())
+    }));
+    reader
+}
+
+/// Start a reflector for cluster-scoped objects.
+fn watch_cluster<K>(client: kube::Client, kind: &'static str) -> reflector::Store<K>
+where
+    K: Resource<Scope = kube::core::ClusterResourceScope, DynamicType = ()>
+        + Clone
+        + std::fmt::Debug
+        + serde::de::DeserializeOwned
+        + Send
+        + Sync
+        + 'static,
+{
+    let (reader, writer) = reflector::store();
+    let stream = reflector(
+        writer,
+        watcher(Api::<K>::all(client), watcher::Config::default()),
+    )
+    .default_backoff()
+    .touched_objects();
+    tokio::spawn(stream.for_each(move |event| {
+        if let Err(err) = event {
+            tracing::warn!("{kind} watcher error: {err}");
+        }
+        futures::future::ready(())
+    }));
+    reader
+}
+
+/// Start a reflector for objects of the kind managed by the operator.
+/// Returns the kind with the store.
+fn watch_managed<K>(client: kube::Client) -> (String, reflector::Store<DynamicObject>)
+where
+    K: Resource<DynamicType = ()>,
+{
+    let resource = ApiResource::erase::<K>(&());
+    let kind = resource.kind.clone();
+    let writer = reflector::store::Writer::new(resource.clone());
+    let reader = writer.as_reader();
+    let config = watcher::Config::default().labels(&format!(
+        "{}={}",
+        consts::MANAGED_BY_LABEL,
+        consts::MANAGED_BY_VALUE
+    ));
+    let stream = reflector(
+        writer,
+        watcher(Api::<DynamicObject>::all_with(client, &resource), config),
+    )
+    .default_backoff()
+    .touched_objects();
+    tokio::spawn(stream.for_each(move |event| {
+        if let Err(err) = event {
+            tracing::warn!("{kind} watcher error: {err}");
+        }
+        futures::future::ready(())
+    }));
+    (resource.kind, reader)
+}
+
+/// Start a reflector for namespaces matching the label selector.
+async fn watch_namespaces(
+    client: kube::Client,
+    selector: &str,
+) -> anyhow::Result<reflector::Store<Namespace>> {
+    let api = Api::<Namespace>::all(client);
+    // Fail fast on invalid selectors instead of retrying the watch forever.
+    api.list_metadata(&ListParams::default().labels(selector).limit(1))
+        .await?;
+    let (reader, writer) = reflector::store();
+    let stream = reflector(
+        writer,
+        watcher(api, watcher::Config::default().labels(selector)),
+    )
+    .default_backoff()
+    .touched_objects();
+    tokio::spawn(stream.for_each(|event| {
+        if let Err(err) = event {
+            tracing::warn!("Namespace watcher error: {err}");
+        }
+        futures::future::ready(())
+    }));
+    Ok(reader)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offline_ctx(args: &[&str]) -> Context {
+        let args = I2GArgs::parse_from(std::iter::once("i2g-operator").chain(args.iter().copied()));
+        Context::offline(args, vec![]).unwrap()
+    }
+
+    #[tokio::test]
+    async fn leadership_transitions() {
+        let ctx = offline_ctx(&[]);
+        assert!(!ctx.is_leader());
+        assert!(ctx.set_leader(true));
+        assert!(ctx.is_leader());
+        // Renewals don't count as a new acquisition.
+        assert!(!ctx.set_leader(true));
+        assert!(!ctx.set_leader(false));
+        assert!(!ctx.is_leader());
+        assert_eq!(ctx.metrics.is_leader.get(), 0);
+        assert!(ctx.set_leader(true));
+        assert_eq!(ctx.metrics.is_leader.get(), 1);
+    }
+
+    #[tokio::test]
+    async fn leader_warmup() {
+        let ctx = offline_ctx(&[]);
+        ctx.set_leader(true);
+        assert_eq!(ctx.leader_warmup_remaining(), None);
+
+        let ctx = offline_ctx(&["--leader-warmup-secs", "60"]);
+        assert_eq!(ctx.leader_warmup_remaining(), None);
+        ctx.set_leader(true);
+        let remaining = ctx.leader_warmup_remaining().unwrap();
+        assert!(remaining <= Duration::from_secs(60) && remaining > Duration::from_secs(50));
+        // Losing leadership resets the warm-up.
+        ctx.set_leader(false);
+        assert_eq!(ctx.leader_warmup_remaining(), None);
+    }
+
+    #[tokio::test]
+    async fn consecutive_failures() {
+        let ctx = offline_ctx(&[]);
+        let web = ObjectRef::<Ingress>::new("web").within("ns");
+        let api = ObjectRef::<Ingress>::new("api").within("ns");
+        assert_eq!(ctx.record_failure(&web), 1);
+        assert_eq!(ctx.record_failure(&web), 2);
+        assert_eq!(ctx.record_failure(&api), 1);
+        ctx.reset_failures(&web);
+        assert_eq!(ctx.record_failure(&web), 1);
+        assert_eq!(ctx.record_failure(&api), 2);
+    }
 }
@@ -16,4 +16,106 @@ pub enum I2GError {
     SerdeJsonError(#[from] serde_json::Error),
     #[error(transparent)]
     AnyhowError(#[from] anyhow::Error),
+    #[error("Finalizer error: {0}")]
+    FinalizerError(#[source] Box<kube::runtime::finalizer::Error<I2GError>>),
+    #[error("Lost leadership during reconciliation")]
+    LostLeadership,
+}
+
+/// How a failed reconcile should be retried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryPolicy {
+    /// Retrying can't help until the Ingress or the cluster setup is changed,
+    /// e.g. invalid annotations or missing route CRDs.
+    Terminal,
+    /// Transient failures, e.g. API timeouts. Retried with exponential backoff.
+    Retryable,
+    /// The API server throttles requests. Retried after the maximum backoff.
+    RateLimited,
+}
+
+fn kube_retry_policy(err: &kube::Error) -> RetryPolicy {
+    match err {
+        kube::Error::Api(response) => match response.code {
+            // Missing CRDs are reported as 404, invalid objects as 400 and 422.
+            400 | 404 | 422 => RetryPolicy::Terminal,
+            429 => RetryPolicy::RateLimited,
+            _ => RetryPolicy::Retryable,
+        },
+        _ => RetryPolicy::Retryable,
+    }
+}
+
+impl I2GError {
+    /// How the failed reconcile should be retried.
+    pub fn retry_policy(&self) -> RetryPolicy {
+        use kube::runtime::finalizer::Error as FinalizerError;
+        match self {
+            I2GError::ParseError(_)
+            | I2GError::MissingAnnotation(_)
+            | I2GError::SerdeJsonError(_) => RetryPolicy::Terminal,
+            I2GError::KubeError(err) => kube_retry_policy(err),
+            I2GError::AnyhowError(err) => err
+                .downcast_ref::<kube::Error>()
+                .map_or(RetryPolicy::Retryable, kube_retry_policy),
+            I2GError::FinalizerError(err) => match err.as_ref() {
+                FinalizerError::ApplyFailed(err) | FinalizerError::CleanupFailed(err) => {
+                    err.retry_policy()
+                }
+                FinalizerError::AddFinalizer(err) | FinalizerError::RemoveFinalizer(err) => {
+                    kube_retry_policy(err)
+                }
+                FinalizerError::UnnamedObject | FinalizerError::InvalidFinalizer => {
+                    RetryPolicy::Terminal
+                }
+            },
+            I2GError::IoError(_) | I2GError::General(_) | I2GError::LostLeadership => {
+                RetryPolicy::Retryable
+            }
+        }
+    }
+
+    /// Short name of the error kind used as a metric label.
+    pub fn kind(&self) -> &str {
+        match self {
+            I2GError::IoError(_) => "io",
+            I2GError::KubeError(_) => "kube",
+            I2GError::MissingAnnotation(_) => "missing_annotation",
+            I2GError::ParseError(_) => "parse",
+            I2GError::General(_) => "general",
+            I2GError::SerdeJsonError(_) => "serde_json",
+            I2GError::AnyhowError(_) => "other",
+            I2GError::FinalizerError(_) => "finalizer",
+            I2GError::LostLeadership => "lost_leadership",
+        }
+    }
+
+    /// Whether the reconcile was aborted because leadership was lost.
+    pub fn is_lost_leadership(&self) -> bool {
+        use kube::runtime::finalizer::Error as FinalizerError;
+        match self {
+            I2GError::LostLeadership => true,
+            I2GError::FinalizerError(err) => matches!(
+                err.as_ref(),
+                FinalizerError::ApplyFailed(err) | FinalizerError::CleanupFailed(err)
+                    if err.is_lost_leadership()
+            ),
+            _ => false,
+        }
+    }
+}
+
+/// Reason why a service port of a backend couldn't be resolved.
+#[derive(Debug, thiserror::Error)]
+pub enum PortError {
+    #[error("port of service {0} has neither number nor name")]
+    NoPort(String),
+    #[error("service {0} was not found")]
+    ServiceNotFound(String),
+    #[error("service {0} has no ports")]
+    NoServicePorts(String),
+    #[error("service {svc_name} has no port named {port_name}")]
+    PortNameNotFound { svc_name: String, port_name: String },
+    #[error("Kubernetes API error: {0}")]
+    KubeError(#[from] kube::Error),
 }
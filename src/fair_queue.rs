@@ -0,0 +1,225 @@
+//! Reconcile slots shared between namespaces in round-robin order.
+//!
+//! The controller starts reconciles in the order objects change, so a namespace
+//! whose Ingresses change at once takes all slots of `--max-concurrent-reconciles`
+//! and other namespaces wait until its backlog is processed. With the fair queue
+//! waiting reconciles are grouped by namespace and every freed slot goes to the
+//! next namespace with waiting reconciles, so a namespace waits for at most one
+//! reconcile of every other namespace.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::oneshot;
+
+/// Queue of reconciles waiting for a slot, see the module documentation.
+#[derive(Clone)]
+pub struct FairQueue {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    /// Maximum number of running reconciles.
+    capacity: usize,
+    /// Maximum number of running reconciles per namespace.
+    namespace_limit: Option<usize>,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    running: usize,
+    running_in_namespace: HashMap<String, usize>,
+    /// Namespaces with waiting reconciles, the front one is served next.
+    rotation: VecDeque<String>,
+    waiting: HashMap<String, VecDeque<oneshot::Sender<FairPermit>>>,
+}
+
+/// Reconcile slot, released when the permit is dropped.
+pub struct FairPermit {
+    inner: Arc<Inner>,
+    namespace: String,
+}
+
+impl FairQueue {
+    pub fn new(capacity: usize, namespace_limit: Option<usize>) -> Self {
+        FairQueue {
+            inner: Arc::new(Inner {
+                capacity,
+                namespace_limit,
+                state: Mutex::default(),
+            }),
+        }
+    }
+
+    /// Wait for a reconcile slot for the namespace.
+    pub async fn acquire(&self, namespace: &str) -> FairPermit {
+        let (sender, receiver) = oneshot::channel();
+        let granted = {
+            let mut state = self.inner.state.lock().unwrap();
+            if !state.waiting.contains_key(namespace) {
+                state.rotation.push_back(namespace.to_string());
+            }
+            state
+                .waiting
+                .entry(namespace.to_string())
+                .or_default()
+                .push_back(sender);
+            self.inner.dispatch(&mut state)
+        };
+        self.inner.grant(granted);
+        // Senders are only dropped after sending a permit.
+        receiver
+            .await
+            .expect("waiting reconciles are always granted")
+    }
+}
+
+impl Inner {
+    /// Assign free slots to waiting reconciles, taking one from every namespace in turn.
+    /// Permits are sent by [`Inner::grant`] after the lock is released, since a permit
+    /// of a cancelled reconcile is dropped and releases its slot right away.
+    fn dispatch(
+        self: &Arc<Self>,
+        state: &mut State,
+    ) -> Vec<(oneshot::Sender<FairPermit>, FairPermit)> {
+        let mut granted = vec![];
+        while state.running < self.capacity {
+            let below_limit = |namespace: &String| {
+                self.namespace_limit.is_none_or(|limit| {
+                    state
+                        .running_in_namespace
+                        .get(namespace)
+                        .copied()
+                        .unwrap_or(0)
+                        < limit
+                })
+            };
+            let Some(position) = state.rotation.iter().position(below_limit) else {
+                break;
+            };
+            let namespace = state.rotation.remove(position).expect("position is valid");
+            let waiting = state
+                .waiting
+                .get_mut(&namespace)
+                .expect("rotated namespace waits");
+            let sender = waiting.pop_front().expect("waiting queues aren't empty");
+            if waiting.is_empty() {
+                state.waiting.remove(&namespace);
+            } else {
+                state.rotation.push_back(namespace.clone());
+            }
+            state.running += 1;
+            *state
+                .running_in_namespace
+                .entry(namespace.clone())
+                .or_default() += 1;
+            granted.push((
+                sender,
+                FairPermit {
+                    inner: self.clone(),
+                    namespace,
+                },
+            ));
+        }
+        granted
+    }
+
+    fn grant(&self, granted: Vec<(oneshot::Sender<FairPermit>, FairPermit)>) {
+        for (sender, permit) in granted {
+            // The reconcile was cancelled, the returned permit frees the slot when dropped.
+            let _ = sender.send(permit);
+        }
+    }
+}
+
+impl Drop for FairPermit {
+    fn drop(&mut self) {
+        let granted = {
+            let mut state = self.inner.state.lock().unwrap();
+            state.running -= 1;
+            if let Some(running) = state.running_in_namespace.get_mut(&self.namespace) {
+                *running -= 1;
+                if *running == 0 {
+                    state.running_in_namespace.remove(&self.namespace);
+                }
+            }
+            self.inner.dispatch(&mut state)
+        };
+        self.inner.grant(granted);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    /// Namespaces in the order their reconciles got a slot, when `busy` queues
+    /// six reconciles before `a` and `b` queue one each and only one slot is free.
+    async fn grant_order(queue: FairQueue) -> Vec<&'static str> {
+        let first = queue.acquire("busy").await;
+        let order = Arc::new(Mutex::new(vec![]));
+        let mut tasks = vec![];
+        for namespace in ["busy"; 6].into_iter().chain(["a", "b"]) {
+            let queue = queue.clone();
+            let order = order.clone();
+            tasks.push(tokio::spawn(async move {
+                let _permit = queue.acquire(namespace).await;
+                order.lock().unwrap().push(namespace);
+                tokio::task::yield_now().await;
+            }));
+            // Let the reconcile queue up before the next one.
+            tokio::task::yield_now().await;
+        }
+        drop(first);
+        for task in tasks {
+            task.await.unwrap();
+        }
+        Arc::try_unwrap(order).unwrap().into_inner().unwrap()
+    }
+
+    #[rstest]
+    #[case(None)]
+    #[case(Some(1))]
+    #[tokio::test]
+    async fn round_robin_under_skewed_load(#[case] namespace_limit: Option<usize>) {
+        let order = grant_order(FairQueue::new(1, namespace_limit)).await;
+        assert_eq!(
+            order,
+            vec!["busy", "a", "b", "busy", "busy", "busy", "busy", "busy"]
+        );
+    }
+
+    #[tokio::test]
+    async fn namespace_limit() {
+        let queue = FairQueue::new(3, Some(2));
+        let first = queue.acquire("busy").await;
+        let _second = queue.acquire("busy").await;
+        // The namespace is at its limit, while other namespaces still get slots.
+        let third = queue.acquire("busy");
+        tokio::pin!(third);
+        assert!(futures::poll!(third.as_mut()).is_pending());
+        let _other = queue.acquire("other").await;
+        drop(first);
+        third.await;
+    }
+
+    #[tokio::test]
+    async fn cancelled_reconcile_frees_slot() {
+        let queue = FairQueue::new(1, None);
+        let first = queue.acquire("a").await;
+        let cancelled = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.acquire("b").await }
+        });
+        tokio::task::yield_now().await;
+        cancelled.abort();
+        let _ = cancelled.await;
+        drop(first);
+        // The slot granted to the cancelled reconcile is released again.
+        let _permit = queue.acquire("c").await;
+    }
+}
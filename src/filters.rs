@@ -0,0 +1,427 @@
+use gateway_api::apis::experimental::httproutes::{
+    HTTPRouteRulesFilters, HTTPRouteRulesFiltersExtensionRef,
+    HTTPRouteRulesFiltersRequestHeaderModifier, HTTPRouteRulesFiltersRequestHeaderModifierSet,
+    HTTPRouteRulesFiltersRequestMirror, HTTPRouteRulesFiltersRequestMirrorBackendRef,
+    HTTPRouteRulesFiltersRequestRedirect, HTTPRouteRulesFiltersRequestRedirectScheme,
+    HTTPRouteRulesFiltersType, HTTPRouteRulesFiltersUrlRewrite,
+    HTTPRouteRulesFiltersUrlRewritePath, HTTPRouteRulesFiltersUrlRewritePathType,
+    HTTPRouteRulesMatchesPathType, HTTPRouteRulesRetry,
+};
+
+use crate::err::I2GError;
+
+/// Parse a comma-separated list of extension references.
+/// Each reference should be in the following format:
+/// `group/kind/name`. Group may be empty for the core API group: `/kind/name`.
+pub fn parse_extension_refs(raw: &str) -> Result<Vec<HTTPRouteRulesFilters>, I2GError> {
+    let mut filters = vec![];
+    for item in raw
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+    {
+        let parts = item.split('/').collect::<Vec<_>>();
+        let [group, kind, name] = parts.as_slice() else {
+            return Err(I2GError::ParseError(format!(
+                "Extension filter '{item}' should be in format group/kind/name"
+            )));
+        };
+        if kind.is_empty() || name.is_empty() {
+            return Err(I2GError::ParseError(format!(
+                "Extension filter '{item}' must have non-empty kind and name"
+            )));
+        }
+        let filter = HTTPRouteRulesFilters {
+            r#type: HTTPRouteRulesFiltersType::ExtensionRef,
+            cors: None,
+            extension_ref: Some(HTTPRouteRulesFiltersExtensionRef {
+                group: group.to_string(),
+                kind: kind.to_string(),
+                name: name.to_string(),
+            }),
+            external_auth: None,
+            request_header_modifier: None,
+            request_mirror: None,
+            request_redirect: None,
+            response_header_modifier: None,
+            url_rewrite: None,
+        };
+        if !filters.contains(&filter) {
+            filters.push(filter);
+        }
+    }
+    Ok(filters)
+}
+
+/// Parse a retry policy in the following format:
+/// `attempts=3,backoff=200ms,codes=502,503`.
+/// Codes are comma-separated as well, so values without a key continue the code list.
+pub fn parse_retry(raw: &str) -> Result<HTTPRouteRulesRetry, I2GError> {
+    let invalid = |msg: String| I2GError::ParseError(format!("Invalid retries '{raw}': {msg}"));
+    let mut retry = HTTPRouteRulesRetry::default();
+    let mut last_key = "";
+    for item in raw
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+    {
+        let (key, value) = match item.split_once('=') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None if last_key == "codes" => ("codes", item),
+            None => return Err(invalid(format!("'{item}' should be in format key=value"))),
+        };
+        match key {
+            "attempts" => {
+                let attempts = value
+                    .parse::<i64>()
+                    .ok()
+                    .filter(|attempts| *attempts >= 0)
+                    .ok_or_else(|| invalid(format!("attempts '{value}' is not a number")))?;
+                retry.attempts = Some(attempts);
+            }
+            "backoff" => {
+                let backoff = value
+                    .parse::<gateway_api::Duration>()
+                    .map_err(|err| invalid(format!("backoff '{value}': {err}")))?;
+                retry.backoff = Some(backoff.to_string());
+            }
+            "codes" => {
+                let code = value
+                    .parse::<i64>()
+                    .ok()
+                    .filter(|code| (400..=599).contains(code))
+                    .ok_or_else(|| invalid(format!("code '{value}' is not in range 400-599")))?;
+                let codes = retry.codes.get_or_insert_default();
+                if !codes.contains(&code) {
+                    codes.push(code);
+                }
+            }
+            _ => return Err(invalid(format!("unknown key '{key}'"))),
+        }
+        last_key = key;
+    }
+    Ok(retry)
+}
+
+/// Path rewrite requested by Ingress annotations.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathRewrite {
+    /// Value of `i2g-operator/rewrite-prefix`.
+    Prefix(String),
+    /// Value of `nginx.ingress.kubernetes.io/rewrite-target`.
+    NginxTarget(String),
+}
+
+/// Suffix of nginx paths capturing everything after the prefix.
+const NGINX_CAPTURE_SUFFIX: &str = "(/|$)(.*)";
+
+fn url_rewrite_filter(
+    path_type: HTTPRouteRulesFiltersUrlRewritePathType,
+    value: &str,
+) -> HTTPRouteRulesFilters {
+    let is_prefix = path_type == HTTPRouteRulesFiltersUrlRewritePathType::ReplacePrefixMatch;
+    HTTPRouteRulesFilters {
+        r#type: HTTPRouteRulesFiltersType::UrlRewrite,
+        cors: None,
+        extension_ref: None,
+        external_auth: None,
+        request_header_modifier: None,
+        request_mirror: None,
+        request_redirect: None,
+        response_header_modifier: None,
+        url_rewrite: Some(HTTPRouteRulesFiltersUrlRewrite {
+            hostname: None,
+            path: Some(HTTPRouteRulesFiltersUrlRewritePath {
+                replace_full_path: (!is_prefix).then(|| value.to_string()),
+                replace_prefix_match: is_prefix.then(|| value.to_string()),
+                r#type: path_type,
+            }),
+        }),
+    }
+}
+
+/// Build a URLRewrite filter for the path of an Ingress rule.
+///
+/// Returns the filter and a new prefix to match instead of the path
+/// if the nginx capture groups form was used.
+pub fn url_rewrite(
+    rewrite: &PathRewrite,
+    path: &str,
+    match_type: &HTTPRouteRulesMatchesPathType,
+) -> Result<(HTTPRouteRulesFilters, Option<String>), I2GError> {
+    use HTTPRouteRulesFiltersUrlRewritePathType::{ReplaceFullPath, ReplacePrefixMatch};
+    match rewrite {
+        PathRewrite::Prefix(prefix) => match match_type {
+            HTTPRouteRulesMatchesPathType::PathPrefix => {
+                Ok((url_rewrite_filter(ReplacePrefixMatch, prefix), None))
+            }
+            HTTPRouteRulesMatchesPathType::Exact => {
+                Ok((url_rewrite_filter(ReplaceFullPath, prefix), None))
+            }
+            HTTPRouteRulesMatchesPathType::RegularExpression => Err(I2GError::ParseError(format!(
+                "Prefix can't be rewritten for regular expression path {path}"
+            ))),
+        },
+        PathRewrite::NginxTarget(target) if !target.contains('$') => {
+            Ok((url_rewrite_filter(ReplaceFullPath, target), None))
+        }
+        PathRewrite::NginxTarget(target) => {
+            let prefix = path
+                .strip_suffix(NGINX_CAPTURE_SUFFIX)
+                .filter(|_| target == "/$2")
+                .ok_or_else(|| {
+                    I2GError::ParseError(format!(
+                        "Rewrite target {target} for path {path} is not supported, only `/prefix{NGINX_CAPTURE_SUFFIX}` paths with `/$2` target can be translated"
+                    ))
+                })?;
+            let prefix = if prefix.is_empty() { "/" } else { prefix };
+            Ok((
+                url_rewrite_filter(ReplacePrefixMatch, "/"),
+                Some(prefix.to_string()),
+            ))
+        }
+    }
+}
+
+/// Filter mirroring the given percent of requests to the service.
+pub fn request_mirror(name: &str, port: i32, percent: Option<i32>) -> HTTPRouteRulesFilters {
+    HTTPRouteRulesFilters {
+        r#type: HTTPRouteRulesFiltersType::RequestMirror,
+        cors: None,
+        extension_ref: None,
+        external_auth: None,
+        request_header_modifier: None,
+        request_mirror: Some(HTTPRouteRulesFiltersRequestMirror {
+            backend_ref: HTTPRouteRulesFiltersRequestMirrorBackendRef {
+                group: None,
+                kind: None,
+                name: name.to_string(),
+                namespace: None,
+                port: Some(port),
+            },
+            fraction: None,
+            percent,
+        }),
+        request_redirect: None,
+        response_header_modifier: None,
+        url_rewrite: None,
+    }
+}
+
+/// Filter permanently redirecting requests to the same URL with the https scheme,
+/// and the port if it's set.
+pub fn https_redirect(port: Option<i32>) -> HTTPRouteRulesFilters {
+    HTTPRouteRulesFilters {
+        r#type: HTTPRouteRulesFiltersType::RequestRedirect,
+        cors: None,
+        extension_ref: None,
+        external_auth: None,
+        request_header_modifier: None,
+        request_mirror: None,
+        request_redirect: Some(HTTPRouteRulesFiltersRequestRedirect {
+            scheme: Some(HTTPRouteRulesFiltersRequestRedirectScheme::Https),
+            port,
+            status_code: Some(301),
+            ..HTTPRouteRulesFiltersRequestRedirect::default()
+        }),
+        response_header_modifier: None,
+        url_rewrite: None,
+    }
+}
+
+/// RequestHeaderModifier filter of the rule.
+/// Gateway API allows only one such filter per rule, so an existing filter is reused.
+fn request_header_modifier(
+    filters: &mut Vec<HTTPRouteRulesFilters>,
+) -> &mut HTTPRouteRulesFiltersRequestHeaderModifier {
+    let position = filters
+        .iter()
+        .position(|filter| filter.r#type == HTTPRouteRulesFiltersType::RequestHeaderModifier);
+    let index = match position {
+        Some(index) => index,
+        None => {
+            filters.push(HTTPRouteRulesFilters {
+                r#type: HTTPRouteRulesFiltersType::RequestHeaderModifier,
+                cors: None,
+                extension_ref: None,
+                external_auth: None,
+                request_header_modifier: None,
+                request_mirror: None,
+                request_redirect: None,
+                response_header_modifier: None,
+                url_rewrite: None,
+            });
+            filters.len() - 1
+        }
+    };
+    filters[index]
+        .request_header_modifier
+        .get_or_insert_default()
+}
+
+/// Add headers to the remove list of the rule's RequestHeaderModifier filter.
+/// Header names are case-insensitive and are not duplicated.
+pub fn add_removed_request_headers(filters: &mut Vec<HTTPRouteRulesFilters>, headers: &[String]) {
+    if headers.is_empty() {
+        return;
+    }
+    let remove = request_header_modifier(filters)
+        .remove
+        .get_or_insert_default();
+    for header in headers {
+        if !remove.iter().any(|name| name.eq_ignore_ascii_case(header)) {
+            remove.push(header.clone());
+        }
+    }
+}
+
+/// Set a request header in the rule's RequestHeaderModifier filter.
+/// A header with the same case-insensitive name is overwritten.
+pub fn set_request_header(filters: &mut Vec<HTTPRouteRulesFilters>, name: &str, value: &str) {
+    let set = request_header_modifier(filters).set.get_or_insert_default();
+    set.retain(|header| !header.name.eq_ignore_ascii_case(name));
+    set.push(HTTPRouteRulesFiltersRequestHeaderModifierSet {
+        name: name.to_string(),
+        value: value.to_string(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn extension_ref(group: &str, kind: &str, name: &str) -> HTTPRouteRulesFiltersExtensionRef {
+        HTTPRouteRulesFiltersExtensionRef {
+            group: group.to_string(),
+            kind: kind.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    #[rstest]
+    #[case("", vec![])]
+    #[case(
+        "example.com/Auth/basic",
+        vec![extension_ref("example.com", "Auth", "basic")]
+    )]
+    #[case(
+        " /ConfigMap/cfg , example.com/Auth/basic,",
+        vec![extension_ref("", "ConfigMap", "cfg"), extension_ref("example.com", "Auth", "basic")]
+    )]
+    #[case(
+        "example.com/Auth/basic,example.com/Auth/basic",
+        vec![extension_ref("example.com", "Auth", "basic")]
+    )]
+    fn extension_refs_valid(
+        #[case] raw: &str,
+        #[case] expected: Vec<HTTPRouteRulesFiltersExtensionRef>,
+    ) {
+        let filters = parse_extension_refs(raw).unwrap();
+        assert!(
+            filters
+                .iter()
+                .all(|filter| filter.r#type == HTTPRouteRulesFiltersType::ExtensionRef)
+        );
+        let refs = filters
+            .into_iter()
+            .filter_map(|filter| filter.extension_ref)
+            .collect::<Vec<_>>();
+        assert_eq!(refs, expected);
+    }
+
+    #[rstest]
+    #[case("Auth/basic")]
+    #[case("example.com/Auth/basic/extra")]
+    #[case("example.com//basic")]
+    #[case("example.com/Auth/")]
+    fn extension_refs_invalid(#[case] raw: &str) {
+        assert!(parse_extension_refs(raw).is_err());
+    }
+
+    fn removed_headers(filters: &[HTTPRouteRulesFilters]) -> Vec<Vec<String>> {
+        filters
+            .iter()
+            .filter(|filter| filter.r#type == HTTPRouteRulesFiltersType::RequestHeaderModifier)
+            .map(|filter| {
+                filter
+                    .request_header_modifier
+                    .as_ref()
+                    .and_then(|modifier| modifier.remove.clone())
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn removed_request_headers() {
+        let mut filters = vec![];
+        add_removed_request_headers(&mut filters, &[]);
+        assert!(filters.is_empty());
+
+        let headers = ["Connection".to_string(), "Upgrade".to_string()];
+        add_removed_request_headers(&mut filters, &headers);
+        assert_eq!(removed_headers(&filters), vec![headers.to_vec()]);
+
+        // The existing filter is extended and names are compared case-insensitively.
+        add_removed_request_headers(&mut filters, &["connection".to_string(), "TE".to_string()]);
+        assert_eq!(
+            removed_headers(&filters),
+            vec![vec![
+                "Connection".to_string(),
+                "Upgrade".to_string(),
+                "TE".to_string()
+            ]]
+        );
+    }
+
+    #[test]
+    fn removed_request_headers_keep_other_filters() {
+        let extension = parse_extension_refs("example.com/Auth/basic").unwrap();
+        let mut filters = extension.clone();
+        add_removed_request_headers(&mut filters, &["Keep-Alive".to_string()]);
+        assert_eq!(filters.len(), 2);
+        assert_eq!(filters[0], extension[0]);
+        assert_eq!(
+            removed_headers(&filters),
+            vec![vec!["Keep-Alive".to_string()]]
+        );
+    }
+
+    #[rstest]
+    #[case(None)]
+    #[case(Some(8443))]
+    fn redirect_port(#[case] port: Option<i32>) {
+        let filter = https_redirect(port);
+        assert_eq!(filter.r#type, HTTPRouteRulesFiltersType::RequestRedirect);
+        let redirect = filter.request_redirect.unwrap();
+        assert_eq!(
+            redirect.scheme,
+            Some(HTTPRouteRulesFiltersRequestRedirectScheme::Https)
+        );
+        assert_eq!(redirect.status_code, Some(301));
+        assert_eq!(redirect.port, port);
+    }
+
+    #[test]
+    fn set_request_headers() {
+        let mut filters = vec![url_rewrite_filter(
+            HTTPRouteRulesFiltersUrlRewritePathType::ReplacePrefixMatch,
+            "/",
+        )];
+        add_removed_request_headers(&mut filters, &["X-Real-IP".to_string()]);
+        set_request_header(&mut filters, "X-Original-Path", "/api");
+        set_request_header(&mut filters, "x-original-path", "/v1");
+        assert_eq!(filters.len(), 2);
+        assert_eq!(filters[0].r#type, HTTPRouteRulesFiltersType::UrlRewrite);
+        let modifier = filters[1].request_header_modifier.as_ref().unwrap();
+        assert_eq!(modifier.remove, Some(vec!["X-Real-IP".to_string()]));
+        assert_eq!(
+            modifier.set,
+            Some(vec![HTTPRouteRulesFiltersRequestHeaderModifierSet {
+                name: "x-original-path".to_string(),
+                value: "/v1".to_string(),
+            }])
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use std::sync::Arc;
+
+use axum::{
+    Router,
+    extract::State,
+    http::{StatusCode, header},
+    response::IntoResponse,
+    routing::get,
+};
+use gateway_api::{
+    apis::experimental::httproutes::HTTPRoute,
+    apis::experimental::{tcproutes::TCPRoute, tlsroutes::TLSRoute, udproutes::UDPRoute},
+};
+use kube::{Api, api::ListParams};
+use prometheus::{Encoder, TextEncoder};
+
+use crate::ctx::Context;
+
+/// Serve Prometheus metrics and health probes on `--metrics-addr`.
+pub async fn serve(ctx: Arc<Context>) -> anyhow::Result<()> {
+    let listener = tokio::net::TcpListener::bind(ctx.args.metrics_addr).await?;
+    tracing::info!("Serving metrics on {}", ctx.args.metrics_addr);
+    let app = Router::new()
+        .route("/metrics", get(metrics))
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        .with_state(ctx);
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+async fn metrics(State(ctx): State<Arc<Context>>) -> impl IntoResponse {
+    let encoder = TextEncoder::new();
+    match encoder.encode_to_string(&ctx.metrics.registry.gather()) {
+        Ok(body) => (
+            StatusCode::OK,
+            [(header::CONTENT_TYPE, encoder.format_type().to_string())],
+            body,
+        ),
+        Err(err) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            [(header::CONTENT_TYPE, "text/plain".to_string())],
+            err.to_string(),
+        ),
+    }
+}
+
+async fn healthz() -> &'static str {
+    "ok"
+}
+
+async fn readyz(State(ctx): State<Arc<Context>>) -> (StatusCode, String) {
+    match check_ready(&ctx).await {
+        Ok(()) => (StatusCode::OK, "ok".to_string()),
+        Err(err) => (StatusCode::SERVICE_UNAVAILABLE, err.to_string()),
+    }
+}
+
+/// Check that the API server is reachable and route CRDs are installed.
+async fn check_ready(ctx: &Context) -> anyhow::Result<()> {
+    ctx.client
+        .apiserver_version()
+        .await
+        .map_err(|err| anyhow::anyhow!("API server is unavailable: {err}"))?;
+    let params = ListParams::default().limit(1);
+    Api::<HTTPRoute>::all(ctx.client.clone())
+        .list_metadata(&params)
+        .await
+        .map_err(|err| anyhow::anyhow!("HTTPRoute CRD is unavailable: {err}"))?;
+    if ctx.args.experimental {
+        Api::<TCPRoute>::all(ctx.client.clone())
+            .list_metadata(&params)
+            .await
+            .map_err(|err| anyhow::anyhow!("TCPRoute CRD is unavailable: {err}"))?;
+        Api::<UDPRoute>::all(ctx.client.clone())
+            .list_metadata(&params)
+            .await
+            .map_err(|err| anyhow::anyhow!("UDPRoute CRD is unavailable: {err}"))?;
+        Api::<TLSRoute>::all(ctx.client.clone())
+            .list_metadata(&params)
+            .await
+            .map_err(|err| anyhow::anyhow!("TLSRoute CRD is unavailable: {err}"))?;
+    }
+    if ctx.args.ready_requires_leader && !ctx.is_leader() {
+        anyhow::bail!("Not a leader");
+    }
+    Ok(())
+}
@@ -0,0 +1,88 @@
+//! Translation of Ingress resources to gateway-api routes.
+//!
+//! The operator binary runs the controller on top of this library,
+//! while [`Translator`] can be used by other tools to translate Ingresses
+//! without a cluster connection.
+
+use std::sync::Arc;
+
+use gateway_api::{
+    apis::experimental::httproutes::HTTPRoute,
+    apis::experimental::{tcproutes::TCPRoute, tlsroutes::TLSRoute, udproutes::UDPRoute},
+};
+use k8s_openapi::api::networking::v1::Ingress;
+
+pub mod args;
+pub mod backends;
+pub mod canary;
+pub mod consts;
+pub mod crd;
+pub mod ctx;
+pub mod err;
+pub mod fair_queue;
+pub mod filters;
+pub mod metrics;
+pub mod naming;
+pub mod providers;
+pub mod settings;
+pub mod templates;
+mod translation;
+pub mod utils;
+pub mod value_filters;
+
+pub use translation::{Translation, skip_reason, translate};
+
+use crate::{args::I2GArgs, backends::NamedPort, ctx::Context, err::I2GResult};
+
+/// Options of offline translation.
+#[derive(Debug, Clone)]
+pub struct TranslationOptions {
+    /// Operator configuration, same as the command line arguments.
+    pub args: I2GArgs,
+    /// Numbers of named service ports, since services can't be looked up offline.
+    pub ports: Vec<NamedPort>,
+}
+
+/// Route generated from an Ingress.
+#[derive(Debug, Clone)]
+pub enum GeneratedRoute {
+    HTTPRoute(HTTPRoute),
+    TCPRoute(TCPRoute),
+    UDPRoute(UDPRoute),
+    TLSRoute(TLSRoute),
+}
+
+/// Translator of Ingresses which doesn't need a running controller
+/// or a cluster connection.
+pub struct Translator {
+    ctx: Arc<Context>,
+}
+
+impl Translator {
+    pub fn new(options: TranslationOptions) -> anyhow::Result<Self> {
+        let ctx = Context::offline(options.args, options.ports)?;
+        Ok(Translator { ctx: Arc::new(ctx) })
+    }
+
+    /// Translate the Ingress to routes. Skipped rules are only logged.
+    pub async fn translate(&self, ingress: &Ingress) -> I2GResult<Vec<GeneratedRoute>> {
+        let translation = translate(ingress, self.ctx.clone()).await?;
+        let http = translation
+            .http_routes
+            .into_iter()
+            .map(GeneratedRoute::HTTPRoute);
+        let tcp = translation
+            .tcp_routes
+            .into_iter()
+            .map(GeneratedRoute::TCPRoute);
+        let udp = translation
+            .udp_routes
+            .into_iter()
+            .map(GeneratedRoute::UDPRoute);
+        let tls = translation
+            .tls_routes
+            .into_iter()
+            .map(GeneratedRoute::TLSRoute);
+        Ok(http.chain(tcp).chain(udp).chain(tls).collect())
+    }
+}
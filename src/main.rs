@@ -1,624 +1,1872 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    collections::{BTreeSet, HashSet},
+    sync::Arc,
+    time::Duration,
+};
 
-use futures::StreamExt;
+use futures::{StreamExt, channel::mpsc::UnboundedSender};
 use gateway_api::{
-    apis::experimental::tcproutes::{
-        TCPRoute, TCPRouteParentRefs, TCPRouteRules, TCPRouteRulesBackendRefs, TCPRouteSpec,
-    },
+    apis::experimental::httproutes::HTTPRoute,
+    apis::experimental::{tcproutes::TCPRoute, tlsroutes::TLSRoute, udproutes::UDPRoute},
+    backendtlspolicies::BackendTLSPolicy,
     gateways,
-    httproutes::{
-        HTTPRoute, HTTPRouteParentRefs, HTTPRouteRules, HTTPRouteRulesBackendRefs,
-        HTTPRouteRulesMatches, HTTPRouteRulesMatchesPath, HTTPRouteRulesMatchesPathType,
-        HTTPRouteSpec,
+    referencegrants::ReferenceGrant,
+};
+use i2g_operator::{
+    Translation, args, canary, consts,
+    crd::{
+        EnvoyBackend, I2GClassConfig, IngressTranslation, IngressTranslationSpec,
+        IngressTranslationStatus,
     },
+    ctx,
+    err::{I2GError, I2GResult, RetryPolicy},
+    metrics::SkipReason,
+    naming, providers,
+    settings::Settings,
+    skip_reason, translate,
+    utils::{self, ObjectMetaI2GExt},
 };
-use k8s_openapi::api::{
-    core::v1::Service,
-    networking::v1::{Ingress, IngressServiceBackend, ServiceBackendPort},
+use k8s_openapi::{
+    api::{
+        core::v1::{Secret, Service},
+        networking::v1::{Ingress, IngressBackend, IngressClass, IngressLoadBalancerIngress},
+    },
+    apimachinery::pkg::apis::meta::v1::Time,
+    chrono,
 };
 use kube::{
-    Api, Resource, ResourceExt,
-    api::{ObjectMeta, PatchParams},
-    runtime::controller::Action,
+    Api, CustomResourceExt, Resource, ResourceExt,
+    api::{DeleteParams, ListParams, ObjectMeta, PatchParams, Preconditions},
+    runtime::{
+        Predicate, WatchStreamExt,
+        controller::Action,
+        events::EventType,
+        finalizer, predicates,
+        reflector::{self, ObjectRef, Store},
+        watcher::{self, watcher},
+    },
 };
+use rand::Rng;
 use tracing::Instrument;
 
-use crate::{
-    err::{I2GError, I2GResult},
-    utils::{ObjectMetaI2GExt, sanitize_hostname},
-    value_filters::{HeadersMatchersList, MatchRule, MatcherList, QueryMatchersList},
-};
-
-mod args;
-mod consts;
-mod ctx;
-mod err;
-mod utils;
-mod value_filters;
+mod audit;
+mod convert;
+mod http_server;
+mod provision;
+mod report;
+mod snapshot;
+mod telemetry;
 
 #[global_allocator]
 static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
-pub struct RouteInputInfo<'a> {
-    pub ingress_name: String,
-    pub ingress_meta: &'a ObjectMeta,
-    pub ingress_namespace: String,
-    pub gw_name: String,
-    pub gw_namespace: String,
-    pub section_name: Option<String>,
-    pub hostname: String,
-    pub header_matchers: Option<value_filters::HeadersMatchersList>,
-    pub query_matchers: Option<value_filters::QueryMatchersList>,
-}
-
-async fn get_svc_port_number(
-    api: Api<Service>,
-    svc_name: &str,
-    port_def: &ServiceBackendPort,
-) -> Option<i32> {
-    if let Some(number) = port_def.number {
-        return Some(number);
-    }
-    let Some(port_name) = &port_def.name else {
-        return None;
-    };
-    let Some(port) = api
-        .get(svc_name)
-        .await
-        .ok()
-        .and_then(|o| o.spec)
-        .and_then(|s| s.ports)
-        .and_then(|ports| {
-            ports
-                .into_iter()
-                .find(|port| port.name.as_ref() == Some(port_name))
-        })
-    else {
-        tracing::warn!(
-            "Cannot resolve port {port_name} for service {svc_name} or service {svc_name} was not found"
-        );
-        return None;
-    };
+/// Whether the route was created by the operator.
+///
+/// Routes created before the management label was introduced
+/// are recognized by the field manager.
+fn is_managed(meta: &ObjectMeta) -> bool {
+    if meta
+        .labels
+        .as_ref()
+        .and_then(|labels| labels.get(consts::MANAGED_BY_LABEL))
+        .is_some_and(|value| value == consts::MANAGED_BY_VALUE)
+    {
+        return true;
+    }
+    meta.managed_fields.as_ref().is_some_and(|fields| {
+        fields
+            .iter()
+            .any(|field| field.manager.as_deref() == Some(consts::FIELD_MANAGER))
+    })
+}
 
-    return Some(port.port);
+/// Whether the route is clearly managed by another tool or controller.
+fn is_owned_by_others(meta: &ObjectMeta) -> bool {
+    let foreign_label = meta
+        .labels
+        .as_ref()
+        .and_then(|labels| labels.get(consts::MANAGED_BY_LABEL))
+        .is_some_and(|value| value != consts::MANAGED_BY_VALUE);
+    let foreign_controller = meta
+        .owner_references
+        .as_ref()
+        .is_some_and(|owners| owners.iter().any(|owner| owner.controller == Some(true)));
+    foreign_label || foreign_controller
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-enum EitherQueryOrHeaderMatcher {
-    Header(MatchRule),
-    Query(MatchRule),
+/// Reason why a generated object wasn't applied.
+enum Refusal {
+    /// The object was reconciled by a newer operator version.
+    Downgrade(String),
+    /// An object with the same name is managed or owned by someone else.
+    Foreign(String),
 }
 
-impl From<EitherQueryOrHeaderMatcher> for MatchRule {
-    fn from(value: EitherQueryOrHeaderMatcher) -> Self {
-        match value {
-            EitherQueryOrHeaderMatcher::Header(match_rule) => match_rule,
-            EitherQueryOrHeaderMatcher::Query(match_rule) => match_rule,
+impl Refusal {
+    fn into_message(self) -> String {
+        match self {
+            Refusal::Downgrade(message) | Refusal::Foreign(message) => message,
         }
     }
 }
 
-fn create_match_rulesets(
-    route_info: &RouteInputInfo<'_>,
-) -> Vec<(Option<HeadersMatchersList>, Option<QueryMatchersList>)> {
-    let mut headers_cart = vec![];
-    if let Some(header_matcher) = &route_info.header_matchers {
-        headers_cart = header_matcher
-            .0
-            .catesian_product()
-            .into_iter()
-            .map(|rules| {
-                rules
-                    .into_iter()
-                    .map(|rule| EitherQueryOrHeaderMatcher::Header(rule))
-                    .collect::<Vec<_>>()
-            })
-            .collect::<Vec<_>>();
-    }
-    let mut query_cart = vec![];
-    if let Some(query_matcher) = &route_info.query_matchers {
-        query_cart = query_matcher
-            .0
-            .catesian_product()
-            .into_iter()
-            .map(|rules| {
-                rules
-                    .into_iter()
-                    .map(|rule| EitherQueryOrHeaderMatcher::Query(rule))
-                    .collect::<Vec<_>>()
-            })
-            .collect::<Vec<_>>();
+/// Apply generated route using server-side apply.
+///
+/// Existing routes which weren't created by the operator are left untouched,
+/// unless `--adopt-existing` is set and nobody else owns them.
+/// Returns the reason if the route wasn't applied.
+async fn apply_route<K>(
+    ctx: &ctx::Context,
+    namespace: &str,
+    mut route: K,
+) -> I2GResult<Option<Refusal>>
+where
+    K: kube::Resource<Scope = kube::core::NamespaceResourceScope, DynamicType = ()>
+        + Clone
+        + std::fmt::Debug
+        + serde::Serialize
+        + serde::de::DeserializeOwned,
+{
+    let api = Api::<K>::namespaced(ctx.client.clone(), namespace);
+    let name = route.name_any();
+    // Removed fields change the hash, while changes made by others make
+    // the desired object differ from the live one.
+    let hash = naming::stable_hash(&[&serde_json::to_string(&route)?]);
+    route
+        .meta_mut()
+        .annotations
+        .get_or_insert_default()
+        .insert(consts::APPLIED_HASH.to_string(), hash.clone());
+    let cached = ctx
+        .managed_routes
+        .get(K::kind(&()).as_ref())
+        .and_then(|store| {
+            store.get(
+                &ObjectRef::new_with(&name, kube::api::ApiResource::erase::<K>(&()))
+                    .within(namespace),
+            )
+        });
+    if let Some(live) = cached
+        && live.annotations().get(consts::APPLIED_HASH) == Some(&hash)
+        && utils::is_json_subset(
+            &serde_json::to_value(&route)?,
+            &serde_json::to_value(&*live)?,
+        )
+    {
+        ctx.metrics.unchanged_patches.inc();
+        return Ok(None);
     }
-
-    if headers_cart.is_empty() && query_cart.is_empty() {
-        return vec![(None, None)];
+    let mut force = false;
+    let existing = api.get_opt(&name).await?;
+    if ctx.args.refuse_downgrade
+        && let Some(existing_version) = existing
+            .as_ref()
+            .and_then(|existing| existing.annotations().get(consts::OPERATOR_VERSION))
+        && utils::is_newer_version(existing_version, consts::VERSION)
+    {
+        let message = format!(
+            "{} {name} was reconciled by newer i2g-operator {existing_version}, refusing to overwrite it with {}",
+            K::kind(&()),
+            consts::VERSION
+        );
+        tracing::warn!("{message}");
+        return Ok(Some(Refusal::Downgrade(message)));
     }
-    if headers_cart.is_empty() {
-        let mut res = vec![];
-        for matchers in query_cart {
-            res.push((
-                None,
-                Some(QueryMatchersList(MatcherList(
-                    matchers.into_iter().map(Into::into).collect(),
-                ))),
-            ));
+    if let Some(existing) = existing
+        && !is_managed(existing.meta())
+    {
+        if !ctx.args.adopt_existing {
+            let message = format!(
+                "{} {name} already exists and isn't managed by i2g-operator, skipping. Use --adopt-existing to take it over.",
+                K::kind(&())
+            );
+            tracing::warn!("{message}");
+            return Ok(Some(Refusal::Foreign(message)));
         }
-        return res;
-    }
-    if query_cart.is_empty() {
-        let mut res = vec![];
-        for matchers in headers_cart {
-            res.push((
-                Some(HeadersMatchersList(MatcherList(
-                    matchers.into_iter().map(Into::into).collect(),
-                ))),
-                None,
-            ));
+        if is_owned_by_others(existing.meta()) {
+            let message = format!(
+                "{} {name} is managed by another controller, refusing to adopt it",
+                K::kind(&())
+            );
+            tracing::warn!("{message}");
+            return Ok(Some(Refusal::Foreign(message)));
         }
-        return res;
+        tracing::info!("Adopting existing {} {name}", K::kind(&()));
+        force = true;
     }
+    api.patch(
+        &name,
+        &PatchParams {
+            field_manager: Some(consts::FIELD_MANAGER.to_string()),
+            force,
+            ..PatchParams::default()
+        },
+        &kube::api::Patch::Apply(route),
+    )
+    .await?;
+    Ok(None)
+}
 
-    let to_permute = vec![headers_cart, query_cart];
+/// Apply a route generated from the Ingress.
+///
+/// If the name is taken by a route which doesn't belong to the operator
+/// and `--rename-conflicting-routes` is set, the route is applied with
+/// an alternative hashed name, which replaces the original one in `desired`.
+/// Returns the reason if the route wasn't applied.
+async fn apply_generated_route<K>(
+    ctx: &ctx::Context,
+    namespace: &str,
+    route: K,
+    desired: &mut HashSet<String>,
+) -> I2GResult<Option<String>>
+where
+    K: kube::Resource<Scope = kube::core::NamespaceResourceScope, DynamicType = ()>
+        + Clone
+        + std::fmt::Debug
+        + serde::Serialize
+        + serde::de::DeserializeOwned,
+{
+    let name = route.name_any();
+    let refusal = match apply_route(ctx, namespace, route.clone()).await? {
+        Some(Refusal::Foreign(message)) if ctx.args.rename_conflicting_routes => message,
+        refusal => return Ok(refusal.map(Refusal::into_message)),
+    };
+    let alternative = naming::alternative_name(&name);
+    tracing::info!(
+        "Applying {} {name} as {alternative}: {refusal}",
+        K::kind(&())
+    );
+    let mut route = route;
+    route.meta_mut().name = Some(alternative.clone());
+    desired.remove(&name);
+    desired.insert(alternative);
+    Ok(apply_route(ctx, namespace, route)
+        .await?
+        .map(Refusal::into_message))
+}
 
-    let mut res = vec![];
+/// Apply an object shared by Ingresses, e.g. a ReferenceGrant.
+/// Ingresses already owning the live object are kept in its owner annotation.
+async fn apply_shared<K>(
+    ctx: &ctx::Context,
+    namespace: &str,
+    mut obj: K,
+) -> I2GResult<Option<Refusal>>
+where
+    K: kube::Resource<Scope = kube::core::NamespaceResourceScope, DynamicType = ()>
+        + Clone
+        + std::fmt::Debug
+        + serde::Serialize
+        + serde::de::DeserializeOwned,
+{
+    let api = Api::<K>::namespaced(ctx.client.clone(), namespace);
+    if let Some(live) = api.get_opt(&obj.name_any()).await?
+        && is_managed(live.meta())
+    {
+        let mut owners = live.meta().shared_owners();
+        owners.extend(obj.meta().shared_owners());
+        obj.meta_mut().set_shared_owners(&owners);
+    }
+    apply_route(ctx, namespace, obj).await
+}
 
-    permutator::cartesian_product(
-        to_permute
-            .iter()
-            .map(|a| a.as_slice())
-            .collect::<Vec<_>>()
-            .as_slice(),
-        |product| {
-            let mut headers_list = vec![];
-            let mut query_list = vec![];
-            for item in product.to_vec().into_iter().flatten() {
-                match item {
-                    EitherQueryOrHeaderMatcher::Header(match_rule) => {
-                        headers_list.push(match_rule.clone())
-                    }
-                    EitherQueryOrHeaderMatcher::Query(match_rule) => {
-                        query_list.push(match_rule.clone())
-                    }
-                }
+/// Release an object shared by Ingresses which the Ingress no longer needs:
+/// the Ingress is removed from its owners and the object is deleted if it has none left.
+/// Objects which aren't managed by the operator or not owned by the Ingress are never touched.
+/// Updates are conditional on the observed version, so an owner added concurrently
+/// fails the reconcile instead of being lost.
+async fn release_shared<K>(
+    ctx: &ctx::Context,
+    owner: &str,
+    namespace: &str,
+    name: &str,
+) -> I2GResult<()>
+where
+    K: kube::Resource<Scope = kube::core::NamespaceResourceScope, DynamicType = ()>
+        + Clone
+        + std::fmt::Debug
+        + serde::de::DeserializeOwned,
+{
+    let api = Api::<K>::namespaced(ctx.client.clone(), namespace);
+    // The object is gone as well if its CRD was uninstalled.
+    let Some(obj) = api.get_opt(name).await? else {
+        return Ok(());
+    };
+    if !is_managed(obj.meta()) {
+        return Ok(());
+    }
+    let Some(owners) = obj.meta().release_shared_owner(owner) else {
+        return Ok(());
+    };
+    if owners.is_empty() {
+        tracing::info!("Deleting unused {} {namespace}/{name}", K::kind(&()));
+        let params = DeleteParams {
+            preconditions: Some(Preconditions {
+                resource_version: obj.resource_version(),
+                uid: None,
+            }),
+            ..DeleteParams::default()
+        };
+        api.delete(name, &params).await?;
+        return Ok(());
+    }
+    tracing::info!(
+        "Releasing {} {namespace}/{name}, still used by {}",
+        K::kind(&()),
+        owners.iter().cloned().collect::<Vec<_>>().join(", ")
+    );
+    let patch = serde_json::json!({
+        "metadata": {
+            "resourceVersion": obj.resource_version(),
+            "annotations": {
+                consts::OWNER_INGRESSES: owners.into_iter().collect::<Vec<_>>().join(","),
+            },
+        }
+    });
+    api.patch(
+        name,
+        &PatchParams::default(),
+        &kube::api::Patch::Merge(patch),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Release shared objects recorded on the Ingress which aren't in `desired`,
+/// see [`release_shared`]. Nothing is requested if the Ingress owns no shared objects.
+async fn release_shared_objects(
+    ctx: &ctx::Context,
+    ingress: &Ingress,
+    desired: &BTreeSet<String>,
+) -> I2GResult<()> {
+    let owner = utils::shared_owner(ingress);
+    for object in utils::shared_objects(ingress).difference(desired) {
+        let Some((kind, (namespace, name))) = object
+            .split_once(':')
+            .and_then(|(kind, object)| Some((kind, object.split_once('/')?)))
+        else {
+            tracing::warn!("Ignoring invalid shared object {object}");
+            continue;
+        };
+        match kind {
+            kind if kind == ReferenceGrant::kind(&()) => {
+                release_shared::<ReferenceGrant>(ctx, &owner, namespace, name).await?;
             }
-            let mut query_ruleset = None;
-            let mut header_ruleset = None;
-            if !query_list.is_empty() {
-                query_ruleset = Some(QueryMatchersList(MatcherList(query_list)));
+            kind if kind == EnvoyBackend::kind(&()) => {
+                release_shared::<EnvoyBackend>(ctx, &owner, namespace, name).await?;
             }
-            if !headers_list.is_empty() {
-                header_ruleset = Some(HeadersMatchersList(MatcherList(headers_list)));
+            kind if kind == BackendTLSPolicy::kind(&()) => {
+                release_shared::<BackendTLSPolicy>(ctx, &owner, namespace, name).await?;
             }
-            res.push((header_ruleset, query_ruleset));
-        },
-    );
-    if res.is_empty() {
-        return vec![(None, None)];
+            _ => tracing::warn!("Ignoring shared object of unknown kind {object}"),
+        }
     }
-    res
+    Ok(())
 }
 
-async fn create_http_routes(
-    ctx: Arc<ctx::Context>,
-    route_info: RouteInputInfo<'_>,
-    http: &k8s_openapi::api::networking::v1::HTTPIngressRuleValue,
-) -> anyhow::Result<Vec<HTTPRoute>> {
-    let safe_hostname = utils::sanitize_hostname(&route_info.hostname);
-    let gw_group = <gateways::Gateway as kube::Resource>::group(&());
-    let gw_kind = <gateways::Gateway as kube::Resource>::kind(&());
-
-    let split_routes = route_info
-        .ingress_meta
-        .annotations
-        .as_ref()
-        .and_then(|ann| ann.get(consts::SPLIT_ROUTES))
-        .map(|v| v.to_lowercase() == "true")
-        .unwrap_or(false);
+/// Validate the object with a server-side dry-run apply. Nothing is persisted.
+async fn dry_run_apply<K>(ctx: &ctx::Context, namespace: &str, obj: &K) -> Result<(), kube::Error>
+where
+    K: kube::Resource<Scope = kube::core::NamespaceResourceScope, DynamicType = ()>
+        + Clone
+        + std::fmt::Debug
+        + serde::Serialize
+        + serde::de::DeserializeOwned,
+{
+    Api::<K>::namespaced(ctx.client.clone(), namespace)
+        .patch(
+            &obj.name_any(),
+            &PatchParams {
+                field_manager: Some(consts::FIELD_MANAGER.to_string()),
+                dry_run: true,
+                ..PatchParams::default()
+            },
+            &kube::api::Patch::Apply(obj),
+        )
+        .await?;
+    Ok(())
+}
 
-    let match_ruleset = create_match_rulesets(&route_info);
-    tracing::debug!("Match ruleset: \n{match_ruleset:#?}");
+/// Validate objects generated for an Ingress with `i2g-operator/dry-run`
+/// and report the result as Events instead of applying them.
+async fn dry_run_ingress(
+    ingress: &Ingress,
+    ctx: &ctx::Context,
+    translation: Translation,
+) -> I2GResult<Action> {
+    let namespace = ingress.namespace().unwrap_or_default();
+    let mut results = vec![];
+    for grant in &translation.reference_grants {
+        let grant_namespace = grant.namespace().unwrap_or_default();
+        let result = dry_run_apply(ctx, &grant_namespace, grant).await;
+        results.push(("ReferenceGrant", grant.name_any(), result));
+    }
+    for policy in &translation.backend_tls_policies {
+        let policy_namespace = policy.namespace().unwrap_or_default();
+        let result = dry_run_apply(ctx, &policy_namespace, policy).await;
+        results.push(("BackendTLSPolicy", policy.name_any(), result));
+    }
+    for backend in &translation.envoy_backends {
+        let backend_namespace = backend.namespace().unwrap_or_default();
+        let result = dry_run_apply(ctx, &backend_namespace, backend).await;
+        results.push(("Backend", backend.name_any(), result));
+    }
+    for route in &translation.http_routes {
+        let result = dry_run_apply(ctx, &namespace, route).await;
+        results.push(("HTTPRoute", route.name_any(), result));
+    }
+    for route in &translation.tcp_routes {
+        let result = dry_run_apply(ctx, &namespace, route).await;
+        results.push(("TCPRoute", route.name_any(), result));
+    }
+    for route in &translation.udp_routes {
+        let result = dry_run_apply(ctx, &namespace, route).await;
+        results.push(("UDPRoute", route.name_any(), result));
+    }
+    for route in &translation.tls_routes {
+        let result = dry_run_apply(ctx, &namespace, route).await;
+        results.push(("TLSRoute", route.name_any(), result));
+    }
 
-    let mut rules = vec![];
+    for warning in translation.warnings {
+        ctx.publish_event(ingress, EventType::Warning, "RuleSkipped", warning)
+            .await;
+    }
+    let mut valid = vec![];
+    for (kind, name, result) in results {
+        match result {
+            Ok(()) => valid.push(format!("{kind} {name}")),
+            Err(err) => {
+                ctx.publish_event(
+                    ingress,
+                    EventType::Warning,
+                    "DryRunFailed",
+                    format!("{kind} {name} is invalid: {err}"),
+                )
+                .await;
+            }
+        }
+    }
+    ctx.publish_event(
+        ingress,
+        EventType::Normal,
+        "DryRun",
+        format!(
+            "{} objects would be applied: {}",
+            valid.len(),
+            valid.join(", ")
+        ),
+    )
+    .await;
+    ctx.reset_failures(&ObjectRef::from_obj(ingress));
+    Ok(Action::requeue(ctx.args.resync()))
+}
 
-    for path in &http.paths {
-        let Some(svc) = &path.backend.service else {
-            tracing::warn!("Skipping backend without service");
+/// Delete routes generated from the Ingress which are no longer in the desired set.
+/// Routes of the other mode (shadow or production) are kept, unless `all_modes` is set.
+async fn prune_routes<K>(
+    ctx: &ctx::Context,
+    namespace: &str,
+    ingress_name: &str,
+    desired: &HashSet<String>,
+    all_modes: bool,
+) -> I2GResult<()>
+where
+    K: kube::Resource<Scope = kube::core::NamespaceResourceScope, DynamicType = ()>
+        + Clone
+        + std::fmt::Debug
+        + serde::de::DeserializeOwned,
+{
+    let api = Api::<K>::namespaced(ctx.client.clone(), namespace);
+    let selector = format!("{}={}", consts::MANAGED_BY_LABEL, consts::MANAGED_BY_VALUE);
+    let shadow_mode = ctx.args.shadow_prefix.is_some();
+    for route in api.list(&ListParams::default().labels(&selector)).await? {
+        let name = route.name_any();
+        let owned = route
+            .annotations()
+            .get(consts::OWNER_INGRESS)
+            .is_some_and(|owner| owner == ingress_name);
+        let shadow = route.labels().contains_key(consts::SHADOW_LABEL);
+        if !owned || (!all_modes && shadow != shadow_mode) || desired.contains(&name) {
             continue;
-        };
-        let Some(svc_port) = &svc.port else {
-            tracing::warn!("Skipping backend without service port");
+        }
+        tracing::info!("Deleting orphaned {} {name}", K::kind(&()));
+        api.delete(&name, &DeleteParams::default()).await?;
+    }
+    Ok(())
+}
+
+/// Stop managing routes generated from the Ingress: remove the management label,
+/// the owner annotation and owner references, so they're neither updated nor deleted.
+async fn orphan_routes<K>(ctx: &ctx::Context, namespace: &str, ingress_name: &str) -> I2GResult<()>
+where
+    K: kube::Resource<Scope = kube::core::NamespaceResourceScope, DynamicType = ()>
+        + Clone
+        + std::fmt::Debug
+        + serde::de::DeserializeOwned,
+{
+    let api = Api::<K>::namespaced(ctx.client.clone(), namespace);
+    let selector = format!("{}={}", consts::MANAGED_BY_LABEL, consts::MANAGED_BY_VALUE);
+    for route in api.list(&ListParams::default().labels(&selector)).await? {
+        let owned = route
+            .annotations()
+            .get(consts::OWNER_INGRESS)
+            .is_some_and(|owner| owner == ingress_name);
+        if !owned {
             continue;
-        };
-        let Some(svc_port_number) = get_svc_port_number(
-            Api::namespaced(ctx.client.clone(), &route_info.ingress_namespace),
-            &svc.name,
-            svc_port,
+        }
+        let owners = route
+            .owner_references()
+            .iter()
+            .filter(|owner| owner.kind != Ingress::kind(&()))
+            .cloned()
+            .collect::<Vec<_>>();
+        let patch = serde_json::json!({
+            "metadata": {
+                "labels": { consts::MANAGED_BY_LABEL: null },
+                "annotations": { consts::OWNER_INGRESS: null },
+                "ownerReferences": owners,
+            }
+        });
+        let name = route.name_any();
+        tracing::info!("Orphaning {} {name}", K::kind(&()));
+        api.patch(
+            &name,
+            &PatchParams::default(),
+            &kube::api::Patch::Merge(patch),
         )
+        .await?;
+    }
+    Ok(())
+}
+
+/// Whether the caches contain routes generated from the Ingress.
+fn has_generated_routes(ctx: &ctx::Context, namespace: &str, ingress_name: &str) -> bool {
+    ctx.managed_routes.values().any(|store| {
+        store.state().iter().any(|route| {
+            route.namespace().as_deref() == Some(namespace)
+                && route
+                    .annotations()
+                    .get(consts::OWNER_INGRESS)
+                    .map(String::as_str)
+                    == Some(ingress_name)
+        })
+    })
+}
+
+/// Delete or orphan routes of the Ingress whose translation was disabled
+/// after they had been generated, according to `--disabled-routes`.
+/// The generated routes annotation of the Ingress is removed in both cases.
+async fn release_disabled_routes(ctx: &ctx::Context, ingress: &Ingress) -> I2GResult<()> {
+    let namespace = ingress.namespace().unwrap_or_default();
+    let ingress_name = ingress.name_any();
+    if !ctx.is_leader() {
+        return Ok(());
+    }
+    match ctx.args.disabled_routes {
+        _ if !has_generated_routes(ctx, &namespace, &ingress_name) => {}
+        args::DisabledRoutes::Delete => {
+            tracing::info!("Translation is disabled, deleting generated routes");
+            let desired = HashSet::new();
+            prune_routes::<HTTPRoute>(ctx, &namespace, &ingress_name, &desired, true).await?;
+            if ctx.args.experimental {
+                prune_routes::<TCPRoute>(ctx, &namespace, &ingress_name, &desired, true).await?;
+                prune_routes::<UDPRoute>(ctx, &namespace, &ingress_name, &desired, true).await?;
+                prune_routes::<TLSRoute>(ctx, &namespace, &ingress_name, &desired, true).await?;
+            }
+            release_shared_objects(ctx, ingress, &BTreeSet::new()).await?;
+            sync_list_annotation(ctx, ingress, consts::SHARED_OBJECTS, &[]).await?;
+        }
+        args::DisabledRoutes::Orphan => {
+            tracing::info!("Translation is disabled, orphaning generated routes");
+            orphan_routes::<HTTPRoute>(ctx, &namespace, &ingress_name).await?;
+            if ctx.args.experimental {
+                orphan_routes::<TCPRoute>(ctx, &namespace, &ingress_name).await?;
+                orphan_routes::<UDPRoute>(ctx, &namespace, &ingress_name).await?;
+                orphan_routes::<TLSRoute>(ctx, &namespace, &ingress_name).await?;
+            }
+        }
+    }
+    sync_list_annotation(ctx, ingress, consts::GENERATED_ROUTES, &[]).await
+}
+
+#[tracing::instrument(skip(ingress, ctx), fields(ingress = ingress.name_any(), namespace = ingress.namespace()), err)]
+pub async fn reconcile(ingress: Arc<Ingress>, ctx: Arc<ctx::Context>) -> I2GResult<Action> {
+    // Shards are assigned statically, so the Ingress never needs to be rechecked.
+    if !ctx.is_namespace_in_shard(&ingress.namespace().unwrap_or_default()) {
+        tracing::debug!("Namespace belongs to another shard, skipping reconciliation");
+        ctx.metrics.record_skip(SkipReason::OtherShard);
+        return Ok(Action::await_change());
+    }
+    if !ctx.is_leader() {
+        tracing::debug!("Not a leader, skipping reconciliation");
+        ctx.metrics.record_skip(SkipReason::NotLeader);
+        return Ok(Action::requeue(ctx.args.resync()));
+    }
+    if let Some(remaining) = ctx.leader_warmup_remaining() {
+        tracing::debug!("Leadership was just acquired, waiting for warm-up to finish");
+        return Ok(Action::requeue(remaining));
+    }
+    // Hold the permits until the end of reconciliation.
+    // The fair queue enforces the namespace limit itself.
+    let _fair_permit = match &ctx.fair_queue {
+        Some(queue) => Some(
+            queue
+                .acquire(&ingress.namespace().unwrap_or_default())
+                .await,
+        ),
+        None => None,
+    };
+    let _permit = match ctx.args.namespace_concurrency {
+        Some(limit) if ctx.fair_queue.is_none() => {
+            let namespace = ingress.namespace().unwrap_or_default();
+            let Ok(permit) = ctx
+                .namespace_semaphore(&namespace, limit)
+                .try_acquire_owned()
+            else {
+                tracing::debug!("Too many reconciles in namespace {namespace}, postponing");
+                return Ok(Action::requeue(Duration::from_secs(1)));
+            };
+            Some(permit)
+        }
+        _ => None,
+    };
+
+    // Owner references remove routes together with the Ingress,
+    // otherwise they are deleted by the operator before the Ingress is removed.
+    let result = if ctx.args.link_to_ingress {
+        apply_ingress(ingress, ctx.clone()).await
+    } else {
+        let ingress_namespace = ingress
+            .namespace()
+            .ok_or_else(|| anyhow::anyhow!("Ingress doesn't have a namespace"))?;
+        let api = Api::<Ingress>::namespaced(ctx.client.clone(), &ingress_namespace);
+        finalizer(&api, consts::CLEANUP_FINALIZER, ingress, |event| async {
+            match event {
+                finalizer::Event::Apply(ingress) => apply_ingress(ingress, ctx.clone()).await,
+                finalizer::Event::Cleanup(ingress) => cleanup_ingress(ingress, ctx.clone()).await,
+            }
+        })
         .await
-        else {
+        .map_err(|err| I2GError::FinalizerError(Box::new(err)))
+    };
+    match result {
+        Err(err) if err.is_lost_leadership() => {
+            tracing::warn!("Lost leadership during reconciliation, aborting");
+            Ok(Action::requeue(ctx.args.resync()))
+        }
+        result => result,
+    }
+}
+
+/// Copy addresses of the gateways to the Ingress status,
+/// so tools relying on `status.loadBalancer` keep working.
+async fn sync_ingress_status(
+    ctx: &ctx::Context,
+    ingress: &Ingress,
+    gateways: &[(String, String)],
+) -> I2GResult<()> {
+    let mut addresses = vec![];
+    for (gw_name, gw_namespace) in gateways {
+        let gateway = Api::<gateways::Gateway>::namespaced(ctx.client.clone(), gw_namespace)
+            .get_opt(gw_name)
+            .await?;
+        let Some(gateway) = gateway else {
             tracing::warn!(
-                "Skipping backend with unresolvable service port for service {}",
-                &svc.name
+                "Gateway {gw_namespace}/{gw_name} was not found, skipping its addresses"
             );
             continue;
         };
-        let match_type = match path.path_type.as_str() {
-            "Prefix" => HTTPRouteRulesMatchesPathType::PathPrefix,
-            "Exact" => HTTPRouteRulesMatchesPathType::Exact,
-            "ImplementationSpecific" => HTTPRouteRulesMatchesPathType::PathPrefix,
-            _ => {
-                return Err(
-                    anyhow::anyhow!("Unknown path type: {}", path.path_type.as_str()).into(),
-                );
-            }
-        };
-        let mut san_path = String::from("");
-        if let Some(path) = &path.path {
-            san_path = format!("{}-", sanitize_hostname(path));
-        }
-
-        for (num, (header_matchers, query_matchers)) in match_ruleset.iter().enumerate() {
-            rules.push(HTTPRouteRules {
-                name: Some(format!("{}{}", san_path, num)),
-                backend_refs: Some(
-                    [HTTPRouteRulesBackendRefs {
-                        name: svc.name.clone(),
-                        port: Some(svc_port_number),
-                        kind: None,
-                        group: None,
-                        namespace: None,
-                        filters: None,
-                        weight: None,
-                    }]
-                    .to_vec(),
-                ),
-                matches: Some(vec![HTTPRouteRulesMatches {
-                    headers: header_matchers.clone().map(Into::into),
-                    method: None,
-                    query_params: query_matchers.clone().map(Into::into),
-                    path: Some(HTTPRouteRulesMatchesPath {
-                        r#type: Some(match_type.clone()),
-                        value: path.path.clone(),
-                    }),
-                }]),
-                filters: None,
-                timeouts: None,
+        let gateway_addresses = gateway
+            .status
+            .and_then(|status| status.addresses)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|address| match address.r#type.as_deref() {
+                Some("Hostname") => IngressLoadBalancerIngress {
+                    hostname: Some(address.value),
+                    ..Default::default()
+                },
+                _ => IngressLoadBalancerIngress {
+                    ip: Some(address.value),
+                    ..Default::default()
+                },
             });
+        for address in gateway_addresses {
+            if !addresses.contains(&address) {
+                addresses.push(address);
+            }
         }
     }
-    if rules.is_empty() {
-        return Err(anyhow::anyhow!("No valid paths found").into());
+    if addresses.is_empty() {
+        return Ok(());
+    }
+    let current = ingress
+        .status
+        .as_ref()
+        .and_then(|status| status.load_balancer.as_ref())
+        .and_then(|lb| lb.ingress.as_ref());
+    if current == Some(&addresses) {
+        return Ok(());
     }
+    let namespace = ingress.namespace().unwrap_or_default();
+    let patch = serde_json::json!({
+        "status": {
+            "loadBalancer": {
+                "ingress": addresses,
+            }
+        }
+    });
+    Api::<Ingress>::namespaced(ctx.client.clone(), &namespace)
+        .patch_status(
+            &ingress.name_any(),
+            &PatchParams::default(),
+            &kube::api::Patch::Merge(patch),
+        )
+        .await?;
+    Ok(())
+}
 
-    // If split_routes is enabled, create a separate HTTPRoute for each rule.
-    if split_routes {
-        return Ok(rules
-            .into_iter()
-            .enumerate()
-            .map(|(index, rule)| {
-                HTTPRoute::new(
-                    &format!("{}-{}-{}", route_info.ingress_name, safe_hostname, index),
-                    HTTPRouteSpec {
-                        hostnames: Some(vec![route_info.hostname.clone()]),
-                        parent_refs: Some(
-                            [HTTPRouteParentRefs {
-                                group: Some(gw_group.to_string()),
-                                kind: Some(gw_kind.to_string()),
-                                name: route_info.gw_name.to_string(),
-                                namespace: Some(route_info.gw_namespace.to_string()),
-                                port: None,
-                                section_name: route_info.section_name.clone(),
-                            }]
-                            .to_vec(),
-                        ),
-                        rules: Some(vec![rule]),
-                    },
-                )
-            })
-            .collect());
-    }
-
-    // Split routes is disabled, create a single HTTPRoute with all rules.
-    Ok([HTTPRoute::new(
-        &format!("{}-{}-http", route_info.ingress_name, safe_hostname),
-        HTTPRouteSpec {
-            hostnames: Some(vec![route_info.hostname.to_string()]),
-            // parent_refs: None,
-            parent_refs: Some(
-                [HTTPRouteParentRefs {
-                    group: Some(gw_group.to_string()),
-                    kind: Some(gw_kind.to_string()),
-                    name: route_info.gw_name.to_string(),
-                    namespace: Some(route_info.gw_namespace.to_string()),
-                    port: None,
-                    section_name: route_info.section_name.clone(),
-                }]
-                .to_vec(),
-            ),
-            rules: Some(rules),
+/// Record the translation result in the IngressTranslation named after the Ingress.
+/// It's owned by the Ingress, so it's removed together with it.
+async fn sync_translation_status(
+    ctx: &ctx::Context,
+    ingress: &Ingress,
+    status: IngressTranslationStatus,
+) -> I2GResult<()> {
+    let namespace = ingress.namespace().unwrap_or_default();
+    let name = ingress.name_any();
+    let api = Api::<IngressTranslation>::namespaced(ctx.client.clone(), &namespace);
+    let mut record = IngressTranslation::new(
+        &name,
+        IngressTranslationSpec {
+            ingress: name.clone(),
         },
-    )]
-    .to_vec())
+    );
+    record.meta_mut().add_owner(ingress);
+    record.meta_mut().add_managed_by_label();
+    let params = PatchParams::apply(consts::FIELD_MANAGER).force();
+    api.patch(&name, &params, &kube::api::Patch::Apply(&record))
+        .await?;
+    record.status = Some(status);
+    api.patch_status(&name, &params, &kube::api::Patch::Apply(&record))
+        .await?;
+    Ok(())
 }
 
-async fn create_tcp_routes(
-    ctx: Arc<ctx::Context>,
-    route_info: RouteInputInfo<'_>,
-    svc: &IngressServiceBackend,
-) -> anyhow::Result<TCPRoute> {
-    let safe_hostname = utils::sanitize_hostname(&route_info.hostname);
-    let gw_group = <gateways::Gateway as kube::Resource>::group(&());
-    let gw_kind = <gateways::Gateway as kube::Resource>::kind(&());
-
-    let Some(svc_port) = &svc.port else {
-        tracing::warn!("Skipping backend without service port");
-        return Err(anyhow::anyhow!("Backend doesn't have port").into());
-    };
-
-    let Some(svc_port_number) = get_svc_port_number(
-        Api::namespaced(ctx.client.clone(), &route_info.ingress_namespace),
-        &svc.name,
-        svc_port,
-    )
-    .await
-    else {
-        tracing::warn!(
-            "skipping backend with unresolvable service port for service {}",
-            &svc.name
-        );
-        return Err(
-            anyhow::anyhow!(format!("Couldn't resolve port for a service {}", &svc.name)).into(),
-        );
-    };
-    Ok(TCPRoute::new(
-        &format!("{}-{}-tcp", route_info.ingress_name, safe_hostname),
-        TCPRouteSpec {
-            use_default_gateways: None,
-            rules: [TCPRouteRules {
-                name: None,
-                backend_refs: [TCPRouteRulesBackendRefs {
-                    name: svc.name.clone(),
-                    port: Some(svc_port_number),
-                    kind: None,
-                    group: None,
-                    namespace: None,
-                    weight: None,
-                }]
-                .to_vec(),
-            }]
-            .to_vec(),
-            parent_refs: Some(
-                [TCPRouteParentRefs {
-                    group: Some(gw_group.to_string()),
-                    kind: Some(gw_kind.to_string()),
-                    name: route_info.gw_name.to_string(),
-                    namespace: Some(route_info.gw_namespace.to_string()),
-                    port: None,
-                    section_name: route_info.section_name.clone(),
-                }]
-                .to_vec(),
-            ),
-        },
-    ))
+/// Keep the comma-separated list annotation of the Ingress in sync with `values`,
+/// e.g. `i2g-operator/untranslated-annotations` with controller annotations
+/// which can't be translated. The annotation is removed if there are no values.
+async fn sync_list_annotation(
+    ctx: &ctx::Context,
+    ingress: &Ingress,
+    key: &str,
+    values: &[String],
+) -> I2GResult<()> {
+    let desired = (!values.is_empty()).then(|| values.join(","));
+    if ingress.annotations().get(key) == desired.as_ref() {
+        return Ok(());
+    }
+    let namespace = ingress.namespace().unwrap_or_default();
+    // Null removes the annotation.
+    let patch = serde_json::json!({
+        "metadata": {
+            "annotations": {
+                key: desired,
+            }
+        }
+    });
+    Api::<Ingress>::namespaced(ctx.client.clone(), &namespace)
+        .patch(
+            &ingress.name_any(),
+            &PatchParams::default(),
+            &kube::api::Patch::Merge(patch),
+        )
+        .await?;
+    Ok(())
 }
 
-#[tracing::instrument(skip(ingress, ctx), fields(ingress = ingress.name_any(), namespace = ingress.namespace()), err)]
-pub async fn reconcile(ingress: Arc<Ingress>, ctx: Arc<ctx::Context>) -> I2GResult<Action> {
-    if !ctx.is_leader.load(std::sync::atomic::Ordering::Relaxed) {
-        tracing::debug!("Not a leader, skipping reconciliation");
-        return Ok(Action::requeue(Duration::from_secs(20)));
+/// Delete all routes generated from the Ingress.
+async fn cleanup_ingress(ingress: Arc<Ingress>, ctx: Arc<ctx::Context>) -> I2GResult<Action> {
+    tracing::info!("Ingress is being deleted, removing generated routes");
+    let ingress_namespace = ingress
+        .namespace()
+        .ok_or_else(|| anyhow::anyhow!("Ingress doesn't have a namespace"))?;
+    let ingress_name = ingress.name_any();
+    let desired = HashSet::new();
+    prune_routes::<HTTPRoute>(&ctx, &ingress_namespace, &ingress_name, &desired, true).await?;
+    if ctx.args.experimental {
+        prune_routes::<TCPRoute>(&ctx, &ingress_namespace, &ingress_name, &desired, true).await?;
+        prune_routes::<UDPRoute>(&ctx, &ingress_namespace, &ingress_name, &desired, true).await?;
+        prune_routes::<TLSRoute>(&ctx, &ingress_namespace, &ingress_name, &desired, true).await?;
     }
+    release_shared_objects(&ctx, &ingress, &BTreeSet::new()).await?;
+    ctx.reset_failures(&ObjectRef::from_obj(ingress.as_ref()));
+    Ok(Action::await_change())
+}
 
-    // Only translate if the annotation is present and true
-    // or if skip_by_default is false and
-    // the annotation is not present or equals to true
-    let skip_translation = ingress
-        .meta()
-        .annotations
-        .as_ref()
-        .and_then(|ann| ann.get(consts::TRANSLATE_INGRESS))
-        .map(|v| v.to_lowercase() != "true")
-        .unwrap_or(ctx.args.skip_by_default);
+/// Fail with [`I2GError::LostLeadership`] unless we're the leader.
+/// Leadership may be lost while we're applying routes. In this case we stop
+/// before the next request and leave the rest to the new leader.
+/// A patch which is already in flight is not interrupted.
+fn ensure_leader(ctx: &ctx::Context) -> I2GResult<()> {
+    if !ctx.is_leader() {
+        return Err(I2GError::LostLeadership);
+    }
+    Ok(())
+}
 
-    if skip_translation {
-        tracing::info!("Skipping translation due to annotation or operator settings");
-        return Ok(Action::requeue(Duration::from_secs(60)));
+/// Translate the Ingress and apply generated routes.
+async fn apply_ingress(ingress: Arc<Ingress>, ctx: Arc<ctx::Context>) -> I2GResult<Action> {
+    if let Some((reason, message)) = skip_reason(&ingress, &ctx).await? {
+        tracing::info!("Skipping translation: {message}");
+        ctx.metrics.record_skip(reason);
+        if reason == SkipReason::SkipAnnotation {
+            release_disabled_routes(&ctx, &ingress)
+                .instrument(tracing::info_span!("Releasing routes of disabled Ingress"))
+                .await?;
+        }
+        ctx.reset_failures(&ObjectRef::from_obj(ingress.as_ref()));
+        return Ok(Action::requeue(ctx.args.resync()));
     }
 
     tracing::info!("Reconciling Ingress");
-    let ingress_spec = ingress
-        .spec
-        .as_ref()
-        .ok_or(anyhow::anyhow!("Ingres doesn't have spec section"))?;
-    let ingress_rules = ingress_spec
-        .rules
-        .as_ref()
-        .ok_or_else(|| anyhow::anyhow!("Ingress doesn't have any routing rules"))?;
+    ctx.metrics.reconciles.inc();
     let ingress_namespace = ingress
         .namespace()
         .ok_or_else(|| anyhow::anyhow!("Ingress doesn't have a namespace"))?;
+    let translation = translate(&ingress, ctx.clone()).await?;
+    let dry_run = ingress
+        .annotations()
+        .get(consts::DRY_RUN)
+        .is_some_and(|v| v.to_lowercase() == "true");
+    if dry_run {
+        ensure_leader(&ctx)?;
+        return dry_run_ingress(&ingress, &ctx, translation)
+            .instrument(tracing::info_span!("Validating generated routes"))
+            .await;
+    }
+    let prune = !translation.failed;
+    let gateways = translation.gateways.clone();
+    let route_count = translation.route_count();
+    let mut warnings = translation.warnings.clone();
+    let mut refused = vec![];
+    let desired_shared = translation
+        .reference_grants
+        .iter()
+        .map(utils::shared_object)
+        .chain(translation.envoy_backends.iter().map(utils::shared_object))
+        .chain(
+            translation
+                .backend_tls_policies
+                .iter()
+                .map(utils::shared_object),
+        )
+        .collect::<BTreeSet<_>>();
+    let mut desired = translation
+        .http_routes
+        .iter()
+        .map(ResourceExt::name_any)
+        .chain(translation.tcp_routes.iter().map(ResourceExt::name_any))
+        .chain(translation.udp_routes.iter().map(ResourceExt::name_any))
+        .chain(translation.tls_routes.iter().map(ResourceExt::name_any))
+        .collect::<HashSet<_>>();
 
-    let desired_section_name = ingress
-        .meta()
-        .annotations
-        .as_ref()
-        .and_then(|ann| ann.get(consts::DESIRED_SECTION))
-        .cloned();
+    // Gateways and grants are applied first, so routes can be attached right away.
+    if route_count > 0
+        && provision::uses_provisioned_gateway(&ingress)
+        && let Some(provisioned) =
+            provision::desired_gateway(&ctx, &ingress_namespace, &mut warnings).await?
+    {
+        if let Some(grant) = provisioned.secret_grant {
+            ensure_leader(&ctx)?;
+            if let Some(refusal) = apply_route(&ctx, &ingress_namespace, grant)
+                .instrument(tracing::info_span!("Applying Secret ReferenceGrant"))
+                .await?
+            {
+                refused.push(refusal.into_message());
+            }
+        }
+        ensure_leader(&ctx)?;
+        let gateway_namespace = provisioned.gateway.namespace().unwrap_or_default();
+        if let Some(refusal) = apply_route(&ctx, &gateway_namespace, provisioned.gateway)
+            .instrument(tracing::info_span!("Applying provisioned Gateway"))
+            .await?
+        {
+            refused.push(refusal.into_message());
+        }
+    }
+    // Shared objects are recorded before they're applied, so they're released
+    // even if the reconcile fails in between.
+    let recorded_shared = utils::shared_objects(&ingress);
+    if !desired_shared.is_subset(&recorded_shared) {
+        ensure_leader(&ctx)?;
+        let shared = recorded_shared
+            .union(&desired_shared)
+            .cloned()
+            .collect::<Vec<_>>();
+        sync_list_annotation(&ctx, &ingress, consts::SHARED_OBJECTS, &shared)
+            .instrument(tracing::info_span!("Recording shared objects"))
+            .await?;
+    }
+    for grant in translation.reference_grants {
+        ensure_leader(&ctx)?;
+        let grant_namespace = grant.namespace().unwrap_or_default();
+        if let Some(refusal) = apply_shared(&ctx, &grant_namespace, grant)
+            .instrument(tracing::info_span!("Applying ReferenceGrant"))
+            .await?
+        {
+            refused.push(refusal.into_message());
+        }
+    }
+    // Policies are applied before routes, so backends are never reached over plain HTTP.
+    for policy in translation.backend_tls_policies {
+        ensure_leader(&ctx)?;
+        let policy_namespace = policy.namespace().unwrap_or_default();
+        if let Some(refusal) = apply_shared(&ctx, &policy_namespace, policy)
+            .instrument(tracing::info_span!("Applying BackendTLSPolicy"))
+            .await?
+        {
+            refused.push(refusal.into_message());
+        }
+    }
+    for backend in translation.envoy_backends {
+        ensure_leader(&ctx)?;
+        let backend_namespace = backend.namespace().unwrap_or_default();
+        if let Some(refusal) = apply_shared(&ctx, &backend_namespace, backend)
+            .instrument(tracing::info_span!("Applying Envoy Gateway Backend"))
+            .await?
+        {
+            refused.push(refusal.into_message());
+        }
+    }
+    for route in translation.http_routes {
+        ensure_leader(&ctx)?;
+        if let Some(refusal) = apply_generated_route(&ctx, &ingress_namespace, route, &mut desired)
+            .instrument(tracing::info_span!("Applying generated HTTPRoute"))
+            .await?
+        {
+            refused.push(refusal);
+            continue;
+        }
+        ctx.metrics
+            .generated_routes
+            .with_label_values(&["HTTPRoute"])
+            .inc();
+    }
+    for route in translation.tcp_routes {
+        ensure_leader(&ctx)?;
+        if let Some(refusal) = apply_generated_route(&ctx, &ingress_namespace, route, &mut desired)
+            .instrument(tracing::info_span!("Applying generated TCPRoute"))
+            .await?
+        {
+            refused.push(refusal);
+            continue;
+        }
+        ctx.metrics
+            .generated_routes
+            .with_label_values(&["TCPRoute"])
+            .inc();
+    }
+    for route in translation.udp_routes {
+        ensure_leader(&ctx)?;
+        if let Some(refusal) = apply_generated_route(&ctx, &ingress_namespace, route, &mut desired)
+            .instrument(tracing::info_span!("Applying generated UDPRoute"))
+            .await?
+        {
+            refused.push(refusal);
+            continue;
+        }
+        ctx.metrics
+            .generated_routes
+            .with_label_values(&["UDPRoute"])
+            .inc();
+    }
+    for route in translation.tls_routes {
+        ensure_leader(&ctx)?;
+        if let Some(refusal) = apply_generated_route(&ctx, &ingress_namespace, route, &mut desired)
+            .instrument(tracing::info_span!("Applying generated TLSRoute"))
+            .await?
+        {
+            refused.push(refusal);
+            continue;
+        }
+        ctx.metrics
+            .generated_routes
+            .with_label_values(&["TLSRoute"])
+            .inc();
+    }
 
-    let gw_namespace = ingress
-        .meta()
-        .annotations
-        .as_ref()
-        .and_then(|annot| annot.get(consts::GATEWAY_NAMESPACE))
-        .unwrap_or(&ctx.args.default_gateway_namespace);
+    let untranslated = providers::unsupported_annotations(ctx.provider(&ingress), &ingress);
+    ensure_leader(&ctx)?;
+    sync_list_annotation(
+        &ctx,
+        &ingress,
+        consts::UNTRANSLATED_ANNOTATIONS,
+        &untranslated,
+    )
+    .instrument(tracing::info_span!("Updating untranslated annotations"))
+    .await?;
 
-    let gw_name = ingress
-        .meta()
-        .annotations
-        .as_ref()
-        .and_then(|annot| annot.get(consts::GATEWAY_NAME))
-        .unwrap_or(&ctx.args.default_gateway_name);
+    if ctx.args.update_ingress_status && !gateways.is_empty() {
+        sync_ingress_status(&ctx, &ingress, &gateways)
+            .instrument(tracing::info_span!("Updating Ingress status"))
+            .await?;
+    }
 
-    let header_matchers = ingress
-        .meta()
-        .annotations
-        .as_ref()
-        .map(|annotations| {
-            MatcherList::from_annotations(annotations, consts::HEADER_FILTERS_PREFIX)
-        })
-        .map(HeadersMatchersList);
-    let query_matchers = ingress
-        .meta()
-        .annotations
-        .as_ref()
-        .map(|annotations| MatcherList::from_annotations(annotations, consts::QUERY_FILTERS_PREFIX))
-        .map(QueryMatchersList);
+    if prune {
+        ensure_leader(&ctx)?;
+        let ingress_name = ingress.name_any();
+        prune_routes::<HTTPRoute>(&ctx, &ingress_namespace, &ingress_name, &desired, false).await?;
+        if ctx.args.experimental {
+            prune_routes::<TCPRoute>(&ctx, &ingress_namespace, &ingress_name, &desired, false)
+                .await?;
+            prune_routes::<UDPRoute>(&ctx, &ingress_namespace, &ingress_name, &desired, false)
+                .await?;
+            prune_routes::<TLSRoute>(&ctx, &ingress_namespace, &ingress_name, &desired, false)
+                .await?;
+        }
+        release_shared_objects(&ctx, &ingress, &desired_shared).await?;
+        let shared = desired_shared.into_iter().collect::<Vec<_>>();
+        sync_list_annotation(&ctx, &ingress, consts::SHARED_OBJECTS, &shared).await?;
+    }
 
-    let default_backend = ingress_spec.default_backend.as_ref();
+    let mut routes = desired.iter().cloned().collect::<Vec<_>>();
+    routes.sort();
+    ensure_leader(&ctx)?;
+    sync_list_annotation(&ctx, &ingress, consts::GENERATED_ROUTES, &routes)
+        .instrument(tracing::info_span!("Updating generated routes annotation"))
+        .await?;
 
-    for rule in ingress_rules {
-        let Some(host) = &rule.host else {
-            tracing::warn!("Skipping rule without host");
-            continue;
+    if ctx.args.translation_status {
+        ensure_leader(&ctx)?;
+        let status = IngressTranslationStatus {
+            route_count: routes.len(),
+            routes,
+            last_sync_time: Some(Time(chrono::Utc::now())),
+            warnings: warnings.iter().chain(&refused).cloned().collect(),
+            untranslated_annotations: untranslated.clone(),
         };
+        sync_translation_status(&ctx, &ingress, status)
+            .instrument(tracing::info_span!("Updating IngressTranslation"))
+            .await?;
+    }
 
-        let route_info = RouteInputInfo {
-            ingress_name: ingress.name_any(),
-            header_matchers: header_matchers.clone(),
-            query_matchers: query_matchers.clone(),
-            gw_name: gw_name.to_string(),
-            gw_namespace: gw_namespace.to_string(),
-            ingress_meta: ingress.meta(),
-            hostname: host.to_string(),
-            ingress_namespace: ingress_namespace.clone(),
-            section_name: desired_section_name.clone(),
-        };
+    for warning in warnings {
+        ctx.publish_event(&ingress, EventType::Warning, "RuleSkipped", warning)
+            .await;
+    }
+    for refusal in refused {
+        ctx.publish_event(&ingress, EventType::Warning, "ApplyRefused", refusal)
+            .await;
+    }
+    if !untranslated.is_empty() {
+        ctx.publish_event(
+            &ingress,
+            EventType::Warning,
+            "UntranslatedAnnotations",
+            format!(
+                "Annotations can't be translated: {}",
+                untranslated.join(", ")
+            ),
+        )
+        .await;
+    }
+    ctx.publish_event(
+        &ingress,
+        EventType::Normal,
+        "Translated",
+        format!("Generated {route_count} routes"),
+    )
+    .await;
 
-        if let Some(http) = &rule.http {
-            let Ok(routes) = create_http_routes(ctx.clone(), route_info, &http).await else {
-                tracing::warn!("Failed to create HTTPRoute for host {}", host);
-                continue;
-            };
-            for mut route in routes {
-                if ctx.args.link_to_ingress {
-                    route.meta_mut().add_owner(ingress.as_ref());
-                }
-                Api::<HTTPRoute>::namespaced(ctx.client.clone(), &ingress_namespace)
-                    .patch(
-                        &route.name_any(),
-                        &PatchParams {
-                            field_manager: Some("ingress-to-gateway-controller".to_string()),
-                            ..PatchParams::default()
-                        },
-                        &kube::api::Patch::Apply(route),
-                    )
-                    .instrument(tracing::info_span!("Applying generated HTTPRoute"))
-                    .await?;
-            }
-        } else {
-            if !ctx.args.experimental {
-                tracing::warn!(
-                    "Skipping rule non-http rule. In order to migrate it to TCPRoute, please add --experimental flag to i2g-operator."
-                );
-                continue;
-            }
-            // In case if rule.http is None
-            let Some(backend) = default_backend else {
-                tracing::warn!("Skipping non-HTTP Ingress rule without default backend");
-                continue;
-            };
-            let Some(backend_svc) = &backend.service else {
-                tracing::warn!("defaultBackend doesn't have a service, skipping.");
-                continue;
-            };
+    ctx.reset_failures(&ObjectRef::from_obj(ingress.as_ref()));
+    Ok(Action::requeue(ctx.args.resync()))
+}
 
-            let Ok(mut route) = create_tcp_routes(ctx.clone(), route_info, backend_svc).await
-            else {
-                tracing::warn!("Failed to create TCPRoute for host {}", host);
-                continue;
-            };
+#[tracing::instrument(skip(obj, err, ctx), fields(ingress = obj.name_any(), namespace = obj.namespace()))]
+fn on_error(obj: Arc<Ingress>, err: &I2GError, ctx: Arc<ctx::Context>) -> Action {
+    let failures = ctx.record_failure(&ObjectRef::from_obj(obj.as_ref()));
+    ctx.metrics
+        .reconcile_errors
+        .with_label_values(&[err.kind()])
+        .inc();
+    let note = err.to_string();
+    let event_ctx = ctx.clone();
+    tokio::spawn(async move {
+        event_ctx
+            .publish_event(&obj, EventType::Warning, "ReconcileFailed", note)
+            .await;
+    });
+    let policy = err.retry_policy();
+    if policy == RetryPolicy::Terminal {
+        // Retrying won't help, the Ingress is reconciled again once it changes.
+        tracing::warn!("Reconcile failed with a terminal error, not retrying: {err}");
+        return Action::await_change();
+    }
+    let delay = error_backoff(&ctx.args, policy, failures);
+    let threshold = ctx.args.dead_letter_threshold;
+    if threshold == 0 || failures < threshold {
+        return Action::requeue(delay);
+    }
+    if failures == threshold {
+        tracing::error!(
+            failures,
+            "Dead-letter: Ingress failed to reconcile {failures} times in a row and needs attention: {err}"
+        );
+    } else {
+        tracing::warn!(
+            failures,
+            "Dead-letter Ingress still fails to reconcile: {err}"
+        );
+    }
+    Action::requeue(delay)
+}
 
-            if ctx.args.link_to_ingress {
-                route.meta_mut().add_owner(ingress.as_ref());
-            }
+/// Delay before retrying a failed reconcile. It grows exponentially from
+/// `--error-backoff-base` to `--error-backoff-max` with consecutive failures,
+/// throttled requests wait the maximum right away.
+/// Up to 10% of jitter is added, so Ingresses failing at once don't retry at once.
+fn error_backoff(args: &args::I2GArgs, policy: RetryPolicy, failures: u32) -> Duration {
+    let base = args.error_backoff_base.as_millis() as u64;
+    let max = args.error_backoff_max.as_millis() as u64;
+    let delay = if policy == RetryPolicy::RateLimited {
+        max
+    } else {
+        let exponent = failures.saturating_sub(1).min(32);
+        base.saturating_mul(2u64.saturating_pow(exponent)).min(max)
+    };
+    let jitter = rand::rng().random_range(0..=delay / 10);
+    Duration::from_millis(delay + jitter)
+}
 
-            Api::<TCPRoute>::namespaced(ctx.client.clone(), &ingress_namespace)
-                .patch(
-                    &route.name_any(),
-                    &PatchParams {
-                        field_manager: Some("ingress-to-gateway-controller".to_string()),
-                        ..PatchParams::default()
-                    },
-                    &kube::api::Patch::Apply(route),
-                )
-                .instrument(tracing::info_span!("Applying generated TCPRoute"))
-                .await?;
-        }
+/// Find Ingresses referencing the Secret in their TLS section.
+fn ingresses_for_secret(ingresses: &Store<Ingress>, secret: &Secret) -> Vec<ObjectRef<Ingress>> {
+    let secret_name = secret.name_any();
+    let secret_namespace = secret.namespace();
+    ingresses
+        .state()
+        .into_iter()
+        .filter(|ingress| ingress.namespace() == secret_namespace)
+        .filter(|ingress| {
+            ingress
+                .spec
+                .as_ref()
+                .and_then(|spec| spec.tls.as_ref())
+                .is_some_and(|tls| {
+                    tls.iter()
+                        .any(|entry| entry.secret_name.as_ref() == Some(&secret_name))
+                })
+        })
+        .map(|ingress| ObjectRef::from_obj(ingress.as_ref()))
+        .collect()
+}
+
+/// Find Ingresses attached to gateways by label selectors.
+/// Changes of gateways may change the selected gateways of such Ingresses.
+fn ingresses_for_gateway(
+    ingresses: &Store<Ingress>,
+    default_selector: bool,
+) -> Vec<ObjectRef<Ingress>> {
+    ingresses
+        .state()
+        .into_iter()
+        .filter(|ingress| {
+            let annotations = ingress.annotations();
+            !annotations.contains_key(consts::GATEWAY_NAME)
+                && (default_selector || annotations.contains_key(consts::GATEWAY_SELECTOR))
+        })
+        .map(|ingress| ObjectRef::from_obj(ingress.as_ref()))
+        .collect()
+}
+
+/// Find Ingresses of classes whose parameters reference the I2GClassConfig.
+fn ingresses_for_class_config(
+    ingresses: &Store<Ingress>,
+    classes: &Store<IngressClass>,
+    config: &I2GClassConfig,
+) -> Vec<ObjectRef<Ingress>> {
+    let config_name = config.name_any();
+    let references_config = |class: &IngressClass| {
+        class
+            .spec
+            .as_ref()
+            .and_then(|spec| spec.parameters.as_ref())
+            .is_some_and(|parameters| {
+                parameters.kind == I2GClassConfig::kind(&()) && parameters.name == config_name
+            })
+    };
+    let class_names = classes
+        .state()
+        .into_iter()
+        .filter(|class| references_config(class))
+        .map(|class| class.name_any())
+        .collect::<HashSet<_>>();
+    ingresses
+        .state()
+        .into_iter()
+        .filter(|ingress| {
+            utils::ingress_class(ingress).is_some_and(|class| class_names.contains(class))
+        })
+        .map(|ingress| ObjectRef::from_obj(ingress.as_ref()))
+        .collect()
+}
+
+/// Find Ingresses of the class.
+fn ingresses_for_class(
+    ingresses: &Store<Ingress>,
+    class: &IngressClass,
+) -> Vec<ObjectRef<Ingress>> {
+    let class_name = class.name_any();
+    ingresses
+        .state()
+        .into_iter()
+        .filter(|ingress| utils::ingress_class(ingress) == Some(&class_name))
+        .map(|ingress| ObjectRef::from_obj(ingress.as_ref()))
+        .collect()
+}
+
+/// Api for resources in watched namespaces.
+/// A single namespace is watched directly. Otherwise all namespaces are watched
+/// and Ingresses from other namespaces are skipped during reconciliation.
+fn watched_api<K>(ctx: &ctx::Context) -> Api<K>
+where
+    K: kube::Resource<Scope = kube::core::NamespaceResourceScope, DynamicType = ()>,
+{
+    match ctx.args.watch_namespaces.as_slice() {
+        [namespace] => Api::namespaced(ctx.client.clone(), namespace),
+        _ => Api::all(ctx.client.clone()),
     }
+}
 
-    Ok(Action::requeue(Duration::from_secs(10)))
+/// Find the Ingress a route was generated from.
+fn ingress_for_route<K: kube::Resource>(route: K) -> Option<ObjectRef<Ingress>> {
+    let ingress = route.annotations().get(consts::OWNER_INGRESS)?;
+    Some(ObjectRef::new(ingress).within(&route.namespace()?))
 }
 
-#[tracing::instrument(skip(obj, _ctx), fields(ingress = obj.name_any()))]
-fn on_error(obj: Arc<Ingress>, _err: &I2GError, _ctx: Arc<ctx::Context>) -> Action {
-    Action::requeue(Duration::from_secs(30))
+/// Find Ingresses with backends referencing the Service.
+fn ingresses_for_service(ingresses: &Store<Ingress>, service: &Service) -> Vec<ObjectRef<Ingress>> {
+    let service_name = service.name_any();
+    let service_namespace = service.namespace();
+    let references_service = |backend: &IngressBackend| {
+        backend
+            .service
+            .as_ref()
+            .is_some_and(|svc| svc.name == service_name)
+    };
+    ingresses
+        .state()
+        .into_iter()
+        .filter(|ingress| {
+            let backend_namespace = ingress
+                .annotations()
+                .get(consts::BACKEND_NAMESPACE)
+                .map(|namespace| namespace.trim().to_string())
+                .filter(|namespace| !namespace.is_empty())
+                .or_else(|| ingress.namespace());
+            backend_namespace == service_namespace
+        })
+        .filter(|ingress| {
+            let Some(spec) = &ingress.spec else {
+                return false;
+            };
+            spec.default_backend.iter().any(references_service)
+                || spec
+                    .rules
+                    .iter()
+                    .flatten()
+                    .filter_map(|rule| rule.http.as_ref())
+                    .flat_map(|http| &http.paths)
+                    .any(|path| references_service(&path.backend))
+        })
+        .map(|ingress| ObjectRef::from_obj(ingress.as_ref()))
+        .collect()
 }
 
-async fn lease_renew(ctx: Arc<ctx::Context>) {
-    let leadership = kube_leader_election::LeaseLock::new(
+fn lease_lock(ctx: &ctx::Context) -> kube_leader_election::LeaseLock {
+    kube_leader_election::LeaseLock::new(
         ctx.client.clone(),
-        ctx.client.default_namespace(),
+        ctx.args
+            .lease_namespace
+            .as_deref()
+            .unwrap_or(ctx.client.default_namespace()),
         kube_leader_election::LeaseLockParams {
             holder_id: ctx.hostname.clone(),
-            lease_name: "i2g-operator-lock".into(),
-            lease_ttl: Duration::from_secs(15),
+            // Every shard elects its own leader.
+            lease_name: match ctx.shard_index {
+                Some(index) => format!("{}-shard-{index}", ctx.args.lease_name),
+                None => ctx.args.lease_name.clone(),
+            },
+            lease_ttl: Duration::from_millis(ctx.args.lease_ttl.as_millis() as u64),
         },
-    );
+    )
+}
+
+async fn lease_renew(ctx: Arc<ctx::Context>, leadership: &kube_leader_election::LeaseLock) {
     loop {
         match leadership.try_acquire_or_renew().await {
             Ok(lease) => {
-                if lease.acquired_lease {
+                if ctx.set_leader(lease.acquired_lease) {
                     tracing::info!("Acquired leadership lease");
                 }
-                ctx.is_leader
-                    .store(lease.acquired_lease, std::sync::atomic::Ordering::Relaxed)
             }
             Err(err) => {
                 tracing::warn!("Failed to acquire or renew lease: {}", err);
             }
         }
-        tokio::time::sleep(Duration::from_secs(5)).await;
+        tokio::time::sleep(Duration::from_millis(
+            ctx.args.lease_renew_interval.as_millis() as u64,
+        ))
+        .await;
     }
 }
 
+/// Re-read the `--config` file every resync interval. When settings change,
+/// all Ingresses are reconciled with the new ones through `changes`.
+/// Invalid files are reported and the previous settings are kept.
+async fn reload_settings(ctx: Arc<ctx::Context>, changes: UnboundedSender<()>) {
+    let Some(path) = ctx.args.config.clone() else {
+        return std::future::pending().await;
+    };
+    loop {
+        tokio::time::sleep(ctx.args.resync()).await;
+        let settings = match Settings::load(&ctx.args) {
+            Ok(settings) => settings,
+            Err(err) => {
+                tracing::warn!("Keeping previous settings: {err}");
+                continue;
+            }
+        };
+        {
+            let mut current = ctx.settings.write().unwrap();
+            if *current == settings {
+                continue;
+            }
+            tracing::info!("Reloaded settings from {}: {settings:?}", path.display());
+            *current = settings;
+        }
+        let _ = changes.unbounded_send(());
+    }
+}
+
+/// Resolves when the process receives SIGTERM or SIGINT.
+async fn shutdown_signal() {
+    let mut sigterm = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+    {
+        Ok(sigterm) => sigterm,
+        Err(err) => {
+            tracing::error!("Failed to listen for SIGTERM: {err}");
+            let _ = tokio::signal::ctrl_c().await;
+            return;
+        }
+    };
+    tokio::select! {
+        _ = sigterm.recv() => {},
+        _ = tokio::signal::ctrl_c() => {},
+    }
+    tracing::info!("Received shutdown signal, waiting for running reconciles");
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     dotenvy::dotenv().ok();
 
     let ctx = Arc::new(ctx::Context::new().await?);
-    tracing_subscriber::fmt()
-        .with_max_level(ctx.args.log_level)
-        .init();
+    let tracer_provider = telemetry::init(&ctx.args)?;
+
+    match &ctx.args.command {
+        Some(args::Command::Audit { output }) => return audit::run(ctx.clone(), *output).await,
+        Some(args::Command::Report { output }) => return report::run(ctx.clone(), *output).await,
+        Some(args::Command::Snapshot) => return snapshot::run(ctx.clone()).await,
+        Some(args::Command::Crd) => {
+            let documents = [
+                serde_yaml::to_string(&IngressTranslation::crd())?,
+                serde_yaml::to_string(&I2GClassConfig::crd())?,
+            ];
+            print!("{}", documents.join("---\n"));
+            return Ok(());
+        }
+        Some(args::Command::Convert { file, .. }) => {
+            return convert::run(ctx.clone(), file).await;
+        }
+        None => {}
+    }
+
     tracing::info!("Staring operator");
     tracing::info!("CLI argument: {:?}", ctx.args);
 
-    let lease_renewer = lease_renew(ctx.clone());
+    let leadership = lease_lock(&ctx);
+    let lease_renewer = lease_renew(ctx.clone(), &leadership);
+    let (settings_changes, settings_changed) = futures::channel::mpsc::unbounded();
+    let settings_reloader = reload_settings(ctx.clone(), settings_changes);
+    let metrics_server = http_server::serve(ctx.clone());
 
-    let ingress_controller = kube::runtime::Controller::new(
-        Api::<Ingress>::all(ctx.client.clone()),
-        kube::runtime::watcher::Config::default(),
+    let excluded_namespaces = ctx.args.excluded_namespaces_selector();
+    // Status updates, including our own, don't change the translation,
+    // so only changes of the spec, annotations and finalizers trigger reconciles.
+    // Deletion bumps the generation of Ingresses with finalizers.
+    let (reader, writer) = reflector::store();
+    let ingress_changes = watcher(
+        watched_api::<Ingress>(&ctx),
+        watcher::Config::default().fields(&excluded_namespaces),
     )
-    .run(reconcile, on_error, ctx.clone())
-    .for_each(|_| futures::future::ready(()));
+    .default_backoff()
+    .reflect(writer)
+    .applied_objects()
+    .predicate_filter(
+        predicates::generation::<Ingress>
+            .combine(predicates::annotations)
+            .combine(predicates::finalizers),
+    );
+    let mut controller = kube::runtime::Controller::for_stream(ingress_changes, reader);
+    let _ = ctx.ingresses.set(controller.store());
+    // Primary Ingresses include backends of their canaries.
+    let ingresses = controller.store();
+    controller = controller.watches(
+        watched_api::<Ingress>(&ctx),
+        kube::runtime::watcher::Config::default().fields(&excluded_namespaces),
+        move |ingress| canary::primaries_for_canary(&ingresses, &ingress),
+    );
+    // Backends with named ports are skipped until the Service exists,
+    // so Service changes trigger reconciles of referencing Ingresses.
+    let ingresses = controller.store();
+    controller = controller.watches(
+        watched_api::<Service>(&ctx),
+        kube::runtime::watcher::Config::default().fields(&excluded_namespaces),
+        move |service| ingresses_for_service(&ingresses, &service),
+    );
+    if ctx.args.watch_tls_secrets {
+        let ingresses = controller.store();
+        let secret_fields = std::iter::once("type=kubernetes.io/tls".to_string())
+            .chain(Some(excluded_namespaces.clone()).filter(|fields| !fields.is_empty()))
+            .collect::<Vec<_>>();
+        controller = controller.watches(
+            watched_api::<Secret>(&ctx),
+            kube::runtime::watcher::Config::default().fields(&secret_fields.join(",")),
+            move |secret| ingresses_for_secret(&ingresses, &secret),
+        );
+    }
+    let ingresses = controller.store();
+    let default_selector = ctx.args.gateway_selector.is_some();
+    controller = controller.watches(
+        Api::<gateways::Gateway>::all(ctx.client.clone()),
+        kube::runtime::watcher::Config::default(),
+        move |_| ingresses_for_gateway(&ingresses, default_selector),
+    );
+    // Class parameters change gateways, sections and providers of Ingresses.
+    if let Some(classes) = ctx.ingress_classes.clone() {
+        let ingresses = controller.store();
+        controller = controller.watches(
+            Api::<I2GClassConfig>::all(ctx.client.clone()),
+            kube::runtime::watcher::Config::default(),
+            move |config| ingresses_for_class_config(&ingresses, &classes, &config),
+        );
+        let ingresses = controller.store();
+        controller = controller.watches(
+            Api::<IngressClass>::all(ctx.client.clone()),
+            kube::runtime::watcher::Config::default(),
+            move |class| ingresses_for_class(&ingresses, &class),
+        );
+    }
+    // Manual changes of generated routes are reverted right away.
+    let managed_routes = kube::runtime::watcher::Config::default()
+        .labels(&format!(
+            "{}={}",
+            consts::MANAGED_BY_LABEL,
+            consts::MANAGED_BY_VALUE
+        ))
+        .fields(&excluded_namespaces);
+    controller = controller.watches(
+        watched_api::<HTTPRoute>(&ctx),
+        managed_routes.clone(),
+        ingress_for_route,
+    );
+    if ctx.args.experimental {
+        controller = controller
+            .watches(
+                watched_api::<TCPRoute>(&ctx),
+                managed_routes.clone(),
+                ingress_for_route,
+            )
+            .watches(
+                watched_api::<UDPRoute>(&ctx),
+                managed_routes.clone(),
+                ingress_for_route,
+            )
+            .watches(
+                watched_api::<TLSRoute>(&ctx),
+                managed_routes,
+                ingress_for_route,
+            );
+    }
+    // Zero means unbounded concurrency. The fair queue limits running reconciles itself,
+    // so all reconciles can start and wait for their turn in it.
+    let concurrency = match &ctx.fair_queue {
+        Some(_) => 0,
+        None => ctx.args.max_concurrent_reconciles.unwrap_or(0),
+    };
+    let config = kube::runtime::controller::Config::default().concurrency(concurrency);
+    let ingress_controller = controller
+        .with_config(config)
+        .reconcile_all_on(settings_changed)
+        // New reconciles aren't started after the signal, running ones are finished.
+        .graceful_shutdown_on(shutdown_signal())
+        .run(reconcile, on_error, ctx.clone())
+        .for_each(|_| futures::future::ready(()));
 
     tokio::select! {
         _ = lease_renewer => {
             tracing::error!("Lease renewer task exited unexpectedly");
         },
+        _ = settings_reloader => {
+            tracing::error!("Settings reloader exited unexpectedly");
+        },
         _ = ingress_controller => {
-            tracing::error!("Ingress controller task exited unexpectedly");
+            tracing::info!("Ingress controller stopped");
+        },
+        res = metrics_server => {
+            tracing::error!("Metrics server exited unexpectedly: {res:?}");
         },
     }
 
+    // Release the lease, so a standby replica doesn't wait for it to expire.
+    if ctx.is_leader() {
+        ctx.set_leader(false);
+        match leadership.step_down().await {
+            Ok(()) => tracing::info!("Released leadership lease"),
+            Err(err) => tracing::warn!("Failed to release leadership lease: {err}"),
+        }
+    }
+    if let Some(provider) = tracer_provider
+        && let Err(err) = provider.shutdown()
+    {
+        tracing::warn!("Failed to flush traces: {err}");
+    }
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use k8s_openapi::apimachinery::pkg::apis::meta::v1::{ManagedFieldsEntry, OwnerReference};
+    use rstest::rstest;
+
+    use super::*;
+
+    fn offline_ctx(args: &[&str]) -> ctx::Context {
+        let args =
+            args::I2GArgs::parse_from(std::iter::once("i2g-operator").chain(args.iter().copied()));
+        ctx::Context::offline(args, vec![]).unwrap()
+    }
+
+    fn ingress() -> Arc<Ingress> {
+        Arc::new(Ingress {
+            metadata: ObjectMeta {
+                name: Some("web".to_string()),
+                namespace: Some("ns".to_string()),
+                ..ObjectMeta::default()
+            },
+            ..Ingress::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn reconcile_requires_leadership() {
+        // The offline client can't reach the API, so any request would fail the reconcile.
+        let ctx = Arc::new(offline_ctx(&["--resync-interval", "30s"]));
+        let action = reconcile(ingress(), ctx.clone()).await.unwrap();
+        assert_eq!(action, Action::requeue(Duration::from_secs(30)));
+        assert_eq!(
+            ctx.metrics
+                .skipped
+                .with_label_values(&[SkipReason::NotLeader.as_str()])
+                .get(),
+            1
+        );
+    }
+
+    /// API server answering reads with 404 and applies with the applied object.
+    /// Leadership is lost once the first HTTPRoute is applied.
+    #[derive(Clone)]
+    struct MockApi {
+        is_leader: Arc<std::sync::atomic::AtomicBool>,
+        requests: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl tower::Service<http::Request<kube::client::Body>> for MockApi {
+        type Response = http::Response<kube::client::Body>;
+        type Error = kube::Error;
+        type Future = futures::future::BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+        fn poll_ready(
+            &mut self,
+            _: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Result<(), Self::Error>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: http::Request<kube::client::Body>) -> Self::Future {
+            let api = self.clone();
+            Box::pin(async move {
+                let (parts, body) = request.into_parts();
+                let request = format!("{} {}", parts.method, parts.uri.path());
+                let (status, body) = if parts.method == http::Method::PATCH {
+                    if request.contains("/httproutes/") {
+                        api.is_leader
+                            .store(false, std::sync::atomic::Ordering::SeqCst);
+                    }
+                    (http::StatusCode::OK, body.collect_bytes().await?.to_vec())
+                } else {
+                    let status = serde_json::json!({
+                        "kind": "Status",
+                        "apiVersion": "v1",
+                        "status": "Failure",
+                        "reason": "NotFound",
+                        "code": 404,
+                    });
+                    (http::StatusCode::NOT_FOUND, status.to_string().into_bytes())
+                };
+                api.requests.lock().unwrap().push(request);
+                Ok(http::Response::builder()
+                    .status(status)
+                    .body(body.into())
+                    .unwrap())
+            })
+        }
+    }
+
+    /// Offline context whose client sends requests to a [`MockApi`].
+    fn mock_ctx(args: &[&str]) -> (ctx::Context, MockApi) {
+        let mut ctx = offline_ctx(args);
+        let api = MockApi {
+            is_leader: ctx.is_leader.clone(),
+            requests: Arc::default(),
+        };
+        ctx.client = kube::Client::new(api.clone(), "default");
+        (ctx, api)
+    }
+
+    #[tokio::test]
+    async fn reconcile_stops_applying_after_losing_leadership() {
+        let (ctx, api) = mock_ctx(&[
+            "--link-to-ingress",
+            "--resync-interval",
+            "30s",
+            "--default-gateway-name",
+            "gw",
+        ]);
+        ctx.set_leader(true);
+        let ingress = serde_yaml::from_str::<Ingress>(
+            r#"
+metadata: {name: web, namespace: ns, uid: "1"}
+spec:
+  rules:
+  - host: a.example.com
+    http: {paths: [{path: /, pathType: Prefix, backend: {service: {name: web, port: {number: 80}}}}]}
+  - host: b.example.com
+    http: {paths: [{path: /, pathType: Prefix, backend: {service: {name: web, port: {number: 80}}}}]}
+"#,
+        )
+        .unwrap();
+        let action = reconcile(Arc::new(ingress), Arc::new(ctx)).await.unwrap();
+        assert_eq!(action, Action::requeue(Duration::from_secs(30)));
+        // The second route and the Ingress annotations aren't applied by the lost leader.
+        let requests = api.requests.lock().unwrap();
+        let patches = requests
+            .iter()
+            .filter(|request| request.starts_with("PATCH"))
+            .collect::<Vec<_>>();
+        assert_eq!(patches.len(), 1, "{requests:?}");
+        assert!(patches[0].contains("/httproutes/"), "{requests:?}");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn reconcile_waits_for_leader_warmup() {
+        let (ctx, api) = mock_ctx(&["--leader-warmup-secs", "60", "--link-to-ingress"]);
+        let ctx = Arc::new(ctx);
+        assert!(ctx.set_leader(true));
+        tokio::time::advance(Duration::from_secs(20)).await;
+        let remaining = ctx.leader_warmup_remaining();
+        assert_eq!(remaining, Some(Duration::from_secs(40)));
+        let action = reconcile(ingress(), ctx.clone()).await.unwrap();
+        assert_eq!(action, Action::requeue(remaining.unwrap()));
+        assert_eq!(*api.requests.lock().unwrap(), Vec::<String>::new());
+
+        // Renewing the lease doesn't start another warm-up.
+        tokio::time::advance(Duration::from_secs(40)).await;
+        assert!(!ctx.set_leader(true));
+        assert_eq!(ctx.leader_warmup_remaining(), None);
+        let mut disabled = ingress().as_ref().clone();
+        disabled
+            .annotations_mut()
+            .insert(consts::TRANSLATE_INGRESS.to_string(), "false".to_string());
+        let action = reconcile(Arc::new(disabled), ctx.clone()).await.unwrap();
+        assert_eq!(action, Action::requeue(ctx.args.resync()));
+    }
+
+    #[tokio::test]
+    async fn dead_letter_keeps_retrying() {
+        let ctx = Arc::new(offline_ctx(&[
+            "--dead-letter-threshold",
+            "2",
+            "--link-to-ingress",
+        ]));
+        let dead_letters = || ctx.metrics.dead_letter_ingresses.get();
+        let err = I2GError::General("boom".to_string());
+        for expected in [0, 1, 1] {
+            let action = on_error(ingress(), &err, ctx.clone());
+            assert_ne!(action, Action::await_change());
+            assert_eq!(dead_letters(), expected);
+        }
+        let key = ObjectRef::from_obj(ingress().as_ref());
+        assert_eq!(ctx.failures.lock().unwrap().get(&key), Some(&3));
+        let terminal = I2GError::ParseError("invalid".to_string());
+        assert_eq!(
+            on_error(ingress(), &terminal, ctx.clone()),
+            Action::await_change()
+        );
+        assert_eq!(dead_letters(), 1);
+
+        // A successful reconcile, skipping the disabled Ingress without requests.
+        ctx.set_leader(true);
+        let mut disabled = ingress().as_ref().clone();
+        disabled
+            .annotations_mut()
+            .insert(consts::TRANSLATE_INGRESS.to_string(), "false".to_string());
+        reconcile(Arc::new(disabled), ctx.clone()).await.unwrap();
+        assert_eq!(ctx.failures.lock().unwrap().get(&key), None);
+        assert_eq!(dead_letters(), 0);
+    }
+
+    #[rstest]
+    #[case::nothing_recorded(None, &[], true)]
+    #[case::still_desired(Some("ReferenceGrant:infra/web"), &["ReferenceGrant:infra/web"], true)]
+    #[case::unused_grant(Some("ReferenceGrant:infra/web"), &[], false)]
+    #[case::used_policy(
+        Some("BackendTLSPolicy:ns/web-backend-tls"),
+        &["BackendTLSPolicy:ns/web-backend-tls"],
+        true
+    )]
+    #[case::unused_policy(Some("BackendTLSPolicy:ns/web-backend-tls"), &[], false)]
+    #[case::unused_backend(Some("Backend:ns/web-80-external"), &[], false)]
+    #[tokio::test]
+    async fn shared_objects_released_by_record(
+        #[case] recorded: Option<&str>,
+        #[case] desired: &[&str],
+        #[case] no_requests: bool,
+    ) {
+        let ctx = offline_ctx(&[]);
+        let mut ingress = ingress().as_ref().clone();
+        if let Some(recorded) = recorded {
+            ingress
+                .annotations_mut()
+                .insert(consts::SHARED_OBJECTS.to_string(), recorded.to_string());
+        }
+        let desired = desired.iter().map(|object| object.to_string()).collect();
+        // The offline client can't reach the API, so only releases without requests succeed.
+        let released = release_shared_objects(&ctx, &ingress, &desired).await;
+        assert_eq!(released.is_ok(), no_requests, "{released:?}");
+    }
+
+    fn route_meta(label: Option<&str>, manager: Option<&str>, controller: bool) -> ObjectMeta {
+        ObjectMeta {
+            labels: label
+                .map(|value| [(consts::MANAGED_BY_LABEL.to_string(), value.to_string())].into()),
+            managed_fields: manager.map(|manager| {
+                vec![ManagedFieldsEntry {
+                    manager: Some(manager.to_string()),
+                    ..ManagedFieldsEntry::default()
+                }]
+            }),
+            owner_references: controller.then(|| {
+                vec![OwnerReference {
+                    controller: Some(true),
+                    ..OwnerReference::default()
+                }]
+            }),
+            ..ObjectMeta::default()
+        }
+    }
+
+    #[rstest]
+    #[case(route_meta(None, None, false), false, false)]
+    #[case(route_meta(Some(consts::MANAGED_BY_VALUE), None, false), true, false)]
+    #[case(route_meta(None, Some(consts::FIELD_MANAGER), false), true, false)]
+    #[case(route_meta(None, Some("kubectl"), false), false, false)]
+    #[case(route_meta(Some("helm"), None, false), false, true)]
+    #[case(route_meta(None, None, true), false, true)]
+    fn route_ownership(
+        #[case] meta: ObjectMeta,
+        #[case] managed: bool,
+        #[case] owned_by_others: bool,
+    ) {
+        assert_eq!(is_managed(&meta), managed);
+        assert_eq!(is_owned_by_others(&meta), owned_by_others);
+    }
+
+    fn ingress_store(ingresses: &[&str]) -> Store<Ingress> {
+        let (reader, mut writer) = kube::runtime::reflector::store::<Ingress>();
+        writer.apply_watcher_event(&kube::runtime::watcher::Event::Init);
+        for ingress in ingresses {
+            let ingress = serde_yaml::from_str::<Ingress>(ingress).unwrap();
+            writer.apply_watcher_event(&kube::runtime::watcher::Event::InitApply(ingress));
+        }
+        writer.apply_watcher_event(&kube::runtime::watcher::Event::InitDone);
+        reader
+    }
+
+    #[test]
+    fn secret_references() {
+        let store = ingress_store(&[
+            "{metadata: {name: a, namespace: ns}, spec: {tls: [{secretName: cert}]}}",
+            "{metadata: {name: b, namespace: ns}, spec: {tls: [{secretName: other}, {secretName: cert}]}}",
+            "{metadata: {name: c, namespace: other}, spec: {tls: [{secretName: cert}]}}",
+            "{metadata: {name: d, namespace: ns}, spec: {}}",
+        ]);
+        let secret = Secret {
+            metadata: ObjectMeta {
+                name: Some("cert".to_string()),
+                namespace: Some("ns".to_string()),
+                ..ObjectMeta::default()
+            },
+            ..Secret::default()
+        };
+        let mut names = ingresses_for_secret(&store, &secret)
+            .into_iter()
+            .map(|ingress| ingress.name)
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, vec!["a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn reconcile_errors_by_kind() {
+        let ctx = Arc::new(offline_ctx(&[]));
+        on_error(
+            ingress(),
+            &I2GError::General("boom".to_string()),
+            ctx.clone(),
+        );
+        on_error(
+            ingress(),
+            &I2GError::ParseError("bad".to_string()),
+            ctx.clone(),
+        );
+        on_error(
+            ingress(),
+            &I2GError::ParseError("bad".to_string()),
+            ctx.clone(),
+        );
+        let errors = |kind: &str| {
+            ctx.metrics
+                .reconcile_errors
+                .with_label_values(&[kind])
+                .get()
+        };
+        assert_eq!(errors("general"), 1);
+        assert_eq!(errors("parse"), 2);
+        assert_eq!(errors("kube"), 0);
+    }
+}
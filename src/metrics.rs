@@ -0,0 +1,199 @@
+use prometheus::{Histogram, HistogramOpts, IntCounter, IntCounterVec, IntGauge, Opts, Registry};
+
+/// Reason why an Ingress or a part of it was not translated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    NotLeader,
+    SkipAnnotation,
+    NamespaceDisabled,
+    IngressClass,
+    NoHost,
+    NoService,
+    NoServicePort,
+    UnresolvablePort,
+    ServiceWithoutPorts,
+    UnknownPathType,
+    ExperimentalDisabled,
+    InvalidBackends,
+    NoDefaultBackend,
+    ConflictingParent,
+    UnsupportedRewrite,
+    Canary,
+    OtherShard,
+}
+
+impl SkipReason {
+    pub const ALL: [SkipReason; 17] = [
+        SkipReason::NotLeader,
+        SkipReason::SkipAnnotation,
+        SkipReason::NamespaceDisabled,
+        SkipReason::IngressClass,
+        SkipReason::NoHost,
+        SkipReason::NoService,
+        SkipReason::NoServicePort,
+        SkipReason::UnresolvablePort,
+        SkipReason::ServiceWithoutPorts,
+        SkipReason::UnknownPathType,
+        SkipReason::ExperimentalDisabled,
+        SkipReason::InvalidBackends,
+        SkipReason::NoDefaultBackend,
+        SkipReason::ConflictingParent,
+        SkipReason::UnsupportedRewrite,
+        SkipReason::Canary,
+        SkipReason::OtherShard,
+    ];
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            SkipReason::NotLeader => "not_leader",
+            SkipReason::SkipAnnotation => "skip_annotation",
+            SkipReason::NamespaceDisabled => "namespace_disabled",
+            SkipReason::IngressClass => "ingress_class",
+            SkipReason::NoHost => "no_host",
+            SkipReason::NoService => "no_service",
+            SkipReason::NoServicePort => "no_service_port",
+            SkipReason::UnresolvablePort => "unresolvable_port",
+            SkipReason::ServiceWithoutPorts => "service_without_ports",
+            SkipReason::UnknownPathType => "unknown_path_type",
+            SkipReason::ExperimentalDisabled => "experimental_disabled",
+            SkipReason::InvalidBackends => "invalid_backends",
+            SkipReason::NoDefaultBackend => "no_default_backend",
+            SkipReason::ConflictingParent => "conflicting_parent",
+            SkipReason::UnsupportedRewrite => "unsupported_rewrite",
+            SkipReason::Canary => "canary",
+            SkipReason::OtherShard => "other_shard",
+        }
+    }
+}
+
+/// Metrics collected by the operator.
+#[derive(Clone)]
+pub struct Metrics {
+    pub registry: Registry,
+    pub skipped: IntCounterVec,
+    pub reconciles: IntCounter,
+    pub reconcile_errors: IntCounterVec,
+    pub generated_routes: IntCounterVec,
+    pub match_ruleset_size: Histogram,
+    pub is_leader: IntGauge,
+    pub unattachable_routes: IntCounterVec,
+    pub unchanged_patches: IntCounter,
+    pub dead_letter_ingresses: IntGauge,
+}
+
+impl Metrics {
+    pub fn new() -> anyhow::Result<Self> {
+        let registry = Registry::new();
+        let skipped = IntCounterVec::new(
+            Opts::new(
+                "i2g_skipped_total",
+                "Number of skipped Ingresses and Ingress rules by reason",
+            ),
+            &["reason"],
+        )?;
+        // Initialize all reasons, so they are exported even before the first skip.
+        for reason in SkipReason::ALL {
+            skipped.with_label_values(&[reason.as_str()]);
+        }
+        let reconciles = IntCounter::new("i2g_reconciles_total", "Number of reconciles")?;
+        let reconcile_errors = IntCounterVec::new(
+            Opts::new(
+                "i2g_reconcile_errors_total",
+                "Number of failed reconciles by error kind",
+            ),
+            &["reason"],
+        )?;
+        let generated_routes = IntCounterVec::new(
+            Opts::new(
+                "i2g_generated_routes_total",
+                "Number of generated and applied routes by kind",
+            ),
+            &["kind"],
+        )?;
+        let match_ruleset_size = Histogram::with_opts(
+            HistogramOpts::new(
+                "i2g_match_ruleset_size",
+                "Number of header, query and method matcher combinations per Ingress rule",
+            )
+            .buckets(vec![1.0, 2.0, 4.0, 8.0, 16.0, 32.0, 64.0]),
+        )?;
+        let is_leader = IntGauge::new(
+            "i2g_is_leader",
+            "Whether this instance holds the leadership lease",
+        )?;
+        let unattachable_routes = IntCounterVec::new(
+            Opts::new(
+                "i2g_unattachable_routes_total",
+                "Number of generated routes which can't be attached to their gateway by reason",
+            ),
+            &["reason"],
+        )?;
+        let unchanged_patches = IntCounter::new(
+            "i2g_unchanged_patches_skipped_total",
+            "Number of patches skipped because the live object is up to date",
+        )?;
+        let dead_letter_ingresses = IntGauge::new(
+            "i2g_dead_letter_ingresses",
+            "Number of Ingresses which failed to reconcile at least --dead-letter-threshold times in a row",
+        )?;
+        registry.register(Box::new(skipped.clone()))?;
+        registry.register(Box::new(reconciles.clone()))?;
+        registry.register(Box::new(reconcile_errors.clone()))?;
+        registry.register(Box::new(generated_routes.clone()))?;
+        registry.register(Box::new(match_ruleset_size.clone()))?;
+        registry.register(Box::new(is_leader.clone()))?;
+        registry.register(Box::new(unattachable_routes.clone()))?;
+        registry.register(Box::new(unchanged_patches.clone()))?;
+        registry.register(Box::new(dead_letter_ingresses.clone()))?;
+        Ok(Metrics {
+            registry,
+            skipped,
+            reconciles,
+            reconcile_errors,
+            generated_routes,
+            match_ruleset_size,
+            is_leader,
+            unattachable_routes,
+            unchanged_patches,
+            dead_letter_ingresses,
+        })
+    }
+
+    pub fn record_skip(&self, reason: SkipReason) {
+        self.skipped.with_label_values(&[reason.as_str()]).inc();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn skip_reasons_are_exported() {
+        let metrics = Metrics::new().unwrap();
+        metrics.record_skip(SkipReason::NoHost);
+        metrics.record_skip(SkipReason::NoHost);
+        let family = metrics
+            .registry
+            .gather()
+            .into_iter()
+            .find(|family| family.name() == "i2g_skipped_total")
+            .unwrap();
+        // Every reason is exported, even before the first skip.
+        assert_eq!(family.get_metric().len(), SkipReason::ALL.len());
+        assert_eq!(
+            metrics
+                .skipped
+                .with_label_values(&[SkipReason::NoHost.as_str()])
+                .get(),
+            2
+        );
+        let labels = SkipReason::ALL
+            .iter()
+            .map(SkipReason::as_str)
+            .collect::<HashSet<_>>();
+        assert_eq!(labels.len(), SkipReason::ALL.len());
+    }
+}
@@ -0,0 +1,112 @@
+//! Names of generated routes and their rules.
+//!
+//! Names are built from the Ingress name, the sanitized host and path.
+//! Names which are too long, or whose host loses characters during
+//! sanitization (e.g. wildcard hosts), get a stable hash of the original
+//! values appended, so different inputs don't end up with the same name.
+
+use crate::{err::I2GError, templates, utils::sanitize_hostname};
+
+/// Maximum length of Kubernetes object names and HTTPRoute rule names.
+pub const MAX_NAME_LENGTH: usize = 253;
+/// Number of hex digits of the hash appended to names.
+const HASH_LENGTH: usize = 10;
+
+/// FNV-1a hash of the parts. Unlike `DefaultHasher`, it doesn't change
+/// between Rust versions, so names stay the same after upgrades.
+pub fn stable_hash(parts: &[&str]) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for part in parts {
+        // Separator, so ("ab", "c") and ("a", "bc") have different hashes.
+        for byte in part.bytes().chain([0]) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    format!("{hash:016x}")[..HASH_LENGTH].to_string()
+}
+
+/// Sanitize the value to be used as a part of a name.
+fn sanitize(value: &str) -> String {
+    sanitize_hostname(value).to_lowercase()
+}
+
+/// Whether sanitization loses more than dots of the host,
+/// e.g. `*.example.com` and `example.com` have the same sanitized form.
+fn is_lossy_host(host: &str) -> bool {
+    !host.is_empty() && sanitize(host) != host.to_lowercase().replace('.', "-")
+}
+
+/// Append the hash of the parts to the name,
+/// truncating the name to fit into [`MAX_NAME_LENGTH`].
+fn with_hash(name: &str, parts: &[&str]) -> String {
+    let mut prefix = name
+        .chars()
+        .take(MAX_NAME_LENGTH - HASH_LENGTH - 1)
+        .collect::<String>();
+    prefix.truncate(prefix.trim_end_matches('-').len());
+    format!("{prefix}-{}", stable_hash(parts))
+}
+
+/// Make sure the name fits into [`MAX_NAME_LENGTH`].
+/// Longer names are truncated and the hash of the full name is appended.
+pub fn fit(name: &str) -> String {
+    if name.len() <= MAX_NAME_LENGTH {
+        return name.to_string();
+    }
+    with_hash(name, &[name])
+}
+
+/// Alternative name for a generated route whose name is taken
+/// by a route which doesn't belong to the operator.
+pub fn alternative_name(name: &str) -> String {
+    with_hash(name, &[name, "alternative"])
+}
+
+/// Name of a route generated for the host of the Ingress:
+/// `{ingress}-{host}-{suffix}`.
+pub fn route_name(ingress: &str, host: &str, suffix: &str) -> String {
+    let name = format!("{ingress}-{}-{suffix}", sanitize(host));
+    if name.len() > MAX_NAME_LENGTH || is_lossy_host(host) {
+        return with_hash(&name, &[ingress, host, suffix]);
+    }
+    name
+}
+
+/// Name of a route rendered from the template.
+/// The host is sanitized before rendering, the result is sanitized
+/// and truncated with [`fit`].
+pub fn render_route_name(
+    template: &str,
+    ingress: &str,
+    namespace: &str,
+    host: &str,
+    kind: &str,
+    index: usize,
+) -> Result<String, I2GError> {
+    let host = sanitize(host);
+    let index = index.to_string();
+    let name = templates::render(
+        template,
+        &[
+            ("ingress", ingress),
+            ("namespace", namespace),
+            ("host", &host),
+            ("kind", kind),
+            ("index", &index),
+        ],
+    )?;
+    Ok(fit(&sanitize(&name)))
+}
+
+/// Name of an HTTPRoute rule generated for the path: `{path}-{suffix}`.
+pub fn rule_name(path: Option<&str>, suffix: &str) -> String {
+    let Some(path) = path else {
+        return suffix.to_string();
+    };
+    let name = format!("{}-{suffix}", sanitize(path));
+    if name.len() > MAX_NAME_LENGTH {
+        return with_hash(&name, &[path, suffix]);
+    }
+    name
+}
@@ -0,0 +1,310 @@
+//! Translation of annotations of other ingress controllers.
+//!
+//! Every provider describes annotations of a single controller: which of them
+//! are translated, which ones mean the same as native `i2g-operator/` annotations,
+//! and how the rest is applied to generated HTTPRoute rules.
+//! The provider is selected with `--compat-provider`.
+//!
+//! Canary Ingresses are merged by the [`crate::canary`] module regardless of the provider.
+
+use std::collections::BTreeMap;
+
+use gateway_api::apis::experimental::httproutes::{
+    HTTPRouteRules, HTTPRouteRulesSessionPersistence, HTTPRouteRulesSessionPersistenceCookieConfig,
+    HTTPRouteRulesSessionPersistenceCookieConfigLifetimeType, HTTPRouteRulesSessionPersistenceType,
+};
+use k8s_openapi::api::networking::v1::Ingress;
+use kube::ResourceExt;
+
+use crate::{args::CompatProvider, consts, err::I2GError};
+
+type Annotations = BTreeMap<String, String>;
+
+pub trait AnnotationProvider: Send + Sync {
+    /// Controller annotations which are translated.
+    /// Other annotations of known controllers are reported as unsupported.
+    fn translated_annotations(&self) -> &'static [&'static str];
+
+    /// Controller annotation with the same meaning as the native one.
+    fn alias(&self, _native: &str) -> Option<&'static str> {
+        None
+    }
+
+    /// Timeout of requests to backends.
+    fn backend_request_timeout(
+        &self,
+        _annotations: &Annotations,
+    ) -> Result<Option<gateway_api::Duration>, I2GError> {
+        Ok(None)
+    }
+
+    /// Sticky sessions of backends.
+    fn session_persistence(
+        &self,
+        _annotations: &Annotations,
+    ) -> Result<Option<HTTPRouteRulesSessionPersistence>, I2GError> {
+        Ok(None)
+    }
+
+    /// Value of the controller annotation, if it's translated by the provider.
+    fn get<'a>(&self, annotations: &'a Annotations, key: &str) -> Option<&'a String> {
+        self.translated_annotations()
+            .contains(&key)
+            .then(|| annotations.get(key))
+            .flatten()
+    }
+
+    /// Value of the native annotation, or of its controller alias if the native one isn't set.
+    fn annotation<'a>(&self, annotations: &'a Annotations, native: &str) -> Option<&'a String> {
+        annotations
+            .get(native)
+            .or_else(|| self.alias(native).and_then(|alias| annotations.get(alias)))
+    }
+
+    /// Apply controller annotations of the Ingress to generated HTTPRoute rules.
+    /// Annotations which can't be applied are reported in `warnings`.
+    fn apply(
+        &self,
+        ingress: &Ingress,
+        rules: Vec<&mut HTTPRouteRules>,
+        experimental: bool,
+        warnings: &mut Vec<String>,
+    ) -> Result<(), I2GError> {
+        let Some(session_persistence) = self.session_persistence(ingress.annotations())? else {
+            return Ok(());
+        };
+        if !experimental {
+            warnings.push(
+                "Ignoring session affinity annotations. HTTPRoute session persistence requires --experimental flag."
+                    .to_string(),
+            );
+            return Ok(());
+        }
+        for rule in rules {
+            rule.session_persistence = Some(session_persistence.clone());
+        }
+        Ok(())
+    }
+}
+
+/// Annotations of ingress controllers which can't be translated with the provider.
+pub fn unsupported_annotations(
+    provider: &dyn AnnotationProvider,
+    ingress: &Ingress,
+) -> Vec<String> {
+    ingress
+        .annotations()
+        .keys()
+        .filter(|key| {
+            consts::VENDOR_ANNOTATION_PREFIXES
+                .iter()
+                .any(|prefix| key.starts_with(prefix))
+                && !provider.translated_annotations().contains(&key.as_str())
+                && !consts::CANARY_ANNOTATIONS.contains(&key.as_str())
+        })
+        .cloned()
+        .collect()
+}
+
+impl CompatProvider {
+    pub fn provider(self) -> &'static dyn AnnotationProvider {
+        match self {
+            CompatProvider::Nginx => &Nginx,
+            CompatProvider::Traefik => &Traefik,
+            CompatProvider::Haproxy => &Haproxy,
+        }
+    }
+}
+
+/// Cookie session persistence. A cookie with `max_age` (in seconds)
+/// is permanent and the session expires after that time,
+/// otherwise a session cookie is used.
+fn cookie_persistence(
+    name: Option<&String>,
+    max_age: Option<u64>,
+) -> HTTPRouteRulesSessionPersistence {
+    let lifetime_type = match max_age {
+        Some(_) => HTTPRouteRulesSessionPersistenceCookieConfigLifetimeType::Permanent,
+        None => HTTPRouteRulesSessionPersistenceCookieConfigLifetimeType::Session,
+    };
+    HTTPRouteRulesSessionPersistence {
+        absolute_timeout: max_age.map(|max_age| format!("{max_age}s")),
+        cookie_config: Some(HTTPRouteRulesSessionPersistenceCookieConfig {
+            lifetime_type: Some(lifetime_type),
+        }),
+        idle_timeout: None,
+        session_name: name.cloned(),
+        r#type: Some(HTTPRouteRulesSessionPersistenceType::Cookie),
+    }
+}
+
+/// Positive number of seconds from the annotation.
+fn parse_seconds(annotations: &Annotations, key: &str) -> Result<Option<u64>, I2GError> {
+    annotations
+        .get(key)
+        .map(|raw| {
+            raw.trim()
+                .parse::<u64>()
+                .ok()
+                .filter(|seconds| *seconds > 0)
+                .ok_or_else(|| {
+                    I2GError::ParseError(format!(
+                        "Invalid {key} '{raw}': not a positive number of seconds"
+                    ))
+                })
+        })
+        .transpose()
+}
+
+/// ingress-nginx.
+pub struct Nginx;
+
+impl AnnotationProvider for Nginx {
+    fn translated_annotations(&self) -> &'static [&'static str] {
+        &[
+            consts::NGINX_SSL_PASSTHROUGH,
+            consts::NGINX_SSL_REDIRECT,
+            consts::NGINX_REWRITE_TARGET,
+            consts::NGINX_PROXY_READ_TIMEOUT,
+            consts::NGINX_PROXY_SEND_TIMEOUT,
+            consts::NGINX_BACKEND_PROTOCOL,
+            consts::NGINX_AFFINITY,
+            consts::NGINX_SESSION_COOKIE_NAME,
+            consts::NGINX_SESSION_COOKIE_MAX_AGE,
+        ]
+    }
+
+    fn alias(&self, native: &str) -> Option<&'static str> {
+        match native {
+            consts::SSL_PASSTHROUGH => Some(consts::NGINX_SSL_PASSTHROUGH),
+            consts::SSL_REDIRECT => Some(consts::NGINX_SSL_REDIRECT),
+            consts::BACKEND_PROTOCOL => Some(consts::NGINX_BACKEND_PROTOCOL),
+            _ => None,
+        }
+    }
+
+    /// The greater of upstream read and send timeouts.
+    fn backend_request_timeout(
+        &self,
+        annotations: &Annotations,
+    ) -> Result<Option<gateway_api::Duration>, I2GError> {
+        let mut timeouts = vec![];
+        for key in [
+            consts::NGINX_PROXY_READ_TIMEOUT,
+            consts::NGINX_PROXY_SEND_TIMEOUT,
+        ] {
+            let Some(raw) = annotations.get(key) else {
+                continue;
+            };
+            let timeout = raw
+                .trim()
+                .parse::<u64>()
+                .map_err(|err| err.to_string())
+                .and_then(gateway_api::Duration::from_secs)
+                .map_err(|err| I2GError::ParseError(format!("Invalid {key} '{raw}': {err}")))?;
+            timeouts.push(timeout);
+        }
+        Ok(timeouts
+            .into_iter()
+            .max_by_key(|timeout| timeout.as_millis()))
+    }
+
+    /// Only `cookie` affinity is supported.
+    fn session_persistence(
+        &self,
+        annotations: &Annotations,
+    ) -> Result<Option<HTTPRouteRulesSessionPersistence>, I2GError> {
+        let Some(affinity) = annotations.get(consts::NGINX_AFFINITY) else {
+            return Ok(None);
+        };
+        if affinity.to_lowercase() != "cookie" {
+            return Err(I2GError::ParseError(format!(
+                "Affinity '{affinity}' is not supported, only 'cookie' can be translated"
+            )));
+        }
+        let max_age = parse_seconds(annotations, consts::NGINX_SESSION_COOKIE_MAX_AGE)?;
+        Ok(Some(cookie_persistence(
+            annotations.get(consts::NGINX_SESSION_COOKIE_NAME),
+            max_age,
+        )))
+    }
+}
+
+/// Traefik Kubernetes Ingress provider.
+pub struct Traefik;
+
+impl AnnotationProvider for Traefik {
+    fn translated_annotations(&self) -> &'static [&'static str] {
+        &[
+            consts::TRAEFIK_STICKY_COOKIE,
+            consts::TRAEFIK_STICKY_COOKIE_NAME,
+            consts::TRAEFIK_STICKY_COOKIE_MAX_AGE,
+        ]
+    }
+
+    fn session_persistence(
+        &self,
+        annotations: &Annotations,
+    ) -> Result<Option<HTTPRouteRulesSessionPersistence>, I2GError> {
+        let sticky = annotations
+            .get(consts::TRAEFIK_STICKY_COOKIE)
+            .is_some_and(|v| v.to_lowercase() == "true");
+        if !sticky {
+            return Ok(None);
+        }
+        let max_age = parse_seconds(annotations, consts::TRAEFIK_STICKY_COOKIE_MAX_AGE)?;
+        Ok(Some(cookie_persistence(
+            annotations.get(consts::TRAEFIK_STICKY_COOKIE_NAME),
+            max_age,
+        )))
+    }
+}
+
+/// HAProxy Kubernetes Ingress Controller.
+pub struct Haproxy;
+
+impl AnnotationProvider for Haproxy {
+    fn translated_annotations(&self) -> &'static [&'static str] {
+        &[
+            consts::HAPROXY_SSL_PASSTHROUGH,
+            consts::HAPROXY_SSL_REDIRECT,
+            consts::HAPROXY_TIMEOUT_SERVER,
+            consts::HAPROXY_COOKIE_PERSISTENCE,
+        ]
+    }
+
+    fn alias(&self, native: &str) -> Option<&'static str> {
+        match native {
+            consts::SSL_PASSTHROUGH => Some(consts::HAPROXY_SSL_PASSTHROUGH),
+            consts::SSL_REDIRECT => Some(consts::HAPROXY_SSL_REDIRECT),
+            _ => None,
+        }
+    }
+
+    fn backend_request_timeout(
+        &self,
+        annotations: &Annotations,
+    ) -> Result<Option<gateway_api::Duration>, I2GError> {
+        annotations
+            .get(consts::HAPROXY_TIMEOUT_SERVER)
+            .map(|raw| {
+                raw.parse::<gateway_api::Duration>().map_err(|err| {
+                    I2GError::ParseError(format!(
+                        "Invalid {} '{raw}': {err}",
+                        consts::HAPROXY_TIMEOUT_SERVER
+                    ))
+                })
+            })
+            .transpose()
+    }
+
+    /// The annotation value is the name of the cookie.
+    fn session_persistence(
+        &self,
+        annotations: &Annotations,
+    ) -> Result<Option<HTTPRouteRulesSessionPersistence>, I2GError> {
+        Ok(annotations
+            .get(consts::HAPROXY_COOKIE_PERSISTENCE)
+            .map(|name| cookie_persistence(Some(name), None)))
+    }
+}
@@ -0,0 +1,288 @@
+use gateway_api::{
+    gateways::{
+        Gateway, GatewayListeners, GatewayListenersAllowedRoutes,
+        GatewayListenersAllowedRoutesNamespaces, GatewayListenersAllowedRoutesNamespacesFrom,
+        GatewayListenersTls, GatewayListenersTlsCertificateRefs, GatewayListenersTlsMode,
+        GatewaySpec,
+    },
+    referencegrants::{ReferenceGrant, ReferenceGrantFrom, ReferenceGrantSpec, ReferenceGrantTo},
+};
+use k8s_openapi::api::{core::v1::Secret, networking::v1::Ingress};
+use kube::{Resource, ResourceExt};
+
+use i2g_operator::{
+    consts,
+    ctx::Context,
+    err::I2GResult,
+    naming, skip_reason,
+    utils::{ObjectMetaI2GExt, sanitize_hostname, tls_host_secrets},
+};
+
+/// Maximum number of listeners in a single Gateway allowed by gateway-api.
+const MAX_LISTENERS: usize = 64;
+
+/// Gateway provisioned for Ingresses of a namespace, with the grant
+/// allowing it to reference TLS secrets of the namespace.
+pub struct Provisioned {
+    pub gateway: Gateway,
+    pub secret_grant: Option<ReferenceGrant>,
+}
+
+/// Whether routes of the Ingress are attached to the provisioned gateway.
+pub fn uses_provisioned_gateway(ingress: &Ingress) -> bool {
+    let annotations = ingress.annotations();
+    !annotations.contains_key(consts::GATEWAY_NAME)
+        && !annotations.contains_key(consts::GATEWAY_SELECTOR)
+}
+
+fn listener_name(prefix: &str, host: &str) -> String {
+    let host = sanitize_hostname(&host.replace('*', "wildcard")).to_lowercase();
+    naming::fit(&format!("{prefix}-{host}"))
+}
+
+fn tls_listener(
+    name: String,
+    host: &str,
+    mode: GatewayListenersTlsMode,
+    secret: Option<(&str, Option<&str>)>,
+    allowed_routes: Option<GatewayListenersAllowedRoutes>,
+) -> GatewayListeners {
+    GatewayListeners {
+        name,
+        hostname: Some(host.to_string()),
+        port: 443,
+        protocol: match mode {
+            GatewayListenersTlsMode::Terminate => "HTTPS".to_string(),
+            GatewayListenersTlsMode::Passthrough => "TLS".to_string(),
+        },
+        tls: Some(GatewayListenersTls {
+            certificate_refs: secret.map(|(secret, namespace)| {
+                vec![GatewayListenersTlsCertificateRefs {
+                    name: secret.to_string(),
+                    namespace: namespace.map(str::to_string),
+                    ..Default::default()
+                }]
+            }),
+            mode: Some(mode),
+            ..Default::default()
+        }),
+        allowed_routes,
+    }
+}
+
+/// Gateway with listeners for hosts of the Ingresses. Plain HTTP hosts share
+/// a single listener, TLS hosts get HTTPS listeners terminating TLS with
+/// Secrets from the Ingresses, ssl-passthrough hosts get TLS listeners.
+/// Hosts which can't be served are skipped, the reason is added to `warnings`.
+/// A Gateway shared by namespaces accepts routes from all of them.
+fn build_gateway(
+    ctx: &Context,
+    namespace: &str,
+    class: &str,
+    ingresses: &[&Ingress],
+    warnings: &mut Vec<String>,
+) -> Gateway {
+    let allowed_routes =
+        ctx.args
+            .provisioned_gateway_namespace
+            .as_ref()
+            .map(|_| GatewayListenersAllowedRoutes {
+                namespaces: Some(GatewayListenersAllowedRoutesNamespaces {
+                    from: Some(GatewayListenersAllowedRoutesNamespacesFrom::All),
+                    selector: None,
+                }),
+                kinds: None,
+            });
+    let mut listeners = vec![GatewayListeners {
+        name: "http".to_string(),
+        port: 80,
+        protocol: "HTTP".to_string(),
+        allowed_routes: allowed_routes.clone(),
+        ..Default::default()
+    }];
+    for ingress in ingresses {
+        let ingress_namespace = ingress.namespace();
+        // Secrets in the Gateway namespace are referenced without a namespace.
+        let secret_namespace = ingress_namespace
+            .as_deref()
+            .filter(|ingress_namespace| *ingress_namespace != namespace);
+        let passthrough = ctx
+            .provider(ingress)
+            .annotation(ingress.annotations(), consts::SSL_PASSTHROUGH)
+            .is_some_and(|v| v.to_lowercase() == "true");
+        let Some(spec) = &ingress.spec else {
+            continue;
+        };
+        if passthrough {
+            if !ctx.args.experimental {
+                continue;
+            }
+            let hosts = spec
+                .rules
+                .iter()
+                .flatten()
+                .filter_map(|rule| rule.host.as_deref());
+            for host in hosts {
+                let listener = tls_listener(
+                    listener_name("tls", host),
+                    host,
+                    GatewayListenersTlsMode::Passthrough,
+                    None,
+                    allowed_routes.clone(),
+                );
+                if !listeners
+                    .iter()
+                    .any(|other| other.hostname == listener.hostname)
+                {
+                    listeners.push(listener);
+                }
+            }
+            continue;
+        }
+        let mut conflicts = vec![];
+        let secrets = tls_host_secrets(
+            spec.tls.as_deref().unwrap_or_default(),
+            ctx.args.tls_secret_conflicts,
+            &mut conflicts,
+        );
+        warnings.extend(
+            conflicts
+                .into_iter()
+                .map(|conflict| format!("Ingress {}: {conflict}", ingress.name_any())),
+        );
+        for (host, secret) in secrets {
+            let Some(secret) = secret else {
+                warnings.push(format!(
+                    "Gateway listener for host {host} of Ingress {} is not provisioned: TLS section doesn't have a secret",
+                    ingress.name_any()
+                ));
+                continue;
+            };
+            let listener = tls_listener(
+                listener_name("https", host),
+                host,
+                GatewayListenersTlsMode::Terminate,
+                Some((secret, secret_namespace)),
+                allowed_routes.clone(),
+            );
+            match listeners
+                .iter()
+                .find(|other| other.hostname == listener.hostname)
+            {
+                Some(other) if other.tls != listener.tls => warnings.push(format!(
+                    "Gateway listener for host {host} of Ingress {} uses another TLS configuration, secret {secret} is ignored",
+                    ingress.name_any()
+                )),
+                Some(_) => {}
+                None => listeners.push(listener),
+            }
+        }
+    }
+    if listeners.len() > MAX_LISTENERS {
+        warnings.push(format!(
+            "Gateway {namespace}/{} needs {} listeners, only the first {MAX_LISTENERS} are provisioned",
+            ctx.args.provisioned_gateway_name,
+            listeners.len()
+        ));
+        listeners.truncate(MAX_LISTENERS);
+    }
+    let mut gateway = Gateway::new(
+        &ctx.args.provisioned_gateway_name,
+        GatewaySpec {
+            gateway_class_name: class.to_string(),
+            listeners,
+            ..Default::default()
+        },
+    );
+    gateway.metadata.namespace = Some(namespace.to_string());
+    gateway.meta_mut().add_managed_by_label();
+    gateway
+}
+
+/// ReferenceGrant in the Ingress namespace allowing the Gateway
+/// from another namespace to reference TLS secrets of the Ingresses.
+fn secret_reference_grant(
+    ingress_namespace: &str,
+    gateway: &Gateway,
+    ingresses: &[&Ingress],
+) -> Option<ReferenceGrant> {
+    let mut secrets = ingresses
+        .iter()
+        .filter(|ingress| ingress.namespace().as_deref() == Some(ingress_namespace))
+        .filter_map(|ingress| ingress.spec.as_ref())
+        .flat_map(|spec| spec.tls.iter().flatten())
+        .filter_map(|tls| tls.secret_name.clone())
+        .collect::<Vec<_>>();
+    secrets.sort();
+    secrets.dedup();
+    if secrets.is_empty() {
+        return None;
+    }
+    let gw_namespace = gateway.namespace().unwrap_or_default();
+    let mut grant = ReferenceGrant::new(
+        &naming::fit(&format!(
+            "i2g-{}-{}-secrets",
+            sanitize_hostname(&gw_namespace),
+            sanitize_hostname(&gateway.name_any())
+        )),
+        ReferenceGrantSpec {
+            from: vec![ReferenceGrantFrom {
+                group: Gateway::group(&()).to_string(),
+                kind: Gateway::kind(&()).to_string(),
+                namespace: gw_namespace,
+            }],
+            to: secrets
+                .into_iter()
+                .map(|secret| ReferenceGrantTo {
+                    group: Secret::group(&()).to_string(),
+                    kind: Secret::kind(&()).to_string(),
+                    name: Some(secret),
+                })
+                .collect(),
+        },
+    );
+    grant.metadata.namespace = Some(ingress_namespace.to_string());
+    grant.meta_mut().add_managed_by_label();
+    Some(grant)
+}
+
+/// Gateway provisioned for translated Ingresses of the namespace.
+/// With `--provisioned-gateway-namespace` all namespaces share a single Gateway,
+/// which gets a grant to reference TLS secrets of `ingress_namespace`.
+/// Returns None if provisioning is disabled.
+pub async fn desired_gateway(
+    ctx: &Context,
+    ingress_namespace: &str,
+    warnings: &mut Vec<String>,
+) -> I2GResult<Option<Provisioned>> {
+    let (true, Some(class), Some(store)) = (
+        ctx.args.provision_gateways,
+        &ctx.args.gateway_class,
+        ctx.ingresses.get(),
+    ) else {
+        return Ok(None);
+    };
+    let shared = ctx.args.provisioned_gateway_namespace.as_deref();
+    let namespace = shared.unwrap_or(ingress_namespace);
+    let mut candidates = store
+        .state()
+        .into_iter()
+        .filter(|ingress| shared.is_some() || ingress.namespace().as_deref() == Some(namespace))
+        .filter(|ingress| uses_provisioned_gateway(ingress))
+        .collect::<Vec<_>>();
+    candidates.sort_by_key(|ingress| (ingress.namespace(), ingress.name_any()));
+    let mut ingresses = vec![];
+    for ingress in &candidates {
+        if skip_reason(ingress, ctx).await?.is_none() {
+            ingresses.push(ingress.as_ref());
+        }
+    }
+    let gateway = build_gateway(ctx, namespace, class, &ingresses, warnings);
+    let secret_grant = shared
+        .filter(|namespace| *namespace != ingress_namespace)
+        .and_then(|_| secret_reference_grant(ingress_namespace, &gateway, &ingresses));
+    Ok(Some(Provisioned {
+        gateway,
+        secret_grant,
+    }))
+}
@@ -0,0 +1,163 @@
+use std::sync::Arc;
+
+use k8s_openapi::api::networking::v1::Ingress;
+use kube::{Api, ResourceExt, api::ListParams};
+
+use i2g_operator::{metrics::SkipReason, providers::unsupported_annotations};
+
+use crate::{args::OutputFormat, ctx::Context};
+
+/// Migration readiness of an Ingress.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Readiness {
+    /// All rules and annotations are translated.
+    Ready,
+    /// Routes are generated, but some rules or annotations are not translated.
+    Partial,
+    /// No routes can be generated.
+    Blocked,
+    /// The Ingress is not handled by the operator, e.g. because of its class.
+    Skipped,
+}
+
+impl Readiness {
+    fn as_str(self) -> &'static str {
+        match self {
+            Readiness::Ready => "ready",
+            Readiness::Partial => "partial",
+            Readiness::Blocked => "blocked",
+            Readiness::Skipped => "skipped",
+        }
+    }
+}
+
+/// Readiness of a single Ingress with reasons why it can't be fully migrated.
+#[derive(Debug, serde::Serialize)]
+pub struct ReportEntry {
+    pub namespace: String,
+    pub name: String,
+    pub readiness: Readiness,
+    pub route_count: usize,
+    pub reasons: Vec<String>,
+}
+
+#[derive(Debug, Default, serde::Serialize)]
+pub struct Summary {
+    pub ready: usize,
+    pub partial: usize,
+    pub blocked: usize,
+    pub skipped: usize,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct Report {
+    pub summary: Summary,
+    pub ingresses: Vec<ReportEntry>,
+}
+
+async fn report_ingress(ingress: &Ingress, ctx: Arc<Context>) -> ReportEntry {
+    let mut entry = ReportEntry {
+        namespace: ingress.namespace().unwrap_or_default(),
+        name: ingress.name_any(),
+        readiness: Readiness::Blocked,
+        route_count: 0,
+        reasons: vec![],
+    };
+    match crate::skip_reason(ingress, &ctx).await {
+        // Canaries are merged into routes of their primary Ingresses.
+        Ok(Some((SkipReason::Canary, _))) => {
+            entry.readiness = Readiness::Ready;
+            return entry;
+        }
+        Ok(Some((_, reason))) => {
+            entry.readiness = Readiness::Skipped;
+            entry.reasons.push(reason);
+            return entry;
+        }
+        Ok(None) => {}
+        Err(err) => {
+            entry.reasons.push(err.to_string());
+            return entry;
+        }
+    }
+    let translation = match crate::translate(ingress, ctx.clone()).await {
+        Ok(translation) => translation,
+        Err(err) => {
+            entry.reasons.push(err.to_string());
+            return entry;
+        }
+    };
+    entry.route_count = translation.route_count();
+    entry.reasons = translation.warnings;
+    entry.reasons.extend(
+        unsupported_annotations(ctx.provider(ingress), ingress)
+            .into_iter()
+            .map(|key| format!("Annotation {key} can't be translated")),
+    );
+    entry.readiness = if entry.route_count == 0 {
+        if entry.reasons.is_empty() {
+            entry.reasons.push("No routes are generated".to_string());
+        }
+        Readiness::Blocked
+    } else if translation.failed || !entry.reasons.is_empty() {
+        Readiness::Partial
+    } else {
+        Readiness::Ready
+    };
+    entry
+}
+
+fn print_table(report: &Report) {
+    println!(
+        "{:<20} {:<40} {:<10} {:<6} REASONS",
+        "NAMESPACE", "NAME", "READINESS", "ROUTES"
+    );
+    for entry in &report.ingresses {
+        println!(
+            "{:<20} {:<40} {:<10} {:<6} {}",
+            entry.namespace,
+            entry.name,
+            entry.readiness.as_str(),
+            entry.route_count,
+            entry.reasons.join("; ")
+        );
+    }
+    let summary = &report.summary;
+    println!(
+        "\nReady: {}, partial: {}, blocked: {}, skipped: {}",
+        summary.ready, summary.partial, summary.blocked, summary.skipped
+    );
+}
+
+/// Print migration readiness of all Ingresses in the cluster.
+///
+/// This is read-only: generated routes are never applied.
+pub async fn run(ctx: Arc<Context>, output: OutputFormat) -> anyhow::Result<()> {
+    let mut ingresses = Api::<Ingress>::all(ctx.client.clone())
+        .list(&ListParams::default())
+        .await?
+        .items;
+    ingresses.sort_by_key(|ingress| (ingress.namespace(), ingress.name_any()));
+
+    let mut report = Report {
+        summary: Summary::default(),
+        ingresses: vec![],
+    };
+    for ingress in &ingresses {
+        let entry = report_ingress(ingress, ctx.clone()).await;
+        match entry.readiness {
+            Readiness::Ready => report.summary.ready += 1,
+            Readiness::Partial => report.summary.partial += 1,
+            Readiness::Blocked => report.summary.blocked += 1,
+            Readiness::Skipped => report.summary.skipped += 1,
+        }
+        report.ingresses.push(entry);
+    }
+
+    match output {
+        OutputFormat::Table => print_table(&report),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+    }
+    Ok(())
+}
@@ -0,0 +1,87 @@
+//! Operator settings which can be changed without a restart.
+//!
+//! With `--config` the settings are read from a YAML file, e.g. a mounted ConfigMap.
+//! Values from the file supersede command line arguments, and removing a value
+//! from the file restores the argument. The file is re-read periodically.
+
+use std::{collections::BTreeMap, path::Path};
+
+use crate::args::{CompatProvider, I2GArgs};
+
+/// Contents of the `--config` file. All fields are optional.
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ConfigFile {
+    pub default_gateway_name: Option<String>,
+    pub default_gateway_namespace: Option<String>,
+    pub skip_by_default: Option<bool>,
+    pub compat_provider: Option<CompatProvider>,
+    /// Default gateways by namespace, in the format of the gateway name annotation.
+    #[serde(default)]
+    pub namespace_gateways: BTreeMap<String, String>,
+}
+
+impl ConfigFile {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let raw = std::fs::read_to_string(path)
+            .map_err(|err| anyhow::anyhow!("Failed to read {}: {err}", path.display()))?;
+        // An empty ConfigMap key is an empty file.
+        if raw.trim().is_empty() {
+            return Ok(ConfigFile::default());
+        }
+        serde_yaml::from_str(&raw)
+            .map_err(|err| anyhow::anyhow!("Invalid config {}: {err}", path.display()))
+    }
+}
+
+/// Effective values of reloadable settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub default_gateway_name: Option<String>,
+    pub default_gateway_namespace: String,
+    pub skip_by_default: bool,
+    pub compat_provider: CompatProvider,
+    /// Default gateways by namespace, only set in the config file.
+    pub namespace_gateways: BTreeMap<String, String>,
+}
+
+impl Settings {
+    /// Settings from arguments, superseded by values from the config file.
+    pub fn new(args: &I2GArgs, config: ConfigFile) -> Self {
+        Settings {
+            default_gateway_name: config
+                .default_gateway_name
+                .or_else(|| args.default_gateway_name.clone()),
+            default_gateway_namespace: config
+                .default_gateway_namespace
+                .unwrap_or_else(|| args.default_gateway_namespace.clone()),
+            skip_by_default: config.skip_by_default.unwrap_or(args.skip_by_default),
+            compat_provider: config.compat_provider.unwrap_or(args.compat_provider),
+            namespace_gateways: config.namespace_gateways,
+        }
+    }
+
+    /// Check that gateways of routes can be chosen.
+    pub fn validate(&self, args: &I2GArgs) -> anyhow::Result<()> {
+        if self.default_gateway_name.is_none()
+            && args.gateway_selector.is_none()
+            && !args.provision_gateways
+        {
+            anyhow::bail!(
+                "Either --default-gateway-name, --gateway-selector or --provision-gateways must be set"
+            );
+        }
+        Ok(())
+    }
+
+    /// Valid settings from arguments and the `--config` file, if it's set.
+    pub fn load(args: &I2GArgs) -> anyhow::Result<Self> {
+        let config = match &args.config {
+            Some(path) => ConfigFile::load(path)?,
+            None => ConfigFile::default(),
+        };
+        let settings = Self::new(args, config);
+        settings.validate(args)?;
+        Ok(settings)
+    }
+}
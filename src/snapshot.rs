@@ -0,0 +1,109 @@
+use std::sync::Arc;
+
+use gateway_api::{
+    apis::experimental::httproutes::HTTPRoute,
+    apis::experimental::{tcproutes::TCPRoute, tlsroutes::TLSRoute, udproutes::UDPRoute},
+};
+use kube::{Api, Resource, ResourceExt, api::ListParams};
+
+use crate::{consts, ctx::Context};
+
+/// Remove server-populated fields, so the object can be applied again.
+///
+/// Owner references are removed as well, because they point to UIDs
+/// of the current Ingresses. The operator adds them back on reconcile.
+pub fn clean<K: Resource>(obj: &mut K) {
+    let meta = obj.meta_mut();
+    meta.managed_fields = None;
+    meta.resource_version = None;
+    meta.uid = None;
+    meta.creation_timestamp = None;
+    meta.generation = None;
+    meta.owner_references = None;
+}
+
+async fn list_managed<K>(ctx: &Context) -> anyhow::Result<Vec<K>>
+where
+    K: Resource<DynamicType = ()> + Clone + std::fmt::Debug + serde::de::DeserializeOwned,
+{
+    let selector = format!("{}={}", consts::MANAGED_BY_LABEL, consts::MANAGED_BY_VALUE);
+    let mut items = Api::<K>::all(ctx.client.clone())
+        .list(&ListParams::default().labels(&selector))
+        .await?
+        .items;
+    items.iter_mut().for_each(clean);
+    items.sort_by_key(|item| (item.namespace(), item.name_any()));
+    Ok(items)
+}
+
+/// Print all routes managed by the operator as a multi-document YAML.
+pub async fn run(ctx: Arc<Context>) -> anyhow::Result<()> {
+    let mut documents = vec![];
+    for mut route in list_managed::<HTTPRoute>(&ctx).await? {
+        route.status = None;
+        documents.push(serde_yaml::to_string(&route)?);
+    }
+    if ctx.args.experimental {
+        for mut route in list_managed::<TCPRoute>(&ctx).await? {
+            route.status = None;
+            documents.push(serde_yaml::to_string(&route)?);
+        }
+        for mut route in list_managed::<UDPRoute>(&ctx).await? {
+            route.status = None;
+            documents.push(serde_yaml::to_string(&route)?);
+        }
+        for mut route in list_managed::<TLSRoute>(&ctx).await? {
+            route.status = None;
+            documents.push(serde_yaml::to_string(&route)?);
+        }
+    }
+    print!("{}", documents.join("---\n"));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use kube::api::ObjectMeta;
+
+    use super::*;
+
+    #[test]
+    fn cleaned_routes_can_be_applied() {
+        let mut route = serde_yaml::from_str::<HTTPRoute>(
+            r#"
+metadata:
+  name: web
+  namespace: ns
+  labels: {app.kubernetes.io/managed-by: i2g-operator}
+  annotations: {example.com/source: ns/web}
+  uid: 0b1c
+  resourceVersion: "42"
+  generation: 3
+  creationTimestamp: "2024-01-01T00:00:00Z"
+  managedFields: [{manager: ingress-to-gateway-controller}]
+  ownerReferences: [{apiVersion: networking.k8s.io/v1, kind: Ingress, name: web, uid: 0b1c}]
+spec: {}
+"#,
+        )
+        .unwrap();
+        clean(&mut route);
+        assert_eq!(
+            route.metadata,
+            ObjectMeta {
+                name: Some("web".to_string()),
+                namespace: Some("ns".to_string()),
+                labels: Some(
+                    [(
+                        consts::MANAGED_BY_LABEL.to_string(),
+                        consts::MANAGED_BY_VALUE.to_string()
+                    )]
+                    .into()
+                ),
+                annotations: Some(
+                    [("example.com/source".to_string(), "ns/web".to_string())].into()
+                ),
+                ..ObjectMeta::default()
+            }
+        );
+    }
+}
@@ -0,0 +1,46 @@
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{Resource, trace::SdkTracerProvider};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+use i2g_operator::{args::I2GArgs, consts};
+
+/// Provider exporting spans over OTLP/gRPC to the collector at `endpoint`.
+fn tracer_provider(endpoint: &str) -> anyhow::Result<SdkTracerProvider> {
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(endpoint)
+        .build()?;
+    Ok(SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(
+            Resource::builder()
+                .with_service_name(consts::MANAGED_BY_VALUE)
+                .with_attribute(opentelemetry::KeyValue::new(
+                    "service.version",
+                    consts::VERSION,
+                ))
+                .build(),
+        )
+        .build())
+}
+
+/// Log to stderr and, with `--otlp-endpoint`, export spans to an OpenTelemetry collector.
+/// Fields of spans, e.g. the name and namespace of the reconciled Ingress, become span attributes.
+/// The returned provider must be shut down before exit to flush pending spans.
+pub fn init(args: &I2GArgs) -> anyhow::Result<Option<SdkTracerProvider>> {
+    let provider = args
+        .otlp_endpoint
+        .as_deref()
+        .map(tracer_provider)
+        .transpose()?;
+    let otel = provider.as_ref().map(|provider| {
+        tracing_opentelemetry::layer().with_tracer(provider.tracer(consts::MANAGED_BY_VALUE))
+    });
+    tracing_subscriber::registry()
+        .with(args.log_level)
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+        .with(otel)
+        .init();
+    Ok(provider)
+}
@@ -0,0 +1,113 @@
+use std::str::FromStr;
+
+use crate::{err::I2GError, utils::is_qualified_name};
+
+/// Placeholders available in route metadata templates.
+pub const ROUTE_PLACEHOLDERS: &[&str] = &["ingress", "namespace", "host", "kind"];
+/// Placeholders available in route name templates.
+pub const ROUTE_NAME_PLACEHOLDERS: &[&str] = &["ingress", "namespace", "host", "kind", "index"];
+
+/// Render a template replacing `{placeholder}` with its value.
+///
+/// Unknown placeholders and unbalanced braces are errors.
+pub fn render(template: &str, values: &[(&str, &str)]) -> Result<String, I2GError> {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}') else {
+            return Err(I2GError::ParseError(format!(
+                "Unclosed placeholder in template '{template}'"
+            )));
+        };
+        let name = &rest[start + 1..start + end];
+        let Some((_, value)) = values.iter().find(|(key, _)| *key == name) else {
+            return Err(I2GError::ParseError(format!(
+                "Unknown placeholder '{{{name}}}' in template '{template}'"
+            )));
+        };
+        result.push_str(value);
+        rest = &rest[start + end + 1..];
+    }
+    if rest.contains('}') {
+        return Err(I2GError::ParseError(format!(
+            "Unexpected '}}' in template '{template}'"
+        )));
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// Check that the template only uses known placeholders.
+pub fn validate(template: &str, placeholders: &[&str]) -> Result<(), I2GError> {
+    let values = placeholders
+        .iter()
+        .map(|placeholder| (*placeholder, ""))
+        .collect::<Vec<_>>();
+    render(template, &values).map(|_| ())
+}
+
+/// Annotation added to generated routes, e.g. `team={namespace}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetadataTemplate {
+    pub key: String,
+    pub template: String,
+}
+
+impl FromStr for MetadataTemplate {
+    type Err = I2GError;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let Some((key, template)) = raw.split_once('=') else {
+            return Err(I2GError::ParseError(format!(
+                "Template '{raw}' should be in format key=template"
+            )));
+        };
+        if !is_qualified_name(key) {
+            return Err(I2GError::ParseError(format!(
+                "'{key}' is not a valid annotation key"
+            )));
+        }
+        validate(template, ROUTE_PLACEHOLDERS)?;
+        Ok(MetadataTemplate {
+            key: key.to_string(),
+            template: template.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    const VALUES: &[(&str, &str)] = &[("ingress", "web"), ("namespace", "ns")];
+
+    #[rstest]
+    #[case("{namespace}/{ingress}", "ns/web")]
+    #[case("static", "static")]
+    #[case("", "")]
+    #[case("{ingress}{ingress}", "webweb")]
+    fn rendered(#[case] template: &str, #[case] expected: &str) {
+        assert_eq!(render(template, VALUES).unwrap(), expected);
+    }
+
+    #[rstest]
+    #[case("{unknown}")]
+    #[case("{ingress")]
+    #[case("ingress}")]
+    #[case("{}")]
+    fn invalid_templates(#[case] template: &str) {
+        assert!(render(template, VALUES).is_err());
+    }
+
+    #[rstest]
+    #[case("team={namespace}", true)]
+    #[case("example.com/owner={ingress}-{host}", true)]
+    #[case("team", false)]
+    #[case("bad key={namespace}", false)]
+    #[case("team={index}", false)]
+    fn metadata_templates(#[case] raw: &str, #[case] valid: bool) {
+        assert_eq!(MetadataTemplate::from_str(raw).is_ok(), valid);
+    }
+}
@@ -0,0 +1,4076 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::Arc,
+};
+
+use gateway_api::{
+    apis::experimental::httproutes::{
+        HTTPRoute, HTTPRouteParentRefs, HTTPRouteRules, HTTPRouteRulesBackendRefs,
+        HTTPRouteRulesFilters, HTTPRouteRulesMatches, HTTPRouteRulesMatchesHeaders,
+        HTTPRouteRulesMatchesHeadersType, HTTPRouteRulesMatchesMethod, HTTPRouteRulesMatchesPath,
+        HTTPRouteRulesMatchesPathType, HTTPRouteRulesRetry, HTTPRouteRulesTimeouts, HTTPRouteSpec,
+    },
+    apis::experimental::tcproutes::{
+        TCPRoute, TCPRouteParentRefs, TCPRouteRules, TCPRouteRulesBackendRefs, TCPRouteSpec,
+        TCPRouteUseDefaultGateways,
+    },
+    apis::experimental::tlsroutes::{
+        TLSRoute, TLSRouteParentRefs, TLSRouteRules, TLSRouteRulesBackendRefs, TLSRouteSpec,
+    },
+    apis::experimental::udproutes::{
+        UDPRoute, UDPRouteParentRefs, UDPRouteRules, UDPRouteRulesBackendRefs, UDPRouteSpec,
+        UDPRouteUseDefaultGateways,
+    },
+    backendtlspolicies::{
+        BackendTLSPolicy, BackendTLSPolicySpec, BackendTLSPolicyTargetRefs,
+        BackendTLSPolicyValidation, BackendTLSPolicyValidationCaCertificateRefs,
+        BackendTLSPolicyValidationWellKnownCaCertificates,
+    },
+    gateways,
+    referencegrants::{ReferenceGrant, ReferenceGrantFrom, ReferenceGrantSpec, ReferenceGrantTo},
+};
+use k8s_openapi::api::{
+    core::v1::{ConfigMap, Namespace, Service},
+    networking::v1::{
+        HTTPIngressRuleValue, Ingress, IngressBackend, IngressRule, ServiceBackendPort,
+    },
+};
+use kube::{Api, Resource, ResourceExt, api::ObjectMeta, runtime::reflector::ObjectRef};
+
+use crate::{
+    args,
+    backends::{BackendSpec, parse_backend_list},
+    canary::{self, Canary},
+    consts,
+    crd::{EnvoyBackend, EnvoyBackendEndpoint, EnvoyBackendFqdn, EnvoyBackendSpec},
+    ctx,
+    err::{I2GError, I2GResult, PortError},
+    filters,
+    metrics::{Metrics, SkipReason},
+    naming,
+    providers::AnnotationProvider,
+    settings::Settings,
+    templates,
+    utils::{
+        self, LabelSelector, ObjectMetaI2GExt, normalize_path, sanitize_hostname, tls_host_secrets,
+        wildcard_prefix,
+    },
+    value_filters,
+    value_filters::{HeadersMatchersList, MatchRule, MatcherList, QueryMatchersList},
+};
+
+#[derive(Clone)]
+pub struct RouteInputInfo<'a> {
+    pub ingress_name: String,
+    pub ingress_meta: &'a ObjectMeta,
+    pub ingress_namespace: String,
+    /// Namespace of backends, the Ingress namespace unless `i2g-operator/backend-namespace` is set.
+    pub backend_namespace: String,
+    /// Gateways routes are attached to, as name and namespace.
+    pub gateways: Vec<(String, String)>,
+    pub section_name: Option<String>,
+    pub hostname: String,
+    pub header_matchers: Option<value_filters::HeadersMatchersList>,
+    pub query_matchers: Option<value_filters::QueryMatchersList>,
+    /// HTTP methods to match, each one in a separate match.
+    pub methods: Vec<HTTPRouteRulesMatchesMethod>,
+    pub filters: Vec<HTTPRouteRulesFilters>,
+    pub timeouts: Option<HTTPRouteRulesTimeouts>,
+    /// Attach TCPRoutes and UDPRoutes to default Gateways instead of the parent gateway.
+    pub use_default_gateways: bool,
+    /// Port of the gateway listener, used for hosts with TLS and for TCP and UDP rules.
+    pub parent_port: Option<i32>,
+    /// Backends of canary Ingresses for the host.
+    pub canaries: Vec<Canary>,
+    /// Retry policy of HTTPRoute rules, requires experimental CRDs.
+    pub retry: Option<HTTPRouteRulesRetry>,
+    /// Validated `i2g-operator/route-name-template` annotation.
+    pub name_template: Option<String>,
+}
+
+/// Name of a generated route of the kind (`http`, `tcp`, `udp`, `tls` or `redirect`).
+/// Rendered from the route name template if it's set,
+/// otherwise `default_suffix` is appended to the Ingress name and host.
+fn route_name(
+    route_info: &RouteInputInfo<'_>,
+    kind: &str,
+    index: usize,
+    default_suffix: &str,
+) -> String {
+    let default_name = || {
+        naming::route_name(
+            &route_info.ingress_name,
+            &route_info.hostname,
+            default_suffix,
+        )
+    };
+    let Some(template) = &route_info.name_template else {
+        return default_name();
+    };
+    naming::render_route_name(
+        template,
+        &route_info.ingress_name,
+        &route_info.ingress_namespace,
+        &route_info.hostname,
+        kind,
+        index,
+    )
+    .unwrap_or_else(|_| default_name())
+}
+
+/// Service from the cache, or from the API if it's not cached yet.
+/// Services can't be looked up offline.
+async fn get_service(
+    ctx: &ctx::Context,
+    namespace: &str,
+    svc_name: &str,
+) -> Result<Option<Arc<Service>>, kube::Error> {
+    if ctx.offline_ports.is_some() {
+        return Ok(None);
+    }
+    let cached = ctx
+        .services
+        .as_ref()
+        .and_then(|store| store.get(&ObjectRef::new(svc_name).within(namespace)));
+    match cached {
+        Some(svc) => Ok(Some(svc)),
+        // The cache may be not synced yet or lag behind the API.
+        None => Ok(Api::<Service>::namespaced(ctx.client.clone(), namespace)
+            .get_opt(svc_name)
+            .await?
+            .map(Arc::new)),
+    }
+}
+
+/// Resolve the port number of a service.
+/// Named ports are looked up in the static port map in offline mode,
+/// otherwise in the Service resource.
+async fn get_svc_port_number(
+    ctx: &ctx::Context,
+    namespace: &str,
+    svc_name: &str,
+    port_def: &ServiceBackendPort,
+) -> Result<i32, PortError> {
+    if let Some(number) = port_def.number {
+        return Ok(number);
+    }
+    let Some(port_name) = &port_def.name else {
+        return Err(PortError::NoPort(svc_name.to_string()));
+    };
+    if let Some(ports) = &ctx.offline_ports {
+        return ports
+            .iter()
+            .find(|port| port.service == svc_name && port.name == *port_name)
+            .map(|port| port.number)
+            .ok_or_else(|| PortError::PortNameNotFound {
+                svc_name: svc_name.to_string(),
+                port_name: port_name.clone(),
+            });
+    }
+    let svc = get_service(ctx, namespace, svc_name)
+        .await?
+        .ok_or_else(|| PortError::ServiceNotFound(svc_name.to_string()))?;
+    let ports = svc
+        .spec
+        .as_ref()
+        .and_then(|spec| spec.ports.as_ref())
+        .filter(|ports| !ports.is_empty())
+        .ok_or_else(|| PortError::NoServicePorts(svc_name.to_string()))?;
+    ports
+        .iter()
+        .find(|port| port.name.as_ref() == Some(port_name))
+        .map(|port| port.port)
+        .ok_or_else(|| PortError::PortNameNotFound {
+            svc_name: svc_name.to_string(),
+            port_name: port_name.clone(),
+        })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum EitherQueryOrHeaderMatcher {
+    Header(MatchRule),
+    Query(MatchRule),
+}
+
+impl From<EitherQueryOrHeaderMatcher> for MatchRule {
+    fn from(value: EitherQueryOrHeaderMatcher) -> Self {
+        match value {
+            EitherQueryOrHeaderMatcher::Header(match_rule) => match_rule,
+            EitherQueryOrHeaderMatcher::Query(match_rule) => match_rule,
+        }
+    }
+}
+
+type MatchRuleset = (
+    Option<HeadersMatchersList>,
+    Option<QueryMatchersList>,
+    Option<HTTPRouteRulesMatchesMethod>,
+);
+
+/// Combine header and query matchers with every matched method.
+/// Duplicate combinations are removed, combinations over `max` are dropped
+/// and the reason is added to `warnings`.
+fn create_match_rulesets(
+    route_info: &RouteInputInfo<'_>,
+    max: usize,
+    warnings: &mut Vec<String>,
+) -> Vec<MatchRuleset> {
+    let rulesets = create_header_query_rulesets(route_info);
+    let methods = match route_info.methods.as_slice() {
+        [] => vec![None],
+        methods => methods.iter().cloned().map(Some).collect(),
+    };
+    let mut unique = Vec::<MatchRuleset>::new();
+    for (headers, query) in rulesets {
+        for method in &methods {
+            let ruleset = (headers.clone(), query.clone(), method.clone());
+            if !unique.contains(&ruleset) {
+                unique.push(ruleset);
+            }
+        }
+    }
+    if unique.len() > max {
+        let msg = format!(
+            "Ingress matchers produce {} combinations for host {}, only the first {max} are used",
+            unique.len(),
+            route_info.hostname
+        );
+        tracing::warn!("{msg}");
+        warnings.push(msg);
+        unique.truncate(max);
+    }
+    unique
+}
+
+fn create_header_query_rulesets(
+    route_info: &RouteInputInfo<'_>,
+) -> Vec<(Option<HeadersMatchersList>, Option<QueryMatchersList>)> {
+    let mut headers_cart = vec![];
+    if let Some(header_matcher) = &route_info.header_matchers {
+        headers_cart = header_matcher
+            .0
+            .catesian_product()
+            .into_iter()
+            .map(|rules| {
+                rules
+                    .into_iter()
+                    .map(EitherQueryOrHeaderMatcher::Header)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+    }
+    let mut query_cart = vec![];
+    if let Some(query_matcher) = &route_info.query_matchers {
+        query_cart = query_matcher
+            .0
+            .catesian_product()
+            .into_iter()
+            .map(|rules| {
+                rules
+                    .into_iter()
+                    .map(EitherQueryOrHeaderMatcher::Query)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+    }
+
+    if headers_cart.is_empty() && query_cart.is_empty() {
+        return vec![(None, None)];
+    }
+    if headers_cart.is_empty() {
+        let mut res = vec![];
+        for matchers in query_cart {
+            res.push((
+                None,
+                Some(QueryMatchersList(MatcherList(
+                    matchers.into_iter().map(Into::into).collect(),
+                ))),
+            ));
+        }
+        return res;
+    }
+    if query_cart.is_empty() {
+        let mut res = vec![];
+        for matchers in headers_cart {
+            res.push((
+                Some(HeadersMatchersList(MatcherList(
+                    matchers.into_iter().map(Into::into).collect(),
+                ))),
+                None,
+            ));
+        }
+        return res;
+    }
+
+    let to_permute = [headers_cart, query_cart];
+
+    let mut res = vec![];
+
+    permutator::cartesian_product(
+        to_permute
+            .iter()
+            .map(|a| a.as_slice())
+            .collect::<Vec<_>>()
+            .as_slice(),
+        |product| {
+            let mut headers_list = vec![];
+            let mut query_list = vec![];
+            for item in product.iter().copied().flatten() {
+                match item {
+                    EitherQueryOrHeaderMatcher::Header(match_rule) => {
+                        headers_list.push(match_rule.clone())
+                    }
+                    EitherQueryOrHeaderMatcher::Query(match_rule) => {
+                        query_list.push(match_rule.clone())
+                    }
+                }
+            }
+            let mut query_ruleset = None;
+            let mut header_ruleset = None;
+            if !query_list.is_empty() {
+                query_ruleset = Some(QueryMatchersList(MatcherList(query_list)));
+            }
+            if !headers_list.is_empty() {
+                header_ruleset = Some(HeadersMatchersList(MatcherList(headers_list)));
+            }
+            res.push((header_ruleset, query_ruleset));
+        },
+    );
+    if res.is_empty() {
+        return vec![(None, None)];
+    }
+    res
+}
+
+/// Header matcher pinning the rule to the hostname.
+///
+/// Wildcard hostnames are translated to a regular expression
+/// matching exactly one DNS label in place of `*`.
+fn host_header_match(hostname: &str) -> HTTPRouteRulesMatchesHeaders {
+    if let Some(domain) = hostname.strip_prefix("*.") {
+        return HTTPRouteRulesMatchesHeaders {
+            name: "Host".to_string(),
+            r#type: Some(HTTPRouteRulesMatchesHeadersType::RegularExpression),
+            value: format!("^[^.]+\\.{}$", regex::escape(domain)),
+        };
+    }
+    HTTPRouteRulesMatchesHeaders {
+        name: "Host".to_string(),
+        r#type: Some(HTTPRouteRulesMatchesHeadersType::Exact),
+        value: hostname.to_string(),
+    }
+}
+
+fn rule_headers(
+    header_matchers: Option<HeadersMatchersList>,
+    host_header: Option<HTTPRouteRulesMatchesHeaders>,
+) -> Option<Vec<HTTPRouteRulesMatchesHeaders>> {
+    let mut headers: Vec<HTTPRouteRulesMatchesHeaders> =
+        header_matchers.map(Into::into).unwrap_or_default();
+    headers.extend(host_header);
+    (!headers.is_empty()).then_some(headers)
+}
+
+/// Backend of generated route rules: a Service port or a custom resource.
+#[derive(Debug, Clone, PartialEq)]
+struct RouteBackend {
+    /// Group and kind of a resource backend, None for Services.
+    resource: Option<(String, String)>,
+    name: String,
+    /// Service port, resource backends don't have one.
+    port: Option<i32>,
+}
+
+impl RouteBackend {
+    fn service(name: String, port: i32) -> Self {
+        RouteBackend {
+            resource: None,
+            name,
+            port: Some(port),
+        }
+    }
+
+    fn group(&self) -> Option<String> {
+        self.resource.as_ref().map(|(group, _)| group.clone())
+    }
+
+    fn kind(&self) -> Option<String> {
+        self.resource.as_ref().map(|(_, kind)| kind.clone())
+    }
+}
+
+/// Resolve service name and port number, or the referenced resource, of the Ingress backend.
+/// Returns None if the backend can't be used in routes,
+/// the reason is added to `warnings`.
+async fn resolve_ingress_backend(
+    ctx: &ctx::Context,
+    namespace: &str,
+    backend: &IngressBackend,
+    warnings: &mut Vec<String>,
+) -> anyhow::Result<Option<RouteBackend>> {
+    let mut skip = |reason: SkipReason, message: String| {
+        tracing::warn!("{message}");
+        ctx.metrics.record_skip(reason);
+        warnings.push(message);
+        Ok(None)
+    };
+    if let Some(resource) = &backend.resource {
+        let group = resource.api_group.clone().unwrap_or_default();
+        let group_kind = match group.as_str() {
+            "" => resource.kind.clone(),
+            group => format!("{}.{group}", resource.kind),
+        };
+        if !ctx.args.resource_backend_kinds.contains(&group_kind) {
+            let message = format!(
+                "Backend {group_kind} {} is not in --resource-backend-kinds, \
+                 gateways may not support it",
+                resource.name
+            );
+            tracing::warn!("{message}");
+            warnings.push(message);
+        }
+        return Ok(Some(RouteBackend {
+            resource: Some((group, resource.kind.clone())),
+            name: resource.name.clone(),
+            port: None,
+        }));
+    }
+    let Some(svc) = &backend.service else {
+        return skip(
+            SkipReason::NoService,
+            "Skipping backend without service".to_string(),
+        );
+    };
+    let Some(svc_port) = &svc.port else {
+        return skip(
+            SkipReason::NoServicePort,
+            format!(
+                "Skipping backend without service port for service {}",
+                svc.name
+            ),
+        );
+    };
+    match get_svc_port_number(ctx, namespace, &svc.name, svc_port).await {
+        Ok(svc_port_number) => Ok(Some(RouteBackend::service(
+            svc.name.clone(),
+            svc_port_number,
+        ))),
+        Err(err @ PortError::NoServicePorts(_)) if ctx.args.strict_service_ports => {
+            ctx.metrics.record_skip(SkipReason::ServiceWithoutPorts);
+            Err(err.into())
+        }
+        Err(err @ PortError::NoServicePorts(_)) => skip(
+            SkipReason::ServiceWithoutPorts,
+            format!("Skipping backend: {err}"),
+        ),
+        Err(err) => skip(
+            SkipReason::UnresolvablePort,
+            format!("Skipping backend with unresolvable service port: {err}"),
+        ),
+    }
+}
+
+/// Resolve the maintenance backend if the Ingress is in maintenance mode.
+async fn maintenance_backend(
+    ctx: &ctx::Context,
+    route_info: &RouteInputInfo<'_>,
+) -> anyhow::Result<Option<RouteBackend>> {
+    let annotations = route_info.ingress_meta.annotations.as_ref();
+    let maintenance = annotations
+        .and_then(|ann| ann.get(consts::MAINTENANCE))
+        .map(|v| v.to_lowercase() == "true")
+        .unwrap_or(false);
+    if !maintenance {
+        return Ok(None);
+    }
+    let raw_backend = annotations
+        .and_then(|ann| ann.get(consts::MAINTENANCE_BACKEND))
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Maintenance mode is enabled, but {} annotation is not set",
+                consts::MAINTENANCE_BACKEND
+            )
+        })?;
+    let backend = raw_backend.parse::<BackendSpec>()?;
+    let port = get_svc_port_number(
+        ctx,
+        &route_info.backend_namespace,
+        &backend.name,
+        &backend.port,
+    )
+    .await
+    .map_err(|err| {
+        ctx.metrics.record_skip(SkipReason::UnresolvablePort);
+        anyhow::anyhow!("Couldn't resolve port of the maintenance backend: {err}")
+    })?;
+    Ok(Some(RouteBackend::service(backend.name, port)))
+}
+
+/// RequestMirror filter for the backend from the mirror annotation.
+async fn mirror_filter(
+    ctx: &ctx::Context,
+    route_info: &RouteInputInfo<'_>,
+) -> anyhow::Result<Option<HTTPRouteRulesFilters>> {
+    let Some(raw_backend) = route_info
+        .ingress_meta
+        .annotations
+        .as_ref()
+        .and_then(|ann| ann.get(consts::MIRROR_BACKEND))
+    else {
+        return Ok(None);
+    };
+    let backend = raw_backend.parse::<BackendSpec>()?;
+    if let Some(percent) = backend.weight
+        && percent > 100
+    {
+        anyhow::bail!("Mirror percent must be between 0 and 100, got {percent}");
+    }
+    let port = get_svc_port_number(
+        ctx,
+        &route_info.backend_namespace,
+        &backend.name,
+        &backend.port,
+    )
+    .await
+    .map_err(|err| {
+        ctx.metrics.record_skip(SkipReason::UnresolvablePort);
+        anyhow::anyhow!("Couldn't resolve port of the mirror backend: {err}")
+    })?;
+    Ok(Some(filters::request_mirror(
+        &backend.name,
+        port,
+        backend.weight,
+    )))
+}
+
+fn backend_ref(backend: &RouteBackend, weight: Option<i32>) -> HTTPRouteRulesBackendRefs {
+    HTTPRouteRulesBackendRefs {
+        name: backend.name.clone(),
+        port: backend.port,
+        kind: backend.kind(),
+        group: backend.group(),
+        namespace: None,
+        filters: None,
+        weight,
+    }
+}
+
+/// Parent refs of an HTTPRoute, one for each gateway of the route.
+fn http_parent_refs(
+    route_info: &RouteInputInfo<'_>,
+    section_name: &Option<String>,
+    port: Option<i32>,
+) -> Vec<HTTPRouteParentRefs> {
+    let gw_group = <gateways::Gateway as kube::Resource>::group(&());
+    let gw_kind = <gateways::Gateway as kube::Resource>::kind(&());
+    route_info
+        .gateways
+        .iter()
+        .map(|(name, namespace)| HTTPRouteParentRefs {
+            group: Some(gw_group.to_string()),
+            kind: Some(gw_kind.to_string()),
+            name: name.clone(),
+            namespace: Some(namespace.clone()),
+            port,
+            section_name: section_name.clone(),
+        })
+        .collect()
+}
+
+/// Maximum number of rules in a single HTTPRoute allowed by gateway-api.
+const MAX_ROUTE_RULES: usize = 16;
+/// Maximum number of hostnames in a single HTTPRoute allowed by gateway-api.
+const MAX_ROUTE_HOSTNAMES: usize = 16;
+/// Name of the rule sending unmatched requests to `spec.defaultBackend`.
+const DEFAULT_BACKEND_RULE: &str = "default-backend";
+
+async fn create_http_routes(
+    ctx: Arc<ctx::Context>,
+    route_info: RouteInputInfo<'_>,
+    http: &k8s_openapi::api::networking::v1::HTTPIngressRuleValue,
+    provider: &dyn AnnotationProvider,
+    warnings: &mut Vec<String>,
+) -> anyhow::Result<Vec<HTTPRoute>> {
+    let split_routes = route_info
+        .ingress_meta
+        .annotations
+        .as_ref()
+        .and_then(|ann| ann.get(consts::SPLIT_ROUTES))
+        .map(|v| v.to_lowercase() == "true")
+        .unwrap_or(false);
+
+    // Rules without a host match all hostnames.
+    let has_host = !route_info.hostname.is_empty();
+    let host_header = (ctx.args.host_as_header_match && has_host)
+        .then(|| host_header_match(&route_info.hostname));
+    // Hostnames are matched by the Host header instead.
+    let hostnames =
+        (!ctx.args.host_as_header_match && has_host).then(|| vec![route_info.hostname.clone()]);
+
+    let path_type_overrides = route_info
+        .ingress_meta
+        .annotations
+        .as_ref()
+        .and_then(|ann| ann.get(consts::PATH_TYPES))
+        .map(|raw| value_filters::parse_path_types(raw))
+        .transpose()?
+        .unwrap_or_default();
+
+    let path_rewrite = route_info
+        .ingress_meta
+        .annotations
+        .as_ref()
+        .and_then(|ann| {
+            ann.get(consts::REWRITE_PREFIX)
+                .cloned()
+                .map(filters::PathRewrite::Prefix)
+                .or_else(|| {
+                    provider
+                        .get(ann, consts::NGINX_REWRITE_TARGET)
+                        .cloned()
+                        .map(filters::PathRewrite::NginxTarget)
+                })
+        });
+
+    let match_ruleset =
+        create_match_rulesets(&route_info, ctx.args.max_generated_matches, warnings);
+    ctx.metrics
+        .match_ruleset_size
+        .observe(match_ruleset.len() as f64);
+    tracing::debug!("Match ruleset: \n{match_ruleset:#?}");
+
+    let mut rules = vec![];
+
+    let maintenance_backend = maintenance_backend(&ctx, &route_info).await?;
+    let mirror = mirror_filter(&ctx, &route_info).await?;
+
+    for path in &http.paths {
+        // In maintenance mode all paths are routed to the maintenance backend.
+        let backend = match &maintenance_backend {
+            Some(backend) => backend.clone(),
+            None => {
+                let Some(backend) = resolve_ingress_backend(
+                    &ctx,
+                    &route_info.backend_namespace,
+                    &path.backend,
+                    warnings,
+                )
+                .await?
+                else {
+                    continue;
+                };
+                backend
+            }
+        };
+        let path_type_override = path
+            .path
+            .as_ref()
+            .and_then(|raw_path| path_type_overrides.get(raw_path));
+        let mut match_type = match (path_type_override, path.path_type.as_str()) {
+            (Some(path_type), _) => path_type.clone(),
+            (None, "Prefix") => HTTPRouteRulesMatchesPathType::PathPrefix,
+            (None, "Exact") => HTTPRouteRulesMatchesPathType::Exact,
+            (None, "ImplementationSpecific") => HTTPRouteRulesMatchesPathType::PathPrefix,
+            (None, _) => {
+                ctx.metrics.record_skip(SkipReason::UnknownPathType);
+                return Err(anyhow::anyhow!(
+                    "Unknown path type: {}",
+                    path.path_type.as_str()
+                ));
+            }
+        };
+        let mut ingress_path = path.path.clone();
+        if ctx.args.translate_wildcard_paths
+            && path_type_override.is_none()
+            && let Some(prefix) = ingress_path.as_deref().and_then(wildcard_prefix)
+        {
+            tracing::debug!("Translating wildcard path {ingress_path:?} to prefix {prefix}");
+            ingress_path = Some(prefix);
+            match_type = HTTPRouteRulesMatchesPathType::PathPrefix;
+        }
+        let mut path_filters = route_info.filters.clone();
+        path_filters.extend(mirror.clone());
+        if let Some(rewrite) = &path_rewrite {
+            let raw_path = ingress_path.clone().unwrap_or_else(|| "/".to_string());
+            match filters::url_rewrite(rewrite, &raw_path, &match_type) {
+                Ok((filter, prefix)) => {
+                    path_filters.push(filter);
+                    if let Some(prefix) = prefix {
+                        ingress_path = Some(prefix);
+                        match_type = HTTPRouteRulesMatchesPathType::PathPrefix;
+                    }
+                    // Only static values are supported, so the header carries
+                    // the matched Ingress path rather than the full request path.
+                    if let Some(header) = &ctx.args.original_path_header {
+                        let original = ingress_path.as_deref().unwrap_or("/");
+                        filters::set_request_header(&mut path_filters, header, original);
+                    }
+                }
+                Err(err) => {
+                    ctx.metrics.record_skip(SkipReason::UnsupportedRewrite);
+                    let msg = format!("Skipping path {raw_path}: {err}");
+                    tracing::warn!("{msg}");
+                    warnings.push(msg);
+                    continue;
+                }
+            }
+        }
+        let path_value = ingress_path.as_ref().map(|raw_path| {
+            let normalized = normalize_path(raw_path, ctx.args.path_normalization);
+            if &normalized != raw_path {
+                tracing::warn!("Path {raw_path} was normalized to {normalized}");
+            }
+            normalized
+        });
+
+        // Canaries are ignored in maintenance mode.
+        let mut canaries = route_info
+            .canaries
+            .iter()
+            .filter(|canary| maintenance_backend.is_none() && canary.path == path.path);
+        let canary = canaries.next();
+        if let Some(extra) = canaries.next() {
+            let msg = format!(
+                "Ignoring canary Ingress {} for path {}, only one canary per path is supported",
+                extra.ingress_name,
+                path.path.as_deref().unwrap_or("/")
+            );
+            tracing::warn!("{msg}");
+            warnings.push(msg);
+        }
+        let canary = match canary {
+            Some(canary) => resolve_ingress_backend(
+                &ctx,
+                &route_info.backend_namespace,
+                &canary.backend,
+                warnings,
+            )
+            .await?
+            .map(|backend| (canary, backend)),
+            None => None,
+        };
+        let backend_refs = match &canary {
+            Some((canary, canary_backend)) if let Some(weight) = canary.weight => vec![
+                backend_ref(&backend, Some(canary.weight_total - weight)),
+                backend_ref(canary_backend, Some(weight)),
+            ],
+            _ => vec![backend_ref(&backend, None)],
+        };
+
+        for (num, (header_matchers, query_matchers, method)) in match_ruleset.iter().enumerate() {
+            let headers = rule_headers(header_matchers.clone(), host_header.clone());
+            let rule_matches = |headers| {
+                Some(vec![HTTPRouteRulesMatches {
+                    headers,
+                    method: method.clone(),
+                    query_params: query_matchers.clone().map(Into::into),
+                    path: Some(HTTPRouteRulesMatchesPath {
+                        r#type: Some(match_type.clone()),
+                        value: path_value.clone(),
+                    }),
+                }])
+            };
+            // Requests with the canary header always go to the canary backend.
+            if let Some((canary, canary_backend)) = &canary
+                && let Some((header, value)) = &canary.header
+            {
+                let mut canary_headers = headers.clone().unwrap_or_default();
+                canary_headers.push(HTTPRouteRulesMatchesHeaders {
+                    name: header.clone(),
+                    r#type: Some(HTTPRouteRulesMatchesHeadersType::Exact),
+                    value: value.clone(),
+                });
+                rules.push(HTTPRouteRules {
+                    name: Some(naming::rule_name(
+                        path_value.as_deref(),
+                        &format!("{num}-canary"),
+                    )),
+                    backend_refs: Some(vec![backend_ref(canary_backend, None)]),
+                    matches: rule_matches(Some(canary_headers)),
+                    filters: (!path_filters.is_empty()).then(|| path_filters.clone()),
+                    timeouts: route_info.timeouts.clone(),
+                    retry: route_info.retry.clone(),
+                    session_persistence: None,
+                });
+            }
+            rules.push(HTTPRouteRules {
+                name: Some(naming::rule_name(path_value.as_deref(), &num.to_string())),
+                backend_refs: Some(backend_refs.clone()),
+                matches: rule_matches(headers),
+                filters: (!path_filters.is_empty()).then(|| path_filters.clone()),
+                timeouts: route_info.timeouts.clone(),
+                retry: route_info.retry.clone(),
+                session_persistence: None,
+            });
+        }
+    }
+    if rules.is_empty() {
+        return Err(anyhow::anyhow!("No valid paths found"));
+    }
+    let mut rules = merge_path_types(rules);
+    dedup_rule_names(&mut rules);
+
+    let make_route = |name: &str, rules: Vec<HTTPRouteRules>| {
+        HTTPRoute::new(
+            name,
+            HTTPRouteSpec {
+                hostnames: hostnames.clone(),
+                parent_refs: Some(http_parent_refs(
+                    &route_info,
+                    &route_info.section_name,
+                    route_info.parent_port,
+                )),
+                rules: Some(rules),
+                use_default_gateways: None,
+            },
+        )
+    };
+
+    // If split_routes is enabled, create a separate HTTPRoute for each rule.
+    if split_routes {
+        return Ok(rules
+            .into_iter()
+            .enumerate()
+            .map(|(index, rule)| {
+                make_route(
+                    &route_name(&route_info, "http", index, &index.to_string()),
+                    vec![rule],
+                )
+            })
+            .collect());
+    }
+
+    // Split routes is disabled, create a single HTTPRoute with all rules.
+    // HTTPRoute can't have more rules than the limit, so larger sets are sharded.
+    if rules.len() <= MAX_ROUTE_RULES {
+        return Ok(vec![make_route(
+            &route_name(&route_info, "http", 0, "http"),
+            rules,
+        )]);
+    }
+    Ok(rules
+        .chunks(MAX_ROUTE_RULES)
+        .enumerate()
+        .map(|(index, chunk)| {
+            let name = route_name(&route_info, "http", index, &format!("http-part-{index}"));
+            make_route(&name, chunk.to_vec())
+        })
+        .collect())
+}
+
+/// Merge rules which differ only in the path type of their match, e.g. Exact and Prefix
+/// paths `/foo` to the same backend with the same matchers, into one rule with a match
+/// of every path type. Generated rules have a single match, merged rules keep the name
+/// and position of the first one.
+fn merge_path_types(rules: Vec<HTTPRouteRules>) -> Vec<HTTPRouteRules> {
+    // The rule without its name and the path type of its first match.
+    let key = |rule: &HTTPRouteRules| {
+        let mut rule = rule.clone();
+        rule.name = None;
+        let first = rule.matches.as_mut().and_then(|matches| {
+            matches.truncate(1);
+            matches.first_mut()
+        });
+        if let Some(path) = first.and_then(|first| first.path.as_mut()) {
+            path.r#type = None;
+        }
+        rule
+    };
+    let mut merged = Vec::<HTTPRouteRules>::with_capacity(rules.len());
+    for rule in rules {
+        let rule_key = key(&rule);
+        match merged.iter_mut().find(|other| key(other) == rule_key) {
+            Some(other) => {
+                let matches = other.matches.get_or_insert_default();
+                for rule_match in rule.matches.into_iter().flatten() {
+                    if !matches.contains(&rule_match) {
+                        matches.push(rule_match);
+                    }
+                }
+            }
+            None => merged.push(rule),
+        }
+    }
+    merged
+}
+
+/// Make rule names unique by appending a counter to repeated names.
+/// Names are built from sanitized paths, so e.g. Exact and Prefix matches
+/// of `/foo`, or `/foo` and `/foo/`, get the same name otherwise.
+fn dedup_rule_names(rules: &mut [HTTPRouteRules]) {
+    let mut names = HashSet::new();
+    for rule in rules {
+        let Some(name) = rule.name.clone() else {
+            continue;
+        };
+        let mut unique = name.clone();
+        let mut counter = 1;
+        while !names.insert(unique.clone()) {
+            unique = naming::fit(&format!("{name}-{counter}"));
+            counter += 1;
+        }
+        rule.name = Some(unique);
+    }
+}
+
+/// Create an HTTPRoute redirecting plain HTTP requests for the host to HTTPS.
+/// The route is attached to the HTTP listener: `--default-section-http` or port 80.
+fn create_redirect_route(
+    ctx: &ctx::Context,
+    route_info: &RouteInputInfo<'_>,
+    redirect_port: Option<i32>,
+) -> HTTPRoute {
+    let section_name = ctx
+        .args
+        .default_section_http
+        .clone()
+        .filter(|_| ctx.args.shadow_prefix.is_none());
+    let port = section_name.is_none().then_some(consts::HTTP_LISTENER_PORT);
+    let host_as_header = ctx.args.host_as_header_match;
+    HTTPRoute::new(
+        &route_name(route_info, "redirect", 0, "redirect"),
+        HTTPRouteSpec {
+            hostnames: (!host_as_header).then(|| vec![route_info.hostname.clone()]),
+            parent_refs: Some(http_parent_refs(route_info, &section_name, port)),
+            rules: Some(vec![HTTPRouteRules {
+                name: Some("https-redirect".to_string()),
+                backend_refs: None,
+                matches: host_as_header.then(|| {
+                    vec![HTTPRouteRulesMatches {
+                        headers: Some(vec![host_header_match(&route_info.hostname)]),
+                        ..HTTPRouteRulesMatches::default()
+                    }]
+                }),
+                filters: Some(vec![filters::https_redirect(redirect_port)]),
+                timeouts: None,
+                retry: None,
+                session_persistence: None,
+            }]),
+            use_default_gateways: None,
+        },
+    )
+}
+
+/// Hosts of the Ingress and listeners used by them,
+/// requests to these hosts which don't match any path go to the default backend.
+#[derive(Default)]
+struct CatchAll<'a> {
+    route_info: Option<RouteInputInfo<'a>>,
+    hosts: Vec<String>,
+    parents: Vec<(Option<String>, Option<i32>)>,
+}
+
+impl<'a> CatchAll<'a> {
+    fn add(&mut self, route_info: &RouteInputInfo<'a>) {
+        let parent = (route_info.section_name.clone(), route_info.parent_port);
+        if !self.parents.contains(&parent) {
+            self.parents.push(parent);
+        }
+        if !self.hosts.contains(&route_info.hostname) {
+            self.hosts.push(route_info.hostname.clone());
+        }
+        self.route_info.get_or_insert_with(|| RouteInputInfo {
+            hostname: String::new(),
+            canaries: vec![],
+            ..route_info.clone()
+        });
+    }
+}
+
+/// Whether the path catches all requests to the host, like the default backend does.
+fn is_root_path(path: &k8s_openapi::api::networking::v1::HTTPIngressPath) -> bool {
+    path.path.as_deref().is_none_or(|path| path == "/") && path.path_type != "Exact"
+}
+
+/// Create HTTPRoutes sending requests to the hosts of the Ingress which don't match
+/// any of their paths to `spec.defaultBackend`. The rule matches the `/` prefix,
+/// so path rules of the hosts take precedence. Hosts are split between several routes
+/// if there are more of them than a single route can have.
+async fn create_default_backend_routes(
+    ctx: &ctx::Context,
+    catch_all: &CatchAll<'_>,
+    backend: &IngressBackend,
+    warnings: &mut Vec<String>,
+) -> anyhow::Result<Vec<HTTPRoute>> {
+    let Some(route_info) = &catch_all.route_info else {
+        return Ok(vec![]);
+    };
+    let backend = match maintenance_backend(ctx, route_info).await? {
+        Some(backend) => backend,
+        None => {
+            let Some(backend) =
+                resolve_ingress_backend(ctx, &route_info.backend_namespace, backend, warnings)
+                    .await?
+            else {
+                return Ok(vec![]);
+            };
+            backend
+        }
+    };
+    let path = Some(HTTPRouteRulesMatchesPath {
+        r#type: Some(HTTPRouteRulesMatchesPathType::PathPrefix),
+        value: Some("/".to_string()),
+    });
+    // Rules without a host make the route match all hostnames.
+    let all_hosts = catch_all.hosts.iter().any(String::is_empty);
+    let host_as_header = ctx.args.host_as_header_match;
+    let host_chunks = if all_hosts {
+        vec![&[][..]]
+    } else {
+        catch_all.hosts.chunks(MAX_ROUTE_HOSTNAMES).collect()
+    };
+    let parent_refs = catch_all
+        .parents
+        .iter()
+        .flat_map(|(section_name, port)| http_parent_refs(route_info, section_name, *port))
+        .collect::<Vec<_>>();
+    let mut routes = vec![];
+    for (index, hosts) in host_chunks.into_iter().enumerate() {
+        let matches = if host_as_header && !all_hosts {
+            hosts
+                .iter()
+                .map(|host| HTTPRouteRulesMatches {
+                    headers: Some(vec![host_header_match(host)]),
+                    path: path.clone(),
+                    ..HTTPRouteRulesMatches::default()
+                })
+                .collect()
+        } else {
+            vec![HTTPRouteRulesMatches {
+                path: path.clone(),
+                ..HTTPRouteRulesMatches::default()
+            }]
+        };
+        // The first route keeps the name it had before hosts were split.
+        let suffix = match index {
+            0 => "default-backend".to_string(),
+            _ => format!("default-backend-part-{index}"),
+        };
+        routes.push(HTTPRoute::new(
+            &route_name(route_info, "default-backend", index, &suffix),
+            HTTPRouteSpec {
+                hostnames: (!host_as_header && !all_hosts).then(|| hosts.to_vec()),
+                parent_refs: Some(parent_refs.clone()),
+                rules: Some(vec![HTTPRouteRules {
+                    name: Some(DEFAULT_BACKEND_RULE.to_string()),
+                    backend_refs: Some(vec![backend_ref(&backend, None)]),
+                    matches: Some(matches),
+                    filters: (!route_info.filters.is_empty()).then(|| route_info.filters.clone()),
+                    timeouts: route_info.timeouts.clone(),
+                    retry: route_info.retry.clone(),
+                    session_persistence: None,
+                }]),
+                use_default_gateways: None,
+            },
+        ));
+    }
+    Ok(routes)
+}
+
+/// Create a TLSRoute for an ssl-passthrough Ingress rule.
+/// Passthrough traffic can't be routed by path, so the backend of the first path is used.
+async fn create_tls_route(
+    ctx: Arc<ctx::Context>,
+    route_info: RouteInputInfo<'_>,
+    http: &k8s_openapi::api::networking::v1::HTTPIngressRuleValue,
+    warnings: &mut Vec<String>,
+) -> anyhow::Result<TLSRoute> {
+    let gw_group = <gateways::Gateway as kube::Resource>::group(&());
+    let gw_kind = <gateways::Gateway as kube::Resource>::kind(&());
+
+    let [path, rest @ ..] = http.paths.as_slice() else {
+        return Err(anyhow::anyhow!("No paths found"));
+    };
+    if rest.iter().any(|other| other.backend != path.backend) {
+        tracing::warn!(
+            "Paths of ssl-passthrough host {} have different backends, only the first one is used",
+            route_info.hostname
+        );
+    }
+    let Some(backend) =
+        resolve_ingress_backend(&ctx, &route_info.backend_namespace, &path.backend, warnings)
+            .await?
+    else {
+        return Err(anyhow::anyhow!("No valid backends found"));
+    };
+
+    Ok(TLSRoute::new(
+        &route_name(&route_info, "tls", 0, "tls"),
+        TLSRouteSpec {
+            hostnames: Some(route_info.hostname.clone())
+                .filter(|host| !host.is_empty())
+                .into_iter()
+                .collect(),
+            use_default_gateways: None,
+            rules: vec![TLSRouteRules {
+                name: None,
+                backend_refs: vec![TLSRouteRulesBackendRefs {
+                    kind: backend.kind(),
+                    group: backend.group(),
+                    name: backend.name,
+                    port: backend.port,
+                    namespace: None,
+                    weight: None,
+                }],
+            }],
+            parent_refs: Some(
+                route_info
+                    .gateways
+                    .iter()
+                    .map(|(name, namespace)| TLSRouteParentRefs {
+                        group: Some(gw_group.to_string()),
+                        kind: Some(gw_kind.to_string()),
+                        name: name.clone(),
+                        namespace: Some(namespace.clone()),
+                        port: None,
+                        section_name: route_info.section_name.clone(),
+                    })
+                    .collect(),
+            ),
+        },
+    ))
+}
+
+/// Resolve port numbers of backends of a TCP or UDP rule.
+async fn resolve_backend_ports(
+    ctx: &ctx::Context,
+    route_info: &RouteInputInfo<'_>,
+    backends: &[BackendSpec],
+    protocol: &str,
+) -> anyhow::Result<Vec<(BackendSpec, i32)>> {
+    let mut resolved = vec![];
+    for backend in backends {
+        let svc_port_number = get_svc_port_number(
+            ctx,
+            &route_info.backend_namespace,
+            &backend.name,
+            &backend.port,
+        )
+        .await
+        .map_err(|err| anyhow::anyhow!("Couldn't resolve port of a {protocol} backend: {err}"))?;
+        resolved.push((backend.clone(), svc_port_number));
+    }
+    if resolved.is_empty() {
+        return Err(anyhow::anyhow!("No backends found"));
+    }
+    Ok(resolved)
+}
+
+/// Routes generated from non-HTTP Ingress rules, see [`create_l4_route`].
+trait L4Route: kube::Resource<DynamicType = ()> + Sized {
+    /// Protocol of the backends, e.g. `TCP`.
+    const PROTOCOL: &'static str;
+
+    /// Route with a single rule. Default gateways are used if `parents` is None.
+    fn with_rule(
+        name: &str,
+        backends: Vec<(BackendSpec, i32)>,
+        parents: Option<Vec<RouteParent>>,
+    ) -> Self;
+}
+
+macro_rules! impl_l4_route {
+    ($route:ident, $protocol:literal, $spec:ident, $rules:ident, $backend_refs:ident, $parent_refs:ident, $default_gateways:ident) => {
+        impl L4Route for $route {
+            const PROTOCOL: &'static str = $protocol;
+
+            fn with_rule(
+                name: &str,
+                backends: Vec<(BackendSpec, i32)>,
+                parents: Option<Vec<RouteParent>>,
+            ) -> Self {
+                let gw_group = <gateways::Gateway as kube::Resource>::group(&());
+                let gw_kind = <gateways::Gateway as kube::Resource>::kind(&());
+                let backend_refs = backends
+                    .into_iter()
+                    .map(|(backend, port)| $backend_refs {
+                        name: backend.name,
+                        port: Some(port),
+                        kind: None,
+                        group: None,
+                        namespace: None,
+                        weight: backend.weight,
+                    })
+                    .collect();
+                let parent_refs = parents.as_ref().map(|parents| {
+                    parents
+                        .iter()
+                        .map(|(name, namespace, section_name, port)| $parent_refs {
+                            group: Some(gw_group.to_string()),
+                            kind: Some(gw_kind.to_string()),
+                            name: name.clone(),
+                            namespace: namespace.clone(),
+                            port: *port,
+                            section_name: section_name.clone(),
+                        })
+                        .collect()
+                });
+                $route::new(
+                    name,
+                    $spec {
+                        use_default_gateways: parents.is_none().then_some($default_gateways::All),
+                        rules: vec![$rules {
+                            name: None,
+                            backend_refs,
+                        }],
+                        parent_refs,
+                    },
+                )
+            }
+        }
+    };
+}
+
+impl_l4_route!(
+    TCPRoute,
+    "TCP",
+    TCPRouteSpec,
+    TCPRouteRules,
+    TCPRouteRulesBackendRefs,
+    TCPRouteParentRefs,
+    TCPRouteUseDefaultGateways
+);
+impl_l4_route!(
+    UDPRoute,
+    "UDP",
+    UDPRouteSpec,
+    UDPRouteRules,
+    UDPRouteRulesBackendRefs,
+    UDPRouteParentRefs,
+    UDPRouteUseDefaultGateways
+);
+
+/// Create a TCPRoute or UDPRoute for a non-HTTP Ingress rule.
+async fn create_l4_route<K: L4Route>(
+    ctx: Arc<ctx::Context>,
+    route_info: RouteInputInfo<'_>,
+    backends: &[BackendSpec],
+) -> anyhow::Result<K> {
+    let backends = resolve_backend_ports(&ctx, &route_info, backends, K::PROTOCOL).await?;
+    // Default gateways are selected by the gateway controller,
+    // so explicit parent refs are omitted.
+    let parents = (!route_info.use_default_gateways).then(|| {
+        route_info
+            .gateways
+            .iter()
+            .map(|(name, namespace)| {
+                (
+                    name.clone(),
+                    Some(namespace.clone()),
+                    route_info.section_name.clone(),
+                    route_info.parent_port,
+                )
+            })
+            .collect()
+    });
+    let suffix = K::PROTOCOL.to_lowercase();
+    Ok(K::with_rule(
+        &route_name(&route_info, &suffix, 0, &suffix),
+        backends,
+        parents,
+    ))
+}
+
+/// ReferenceGrant in the gateway namespace allowing routes
+/// from the Ingress namespace to reference the gateway.
+fn create_reference_grant(
+    ctx: &ctx::Context,
+    ingress_namespace: &str,
+    gw_name: &str,
+    gw_namespace: &str,
+) -> ReferenceGrant {
+    let gw_group = <gateways::Gateway as kube::Resource>::group(&());
+    let gw_kind = <gateways::Gateway as kube::Resource>::kind(&());
+    let mut route_kinds = vec![<HTTPRoute as kube::Resource>::kind(&())];
+    if ctx.args.experimental {
+        route_kinds.push(<TCPRoute as kube::Resource>::kind(&()));
+        route_kinds.push(<UDPRoute as kube::Resource>::kind(&()));
+        route_kinds.push(<TLSRoute as kube::Resource>::kind(&()));
+    }
+    let mut grant = ReferenceGrant::new(
+        &format!(
+            "i2g-{}-{}",
+            sanitize_hostname(ingress_namespace),
+            sanitize_hostname(gw_name)
+        ),
+        ReferenceGrantSpec {
+            from: route_kinds
+                .into_iter()
+                .map(|kind| ReferenceGrantFrom {
+                    group: gw_group.to_string(),
+                    kind: kind.to_string(),
+                    namespace: ingress_namespace.to_string(),
+                })
+                .collect(),
+            to: vec![ReferenceGrantTo {
+                group: gw_group.to_string(),
+                kind: gw_kind.to_string(),
+                name: Some(gw_name.to_string()),
+            }],
+        },
+    );
+    grant.metadata.namespace = Some(gw_namespace.to_string());
+    grant.meta_mut().add_managed_by_label();
+    grant
+}
+
+/// ReferenceGrant in the backend namespace allowing routes of the Ingress
+/// to reference their backends. Other Ingresses may reference other backends,
+/// so the grant is named after the Ingress.
+fn create_backend_reference_grant(
+    ingress: &Ingress,
+    backend_namespace: &str,
+    translation: &mut Translation,
+) -> ReferenceGrant {
+    let gw_group = <gateways::Gateway as kube::Resource>::group(&());
+    let ingress_namespace = ingress.namespace().unwrap_or_default();
+    let mut from = vec![];
+    if !translation.http_routes.is_empty() {
+        from.push(HTTPRoute::kind(&()));
+    }
+    if !translation.tcp_routes.is_empty() {
+        from.push(TCPRoute::kind(&()));
+    }
+    if !translation.udp_routes.is_empty() {
+        from.push(UDPRoute::kind(&()));
+    }
+    if !translation.tls_routes.is_empty() {
+        from.push(TLSRoute::kind(&()));
+    }
+    let to = backend_refs_mut(translation)
+        .into_iter()
+        .map(|backend_ref| {
+            (
+                backend_ref.group.clone().unwrap_or_default(),
+                backend_ref
+                    .kind
+                    .clone()
+                    .unwrap_or_else(|| Service::kind(&()).to_string()),
+                backend_ref.name.clone(),
+            )
+        })
+        .collect::<std::collections::BTreeSet<_>>();
+    let mut grant = ReferenceGrant::new(
+        &naming::fit(&format!(
+            "i2g-{}-{}-backends",
+            sanitize_hostname(&ingress_namespace),
+            sanitize_hostname(&ingress.name_any())
+        )),
+        ReferenceGrantSpec {
+            from: from
+                .into_iter()
+                .map(|kind| ReferenceGrantFrom {
+                    group: gw_group.to_string(),
+                    kind: kind.to_string(),
+                    namespace: ingress_namespace.clone(),
+                })
+                .collect(),
+            to: to
+                .into_iter()
+                .map(|(group, kind, name)| ReferenceGrantTo {
+                    group,
+                    kind,
+                    name: Some(name),
+                })
+                .collect(),
+        },
+    );
+    grant.metadata.namespace = Some(backend_namespace.to_string());
+    grant.meta_mut().add_managed_by_label();
+    grant
+}
+
+/// Whether backends of the Ingress are served over TLS,
+/// according to `backend-protocol` annotations.
+fn has_tls_backends(
+    provider: &dyn AnnotationProvider,
+    ingress: &Ingress,
+    translation: &mut Translation,
+) -> bool {
+    let Some(protocol) = provider.annotation(ingress.annotations(), consts::BACKEND_PROTOCOL)
+    else {
+        return false;
+    };
+    match protocol.to_uppercase().as_str() {
+        "HTTPS" | "GRPCS" => true,
+        "HTTP" | "GRPC" | "AUTO_HTTP" => false,
+        _ => {
+            translation.warn(format!(
+                "Backend protocol {protocol} is not supported, backends are reached over HTTP"
+            ));
+            false
+        }
+    }
+}
+
+/// Envoy Gateway Backend for the port of an ExternalName Service.
+/// Services may be shared by Ingresses, so the Backend is named after the Service and port only.
+fn create_envoy_backend(service: &str, port: i32, hostname: &str, namespace: &str) -> EnvoyBackend {
+    let mut backend = EnvoyBackend::new(
+        &naming::fit(&format!("{service}-{port}-external")),
+        EnvoyBackendSpec {
+            endpoints: vec![EnvoyBackendEndpoint {
+                fqdn: Some(EnvoyBackendFqdn {
+                    hostname: hostname.to_string(),
+                    port,
+                }),
+            }],
+        },
+    );
+    backend.metadata.namespace = Some(namespace.to_string());
+    backend.meta_mut().add_managed_by_label();
+    backend
+}
+
+/// Mutable fields of a backend reference of generated routes.
+struct BackendRefMut<'a> {
+    group: &'a mut Option<String>,
+    kind: &'a mut Option<String>,
+    name: &'a mut String,
+    namespace: &'a mut Option<String>,
+    port: &'a mut Option<i32>,
+}
+
+impl BackendRefMut<'_> {
+    fn is_service(&self) -> bool {
+        self.group.as_deref().unwrap_or_default().is_empty()
+            && self.kind.as_deref().unwrap_or("Service") == "Service"
+    }
+}
+
+macro_rules! backend_ref_mut {
+    ($r:expr) => {
+        BackendRefMut {
+            group: &mut $r.group,
+            kind: &mut $r.kind,
+            name: &mut $r.name,
+            namespace: &mut $r.namespace,
+            port: &mut $r.port,
+        }
+    };
+}
+
+/// Backend references of all generated routes, including backends of RequestMirror filters.
+fn backend_refs_mut(translation: &mut Translation) -> Vec<BackendRefMut<'_>> {
+    let mut refs = vec![];
+    for rule in translation
+        .http_routes
+        .iter_mut()
+        .flat_map(|route| route.spec.rules.iter_mut().flatten())
+    {
+        refs.extend(
+            rule.backend_refs
+                .iter_mut()
+                .flatten()
+                .map(|r| backend_ref_mut!(r)),
+        );
+        refs.extend(
+            rule.filters
+                .iter_mut()
+                .flatten()
+                .filter_map(|filter| filter.request_mirror.as_mut())
+                .map(|mirror| backend_ref_mut!(mirror.backend_ref)),
+        );
+    }
+    refs.extend(
+        translation
+            .tcp_routes
+            .iter_mut()
+            .flat_map(|route| route.spec.rules.iter_mut())
+            .flat_map(|rule| rule.backend_refs.iter_mut())
+            .map(|r| backend_ref_mut!(r)),
+    );
+    refs.extend(
+        translation
+            .udp_routes
+            .iter_mut()
+            .flat_map(|route| route.spec.rules.iter_mut())
+            .flat_map(|rule| rule.backend_refs.iter_mut())
+            .map(|r| backend_ref_mut!(r)),
+    );
+    refs.extend(
+        translation
+            .tls_routes
+            .iter_mut()
+            .flat_map(|route| route.spec.rules.iter_mut())
+            .flat_map(|rule| rule.backend_refs.iter_mut())
+            .map(|r| backend_ref_mut!(r)),
+    );
+    refs
+}
+
+/// Handle backends pointing at ExternalName Services according to `--external-name-backends`:
+/// keep referencing the Service with a warning, or reference Envoy Gateway Backends
+/// with the external hostname, which are added to the translation.
+async fn translate_external_name_backends(
+    ctx: &ctx::Context,
+    namespace: &str,
+    translation: &mut Translation,
+) -> I2GResult<()> {
+    let services = backend_refs_mut(translation)
+        .into_iter()
+        .filter(BackendRefMut::is_service)
+        .map(|backend| backend.name.clone())
+        .collect::<std::collections::BTreeSet<_>>();
+    let mut external = BTreeMap::new();
+    for service in services {
+        let svc = get_service(ctx, namespace, &service).await?;
+        let external_name = svc
+            .as_ref()
+            .and_then(|svc| svc.spec.as_ref())
+            .filter(|spec| spec.type_.as_deref() == Some("ExternalName"))
+            .and_then(|spec| spec.external_name.clone());
+        if let Some(external_name) = external_name {
+            external.insert(service, external_name);
+        }
+    }
+    if external.is_empty() {
+        return Ok(());
+    }
+    if ctx.args.external_name_backends == args::ExternalNameBackends::Service {
+        for (service, external_name) in external {
+            translation.warn(format!(
+                "Service {service} is an ExternalName Service for {external_name}, \
+                 not all gateways can route to it"
+            ));
+        }
+        return Ok(());
+    }
+    let mut backends = BTreeMap::new();
+    for backend_ref in backend_refs_mut(translation) {
+        let Some(external_name) = external.get(backend_ref.name.as_str()) else {
+            continue;
+        };
+        let Some(port) = *backend_ref.port else {
+            continue;
+        };
+        if !backend_ref.is_service() {
+            continue;
+        }
+        let backend = create_envoy_backend(backend_ref.name, port, external_name, namespace);
+        *backend_ref.group = Some(EnvoyBackend::group(&()).to_string());
+        *backend_ref.kind = Some(EnvoyBackend::kind(&()).to_string());
+        *backend_ref.name = backend.name_any();
+        backends.insert(backend.name_any(), backend);
+    }
+    translation.envoy_backends = backends.into_values().collect();
+    Ok(())
+}
+
+/// BackendTLSPolicy making gateways use TLS to reach the Service.
+/// Services may be shared by Ingresses, so the policy is named after the Service only.
+fn create_backend_tls_policy(
+    ingress: &Ingress,
+    service: &str,
+    namespace: &str,
+) -> BackendTLSPolicy {
+    let annotations = ingress.annotations();
+    let hostname = annotations
+        .get(consts::BACKEND_TLS_HOSTNAME)
+        .cloned()
+        .unwrap_or_else(|| format!("{service}.{namespace}.svc"));
+    let (ca_certificate_refs, well_known_ca_certificates) =
+        match annotations.get(consts::BACKEND_TLS_CA) {
+            Some(config_map) => (
+                Some(vec![BackendTLSPolicyValidationCaCertificateRefs {
+                    group: ConfigMap::group(&()).to_string(),
+                    kind: ConfigMap::kind(&()).to_string(),
+                    name: config_map.clone(),
+                }]),
+                None,
+            ),
+            None => (
+                None,
+                Some(BackendTLSPolicyValidationWellKnownCaCertificates::System),
+            ),
+        };
+    let mut policy = BackendTLSPolicy::new(
+        &naming::fit(&format!("{service}-backend-tls")),
+        BackendTLSPolicySpec {
+            target_refs: vec![BackendTLSPolicyTargetRefs {
+                group: Service::group(&()).to_string(),
+                kind: Service::kind(&()).to_string(),
+                name: service.to_string(),
+                section_name: None,
+            }],
+            validation: BackendTLSPolicyValidation {
+                ca_certificate_refs,
+                hostname,
+                subject_alt_names: None,
+                well_known_ca_certificates,
+            },
+            options: None,
+        },
+    );
+    policy.metadata.namespace = Some(namespace.to_string());
+    policy.meta_mut().add_managed_by_label();
+    policy
+}
+
+/// Timeouts of HTTPRoute rules from Ingress annotations or operator defaults.
+/// A backend request timeout greater than the request timeout is dropped,
+/// since gateway-api doesn't allow it.
+fn route_timeouts(
+    args: &args::I2GArgs,
+    provider: &dyn AnnotationProvider,
+    meta: &ObjectMeta,
+    warnings: &mut Vec<String>,
+) -> I2GResult<Option<HTTPRouteRulesTimeouts>> {
+    let annotations = meta.annotations.clone().unwrap_or_default();
+    let request = match annotations.get(consts::REQUEST_TIMEOUT) {
+        Some(raw) => Some(raw.parse::<gateway_api::Duration>().map_err(|err| {
+            I2GError::ParseError(format!("Invalid request timeout '{raw}': {err}"))
+        })?),
+        None => args.default_request_timeout,
+    };
+    let mut backend_request = provider
+        .backend_request_timeout(&annotations)?
+        .or(args.default_backend_request_timeout);
+    if let (Some(request), Some(backend)) = (request, backend_request)
+        && backend.as_millis() > request.as_millis()
+    {
+        let msg = format!(
+            "Ignoring backend request timeout {backend} greater than request timeout {request}"
+        );
+        tracing::warn!("{msg}");
+        warnings.push(msg);
+        backend_request = None;
+    }
+    if request.is_none() && backend_request.is_none() {
+        return Ok(None);
+    }
+    Ok(Some(HTTPRouteRulesTimeouts {
+        request: request.map(|t| t.to_string()),
+        backend_request: backend_request.map(|t| t.to_string()),
+    }))
+}
+
+/// Add annotations from `--route-annotation` templates.
+fn render_route_annotations(
+    args: &args::I2GArgs,
+    meta: &mut ObjectMeta,
+    ingress: &Ingress,
+    host: &str,
+    kind: &str,
+) -> I2GResult<()> {
+    let ingress_name = ingress.name_any();
+    let namespace = ingress.namespace().unwrap_or_default();
+    let values = [
+        ("ingress", ingress_name.as_str()),
+        ("namespace", namespace.as_str()),
+        ("host", host),
+        ("kind", kind),
+    ];
+    for annotation in &args.route_annotations {
+        let value = templates::render(&annotation.template, &values)?;
+        meta.add_annotation(&annotation.key, value);
+    }
+    Ok(())
+}
+
+/// Parse a JSON map of labels or annotations from the Ingress annotation.
+fn metadata_from_annotation(
+    ingress: &Ingress,
+    key: &str,
+    labels: bool,
+) -> I2GResult<BTreeMap<String, String>> {
+    let Some(raw) = ingress.annotations().get(key) else {
+        return Ok(BTreeMap::new());
+    };
+    let invalid = |msg: String| I2GError::ParseError(format!("Invalid {key} annotation: {msg}"));
+    let map = serde_json::from_str::<BTreeMap<String, String>>(raw)
+        .map_err(|err| invalid(err.to_string()))?;
+    for (name, value) in &map {
+        if !utils::is_qualified_name(name) {
+            return Err(invalid(format!("'{name}' is not a valid key")));
+        }
+        // Label values have the same format as names without a prefix.
+        if labels && !value.is_empty() && (value.contains('/') || !utils::is_qualified_name(value))
+        {
+            return Err(invalid(format!("'{value}' is not a valid label value")));
+        }
+    }
+    Ok(map)
+}
+
+/// Add labels, annotations and owner references to a generated route.
+fn finish_route<K>(
+    ctx: &ctx::Context,
+    route: &mut K,
+    ingress: &Ingress,
+    host: &str,
+) -> I2GResult<()>
+where
+    K: kube::Resource<DynamicType = ()>,
+{
+    let meta = route.meta_mut();
+    // Metadata of the Ingress is added first, so it can't override the operator's own.
+    let mut labels =
+        utils::matching_entries(ingress.meta().labels.as_ref(), &ctx.args.propagate_labels);
+    labels.extend(metadata_from_annotation(
+        ingress,
+        consts::ROUTE_LABELS,
+        true,
+    )?);
+    if !labels.is_empty() {
+        meta.labels.get_or_insert_default().extend(labels);
+    }
+    let mut annotations = utils::matching_entries(
+        ingress.meta().annotations.as_ref(),
+        &ctx.args.propagate_annotations,
+    );
+    annotations.extend(metadata_from_annotation(
+        ingress,
+        consts::ROUTE_ANNOTATIONS,
+        false,
+    )?);
+    if !annotations.is_empty() {
+        meta.annotations.get_or_insert_default().extend(annotations);
+    }
+    meta.add_managed_by_label();
+    meta.add_annotation(consts::OPERATOR_VERSION, consts::VERSION.to_string());
+    meta.add_annotation(consts::OWNER_INGRESS, ingress.name_any());
+    render_route_annotations(&ctx.args, meta, ingress, host, &K::kind(&()))?;
+    if let Some(prefix) = &ctx.args.shadow_prefix {
+        mark_shadow(meta, prefix);
+    }
+    // Ingresses read from files by the `convert` subcommand have no uid.
+    if ctx.args.link_to_ingress && ingress.uid().is_some() {
+        meta.add_owner(ingress);
+    }
+    Ok(())
+}
+
+/// Prefix the route name and label the route as a shadow route.
+fn mark_shadow(meta: &mut ObjectMeta, prefix: &str) {
+    meta.name = meta
+        .name
+        .as_ref()
+        .map(|name| naming::fit(&format!("{prefix}{name}")));
+    meta.labels
+        .get_or_insert_default()
+        .insert(consts::SHADOW_LABEL.to_string(), "true".to_string());
+}
+
+/// Merge routes which only differ by hostnames into the first of them.
+/// Routes without hostnames match all hosts and are never merged.
+fn consolidate_hosts(routes: Vec<HTTPRoute>) -> Vec<HTTPRoute> {
+    let without_hostnames = |route: &HTTPRoute| HTTPRouteSpec {
+        hostnames: None,
+        ..route.spec.clone()
+    };
+    let mut merged: Vec<HTTPRoute> = vec![];
+    for route in routes {
+        let Some(hostnames) = route.spec.hostnames.clone() else {
+            merged.push(route);
+            continue;
+        };
+        let target = merged.iter_mut().find(|other| {
+            other
+                .spec
+                .hostnames
+                .as_ref()
+                .is_some_and(|other_hostnames| {
+                    other_hostnames.len() + hostnames.len() <= MAX_ROUTE_HOSTNAMES
+                })
+                && without_hostnames(other) == without_hostnames(&route)
+        });
+        match target {
+            Some(other) => other
+                .spec
+                .hostnames
+                .get_or_insert_default()
+                .extend(hostnames),
+            None => merged.push(route),
+        }
+    }
+    merged
+}
+
+/// Move the default backend rule into consolidated routes whose hosts all fall back to it.
+/// The rule is appended last, so explicit rules of the hosts take precedence.
+/// Hosts of routes which can't have another rule keep the separate default backend route.
+fn merge_default_backend(routes: Vec<HTTPRoute>) -> Vec<HTTPRoute> {
+    let is_default = |route: &HTTPRoute| {
+        route.spec.rules.as_deref().is_some_and(
+            |rules| matches!(rules, [rule] if rule.name.as_deref() == Some(DEFAULT_BACKEND_RULE)),
+        )
+    };
+    let (mut defaults, mut routes): (Vec<_>, Vec<_>) = routes.into_iter().partition(is_default);
+    let Some(default_rule) = defaults
+        .first()
+        .and_then(|route| route.spec.rules.as_ref())
+        .and_then(|rules| rules.first().cloned())
+    else {
+        return routes;
+    };
+    // The default backend of hostless rules already catches requests to all hosts.
+    if defaults.iter().any(|route| route.spec.hostnames.is_none()) {
+        routes.extend(defaults);
+        return routes;
+    }
+    let mut fallback_hosts = defaults
+        .iter()
+        .flat_map(|route| route.spec.hostnames.iter().flatten().cloned())
+        .collect::<HashSet<_>>();
+    for route in &mut routes {
+        let (Some(hostnames), Some(rules)) = (&route.spec.hostnames, &mut route.spec.rules) else {
+            continue;
+        };
+        // Redirect routes don't forward requests.
+        if rules.len() >= MAX_ROUTE_RULES || rules.iter().any(|rule| rule.backend_refs.is_none()) {
+            continue;
+        }
+        if !hostnames.iter().all(|host| fallback_hosts.contains(host)) {
+            continue;
+        }
+        rules.push(default_rule.clone());
+        for host in hostnames {
+            fallback_hosts.remove(host);
+        }
+    }
+    for route in &mut defaults {
+        if let Some(hostnames) = &mut route.spec.hostnames {
+            hostnames.retain(|host| fallback_hosts.contains(host));
+        }
+    }
+    defaults.retain(|route| {
+        route
+            .spec
+            .hostnames
+            .as_ref()
+            .is_some_and(|hostnames| !hostnames.is_empty())
+    });
+    routes.extend(defaults);
+    routes
+}
+
+/// Remove routes with names of previous routes, returns names of removed routes.
+fn drop_duplicate_names<K: kube::Resource>(routes: &mut Vec<K>) -> Vec<String> {
+    let mut names = HashSet::new();
+    let mut duplicates = vec![];
+    routes.retain(|route| {
+        let name = route.meta().name.clone().unwrap_or_default();
+        if names.insert(name.clone()) {
+            return true;
+        }
+        duplicates.push(name);
+        false
+    });
+    duplicates
+}
+
+/// Routes generated from a single Ingress.
+#[derive(Default)]
+pub struct Translation {
+    pub http_routes: Vec<HTTPRoute>,
+    pub tcp_routes: Vec<TCPRoute>,
+    pub udp_routes: Vec<UDPRoute>,
+    pub tls_routes: Vec<TLSRoute>,
+    /// Grants created in gateway namespaces with `--create-reference-grants`.
+    pub reference_grants: Vec<ReferenceGrant>,
+    /// Policies for Services of the Ingress whose `backend-protocol` is HTTPS.
+    pub backend_tls_policies: Vec<BackendTLSPolicy>,
+    /// Envoy Gateway Backends of ExternalName Services
+    /// with `--external-name-backends envoy-backend`.
+    pub envoy_backends: Vec<EnvoyBackend>,
+    /// Names and namespaces of gateways routes are attached to.
+    pub gateways: Vec<(String, String)>,
+    /// Whether routes for some of the hosts couldn't be generated.
+    /// Existing routes are not pruned in this case, since they may still be valid.
+    pub failed: bool,
+    /// Reasons why some of the Ingress rules were not translated.
+    pub warnings: Vec<String>,
+}
+
+impl Translation {
+    fn skip_rule(&mut self, metrics: &Metrics, reason: SkipReason, message: String) {
+        metrics.record_skip(reason);
+        self.warn(message);
+    }
+
+    fn warn(&mut self, message: String) {
+        tracing::warn!("{message}");
+        self.warnings.push(message);
+    }
+
+    fn fail(&mut self, message: String) {
+        self.failed = true;
+        self.warn(message);
+    }
+
+    pub fn route_count(&self) -> usize {
+        self.http_routes.len()
+            + self.tcp_routes.len()
+            + self.udp_routes.len()
+            + self.tls_routes.len()
+    }
+}
+
+/// Returns the reason why the Ingress must not be translated, if any.
+pub async fn skip_reason(
+    ingress: &Ingress,
+    ctx: &ctx::Context,
+) -> I2GResult<Option<(SkipReason, String)>> {
+    let namespace = ingress.namespace().unwrap_or_default();
+    if !ctx.args.is_namespace_watched(&namespace) {
+        return Ok(Some((
+            SkipReason::NamespaceDisabled,
+            format!("namespace {namespace} is not watched"),
+        )));
+    }
+    if let Some(namespaces) = &ctx.namespaces {
+        namespaces
+            .wait_until_ready()
+            .await
+            .map_err(|err| anyhow::anyhow!("Namespace cache is unavailable: {err}"))?;
+        if namespaces.get(&ObjectRef::new(&namespace)).is_none() {
+            return Ok(Some((
+                SkipReason::NamespaceDisabled,
+                format!("namespace {namespace} is not enabled"),
+            )));
+        }
+    }
+
+    if !ctx.args.ingress_classes.is_empty() {
+        let class = utils::ingress_class(ingress);
+        if !class.is_some_and(|class| ctx.args.ingress_classes.contains(class)) {
+            return Ok(Some((
+                SkipReason::IngressClass,
+                format!(
+                    "ingress class {} is not translated",
+                    class.map(String::as_str).unwrap_or("<none>")
+                ),
+            )));
+        }
+    }
+
+    // Only translate if the annotation is present and true
+    // or if skip_by_default is false and
+    // the annotation is not present or equals to true
+    let skip_translation = ingress
+        .meta()
+        .annotations
+        .as_ref()
+        .and_then(|ann| ann.get(consts::TRANSLATE_INGRESS))
+        .map(|v| v.to_lowercase() != "true")
+        .unwrap_or(ctx.settings().skip_by_default);
+
+    if skip_translation {
+        return Ok(Some((
+            SkipReason::SkipAnnotation,
+            "translation is disabled by annotation or operator settings".to_string(),
+        )));
+    }
+
+    if canary::is_canary(ingress) {
+        return Ok(Some((
+            SkipReason::Canary,
+            "canary Ingress is merged into routes of the primary Ingress".to_string(),
+        )));
+    }
+    Ok(None)
+}
+
+/// How well the listener hostname matches the host.
+/// Exact matches are the best, then longer wildcard suffixes,
+/// listeners without a hostname match any host.
+fn listener_host_score(listener_hostname: Option<&str>, host: &str) -> Option<usize> {
+    let Some(hostname) = listener_hostname else {
+        return Some(0);
+    };
+    if hostname == host {
+        return Some(usize::MAX);
+    }
+    hostname
+        .strip_prefix('*')
+        .filter(|suffix| host.len() > suffix.len() && host.ends_with(suffix))
+        .map(str::len)
+}
+
+/// Pick the listener of the gateway with the protocol (and port, if set) best matching the host.
+/// Returns None with a warning if no listener or several listeners match equally well,
+/// routes are attached to all listeners of the gateway then.
+fn auto_section(
+    ctx: &ctx::Context,
+    gateways: &[(String, String)],
+    host: &str,
+    protocol: &str,
+    port: Option<i32>,
+    translation: &mut Translation,
+) -> Option<String> {
+    let store = ctx.gateways.as_ref()?;
+    let [(name, namespace)] = gateways else {
+        translation.warn(format!(
+            "Listener for host {host} is not selected automatically, since routes are attached to several gateways"
+        ));
+        return None;
+    };
+    let Some(gateway) = store.get(&ObjectRef::new(name).within(namespace)) else {
+        translation.warn(format!(
+            "Gateway {namespace}/{name} was not found, listener for host {host} is not selected"
+        ));
+        return None;
+    };
+    let mut best_score = None;
+    let mut best = vec![];
+    for listener in &gateway.spec.listeners {
+        if !listener.protocol.eq_ignore_ascii_case(protocol)
+            || port.is_some_and(|port| listener.port != port)
+        {
+            continue;
+        }
+        let Some(score) = listener_host_score(listener.hostname.as_deref(), host) else {
+            continue;
+        };
+        match best_score.cmp(&Some(score)) {
+            std::cmp::Ordering::Less => {
+                best_score = Some(score);
+                best = vec![listener.name.clone()];
+            }
+            std::cmp::Ordering::Equal => best.push(listener.name.clone()),
+            std::cmp::Ordering::Greater => {}
+        }
+    }
+    match best.as_slice() {
+        [section] => Some(section.clone()),
+        [] => {
+            translation.warn(format!(
+                "No {protocol} listener of gateway {namespace}/{name} matches host {host}"
+            ));
+            None
+        }
+        sections => {
+            translation.warn(format!(
+                "Several {protocol} listeners of gateway {namespace}/{name} match host {host} equally well ({}), routes are attached to all listeners",
+                sections.join(", ")
+            ));
+            None
+        }
+    }
+}
+
+/// Parent of a generated route: gateway name, namespace, section name and port.
+type RouteParent = (String, Option<String>, Option<String>, Option<i32>);
+
+/// Route kinds generated by the operator.
+trait GeneratedRoute: kube::Resource<DynamicType = ()> {
+    fn parents(&self) -> Vec<RouteParent>;
+}
+
+macro_rules! impl_generated_route {
+    ($($route:ident),*) => {
+        $(
+            impl GeneratedRoute for $route {
+                fn parents(&self) -> Vec<RouteParent> {
+                    self.spec
+                        .parent_refs
+                        .iter()
+                        .flatten()
+                        .map(|p| (p.name.clone(), p.namespace.clone(), p.section_name.clone(), p.port))
+                        .collect()
+                }
+            }
+        )*
+    };
+}
+
+impl_generated_route!(HTTPRoute, TCPRoute, UDPRoute, TLSRoute);
+
+/// Kind, name and parents of every generated route.
+fn route_parents(translation: &Translation) -> Vec<(String, String, Vec<RouteParent>)> {
+    fn parents_of<K: GeneratedRoute>(
+        routes: &[K],
+    ) -> impl Iterator<Item = (String, String, Vec<RouteParent>)> + '_ {
+        routes
+            .iter()
+            .map(|route| (K::kind(&()).to_string(), route.name_any(), route.parents()))
+    }
+    parents_of(&translation.http_routes)
+        .chain(parents_of(&translation.tcp_routes))
+        .chain(parents_of(&translation.udp_routes))
+        .chain(parents_of(&translation.tls_routes))
+        .collect()
+}
+
+/// Whether the namespace selector of a listener matches the namespace labels.
+fn namespace_selector_matches(
+    selector: &gateways::GatewayListenersAllowedRoutesNamespacesSelector,
+    labels: &BTreeMap<String, String>,
+) -> bool {
+    let labels_match = selector
+        .match_labels
+        .iter()
+        .flatten()
+        .all(|(key, value)| labels.get(key) == Some(value));
+    let expressions_match = selector.match_expressions.iter().flatten().all(|expr| {
+        let values = expr.values.as_deref().unwrap_or_default();
+        let value = labels.get(&expr.key);
+        match expr.operator.as_str() {
+            "In" => value.is_some_and(|value| values.contains(value)),
+            "NotIn" => value.is_none_or(|value| !values.contains(value)),
+            "Exists" => value.is_some(),
+            "DoesNotExist" => value.is_none(),
+            _ => false,
+        }
+    });
+    labels_match && expressions_match
+}
+
+/// Route kinds admitted by listeners of the protocol without `allowedRoutes.kinds`.
+fn default_route_kinds(protocol: &str) -> &'static [&'static str] {
+    match protocol.to_uppercase().as_str() {
+        "HTTP" | "HTTPS" => &["HTTPRoute", "GRPCRoute"],
+        "TLS" => &["TLSRoute"],
+        "TCP" => &["TCPRoute"],
+        "UDP" => &["UDPRoute"],
+        _ => &[],
+    }
+}
+
+/// Reason and description why the route can't be attached to the parent gateway.
+/// Only checked when gateways are cached, i.e. not in offline mode.
+async fn attachment_error(
+    ctx: &ctx::Context,
+    route_kind: &str,
+    route_namespace: &str,
+    parent: &RouteParent,
+) -> I2GResult<Option<(&'static str, String)>> {
+    let Some(store) = &ctx.gateways else {
+        return Ok(None);
+    };
+    let (name, namespace, section_name, port) = parent;
+    let namespace = namespace.as_deref().unwrap_or(route_namespace);
+    let Some(gateway) = store.get(&ObjectRef::new(name).within(namespace)) else {
+        return Ok(Some((
+            "gateway_not_found",
+            format!("Gateway {namespace}/{name} doesn't exist"),
+        )));
+    };
+    let listeners = gateway
+        .spec
+        .listeners
+        .iter()
+        .filter(|listener| {
+            section_name
+                .as_ref()
+                .is_none_or(|section| listener.name == *section)
+        })
+        .filter(|listener| port.is_none_or(|port| listener.port == port))
+        .collect::<Vec<_>>();
+    if listeners.is_empty() {
+        return Ok(Some((
+            "listener_not_found",
+            format!(
+                "Gateway {namespace}/{name} doesn't have a listener with section {} and port {}",
+                section_name.as_deref().unwrap_or("any"),
+                port.map_or("any".to_string(), |port| port.to_string())
+            ),
+        )));
+    }
+    let mut route_namespace_labels = None;
+    for listener in listeners {
+        let allowed = listener.allowed_routes.as_ref();
+        let kind_allowed = match allowed.and_then(|allowed| allowed.kinds.as_ref()) {
+            Some(kinds) if !kinds.is_empty() => kinds.iter().any(|kind| kind.kind == route_kind),
+            _ => default_route_kinds(&listener.protocol).contains(&route_kind),
+        };
+        if !kind_allowed {
+            continue;
+        }
+        let namespaces = allowed.and_then(|allowed| allowed.namespaces.as_ref());
+        let namespace_allowed = match namespaces.and_then(|namespaces| namespaces.from.as_ref()) {
+            Some(gateways::GatewayListenersAllowedRoutesNamespacesFrom::All) => true,
+            Some(gateways::GatewayListenersAllowedRoutesNamespacesFrom::Selector) => {
+                let Some(selector) = namespaces.and_then(|namespaces| namespaces.selector.as_ref())
+                else {
+                    continue;
+                };
+                if route_namespace_labels.is_none() {
+                    let namespace_object = Api::<Namespace>::all(ctx.client.clone())
+                        .get_opt(route_namespace)
+                        .await?;
+                    route_namespace_labels = Some(
+                        namespace_object
+                            .and_then(|namespace| namespace.metadata.labels)
+                            .unwrap_or_default(),
+                    );
+                }
+                namespace_selector_matches(selector, route_namespace_labels.as_ref().unwrap())
+            }
+            Some(gateways::GatewayListenersAllowedRoutesNamespacesFrom::Same) | None => {
+                namespace == route_namespace
+            }
+        };
+        if namespace_allowed {
+            return Ok(None);
+        }
+    }
+    Ok(Some((
+        "not_allowed",
+        format!(
+            "Listeners of gateway {namespace}/{name} don't allow {route_kind}s from namespace {route_namespace}"
+        ),
+    )))
+}
+
+/// Default gateways of the namespace from its `i2g-operator/default-gateway` annotation
+/// or the `namespaceGateways` section of the settings file, in the format of
+/// the gateway name annotation. Namespaces aren't looked up offline.
+async fn namespace_default_gateways(
+    ctx: &ctx::Context,
+    settings: &Settings,
+    namespace: &str,
+) -> I2GResult<Option<String>> {
+    if ctx.offline_ports.is_none() {
+        let cached = ctx
+            .namespaces
+            .as_ref()
+            .and_then(|store| store.get(&ObjectRef::new(namespace)));
+        let namespace_object = match cached {
+            Some(namespace_object) => Some(namespace_object),
+            None => Api::<Namespace>::all(ctx.client.clone())
+                .get_opt(namespace)
+                .await?
+                .map(Arc::new),
+        };
+        if let Some(raw) = namespace_object.as_ref().and_then(|namespace_object| {
+            namespace_object.annotations().get(consts::DEFAULT_GATEWAY)
+        }) {
+            return Ok(Some(raw.clone()));
+        }
+    }
+    Ok(settings.namespace_gateways.get(namespace).cloned())
+}
+
+/// Parse a comma-separated list of gateways from the gateway-name annotation.
+/// Each gateway is either `name` in the default namespace or `namespace/name`.
+fn parse_gateways(raw: &str, default_namespace: &str) -> I2GResult<Vec<(String, String)>> {
+    let mut gateways = vec![];
+    for item in raw
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+    {
+        let gateway = match item.split_once('/') {
+            Some((namespace, name)) if !namespace.is_empty() && !name.is_empty() => {
+                (name.to_string(), namespace.to_string())
+            }
+            Some(_) => {
+                return Err(I2GError::ParseError(format!(
+                    "Gateway '{item}' should be in format name or namespace/name"
+                )));
+            }
+            None => (item.to_string(), default_namespace.to_string()),
+        };
+        if !gateways.contains(&gateway) {
+            gateways.push(gateway);
+        }
+    }
+    if gateways.is_empty() {
+        return Err(I2GError::ParseError(format!(
+            "Annotation {} doesn't contain any gateways",
+            consts::GATEWAY_NAME
+        )));
+    }
+    Ok(gateways)
+}
+
+/// Names and namespaces of gateways matching the label selector, sorted.
+fn select_gateways(
+    ctx: &ctx::Context,
+    selector: &LabelSelector,
+) -> I2GResult<Vec<(String, String)>> {
+    let Some(store) = &ctx.gateways else {
+        return Err(anyhow::anyhow!("Gateway selectors require a cluster connection").into());
+    };
+    let mut gateways = store
+        .state()
+        .iter()
+        .filter(|gateway| selector.matches(gateway.labels()))
+        .map(|gateway| (gateway.name_any(), gateway.namespace().unwrap_or_default()))
+        .collect::<Vec<_>>();
+    if gateways.is_empty() {
+        return Err(anyhow::anyhow!("No gateways match selector {selector}").into());
+    }
+    gateways.sort_by(|a, b| (&a.1, &a.0).cmp(&(&b.1, &b.0)));
+    Ok(gateways)
+}
+
+/// Generate routes for the Ingress without applying them.
+pub async fn translate(ingress: &Ingress, ctx: Arc<ctx::Context>) -> I2GResult<Translation> {
+    let ingress_spec = ingress
+        .spec
+        .as_ref()
+        .ok_or(anyhow::anyhow!("Ingres doesn't have spec section"))?;
+    // An Ingress without rules sends requests to all hosts to its default backend,
+    // so a hostless rule without paths is translated instead.
+    let default_rules = ingress_spec
+        .default_backend
+        .iter()
+        .map(|_| IngressRule {
+            host: None,
+            http: Some(HTTPIngressRuleValue { paths: vec![] }),
+        })
+        .collect::<Vec<_>>();
+    let ingress_rules = match ingress_spec.rules.as_deref() {
+        Some(rules) if !rules.is_empty() => rules,
+        _ => default_rules.as_slice(),
+    };
+    let ingress_namespace = ingress
+        .namespace()
+        .ok_or_else(|| anyhow::anyhow!("Ingress doesn't have a namespace"))?;
+    let backend_namespace = ingress
+        .annotations()
+        .get(consts::BACKEND_NAMESPACE)
+        .map(|namespace| namespace.trim())
+        .filter(|namespace| !namespace.is_empty())
+        .unwrap_or(&ingress_namespace)
+        .to_string();
+    // Settings may be reloaded during translation, so they're read once.
+    let settings = ctx.settings();
+    let provider = ctx.provider(ingress);
+    let class_config = ctx.class_config(ingress);
+
+    let desired_section_name = ingress
+        .meta()
+        .annotations
+        .as_ref()
+        .and_then(|ann| ann.get(consts::DESIRED_SECTION))
+        .cloned()
+        .or_else(|| {
+            class_config
+                .as_ref()
+                .and_then(|config| config.spec.section_name.clone())
+        });
+
+    let gw_namespace = ingress
+        .meta()
+        .annotations
+        .as_ref()
+        .and_then(|annot| annot.get(consts::GATEWAY_NAMESPACE))
+        .unwrap_or(&settings.default_gateway_namespace);
+
+    let gateway_name = ingress.annotations().get(consts::GATEWAY_NAME);
+    let gateway_selector = ingress
+        .annotations()
+        .get(consts::GATEWAY_SELECTOR)
+        .map(|raw| raw.parse::<LabelSelector>())
+        .transpose()?;
+    // Gateways of the class take precedence over namespace defaults,
+    // and both take precedence over operator-wide ones.
+    // Class parameters take precedence over `--class-gateway-map`.
+    let class_parameters_gateway = class_config.as_ref().and_then(|config| {
+        let name = config.spec.gateway_name.as_ref()?;
+        Some(match &config.spec.gateway_namespace {
+            Some(namespace) => format!("{namespace}/{name}"),
+            None => name.clone(),
+        })
+    });
+    let class_gateway = class_parameters_gateway.or_else(|| {
+        utils::ingress_class(ingress).and_then(|class| {
+            ctx.args
+                .class_gateway_map
+                .iter()
+                .find(|mapping| mapping.class == *class)
+                .map(|mapping| mapping.gateway.clone())
+        })
+    });
+    let default_gateways = match (gateway_name, &gateway_selector, class_gateway) {
+        (None, None, Some(gateway)) => Some(gateway),
+        (None, None, None) => {
+            namespace_default_gateways(&ctx, &settings, &ingress_namespace).await?
+        }
+        _ => None,
+    };
+    let gateway_selector = gateway_selector.or_else(|| ctx.args.gateway_selector.clone());
+    let gateways = match (gateway_name.or(default_gateways.as_ref()), gateway_selector) {
+        (Some(raw), _) => parse_gateways(raw, gw_namespace)?,
+        (None, Some(selector)) => select_gateways(&ctx, &selector)?,
+        (None, None) if ctx.args.provision_gateways => vec![(
+            ctx.args.provisioned_gateway_name.clone(),
+            ctx.args
+                .provisioned_gateway_namespace
+                .clone()
+                .unwrap_or_else(|| ingress_namespace.clone()),
+        )],
+        (None, None) => {
+            let gw_name = settings.default_gateway_name.clone().ok_or_else(|| {
+                anyhow::anyhow!("Neither gateway name nor gateway selector is set")
+            })?;
+            vec![(gw_name, gw_namespace.clone())]
+        }
+    };
+
+    // In shadow mode all routes are attached to the shadow gateway.
+    // Its listeners are unknown, so routes are attached to all of them.
+    let shadow_gateway = ctx.args.shadow_prefix.as_ref().map(|_| {
+        (
+            ctx.args.shadow_gateway_name.clone().unwrap_or_default(),
+            ctx.args
+                .shadow_gateway_namespace
+                .clone()
+                .unwrap_or_else(|| settings.default_gateway_namespace.clone()),
+        )
+    });
+    let gateways = match &shadow_gateway {
+        Some(gateway) => vec![gateway.clone()],
+        None => gateways,
+    };
+
+    let header_matchers = ingress
+        .meta()
+        .annotations
+        .as_ref()
+        .map(|annotations| {
+            MatcherList::from_annotations(annotations, consts::HEADER_FILTERS_PREFIX)
+        })
+        .map(HeadersMatchersList);
+    let query_matchers = ingress
+        .meta()
+        .annotations
+        .as_ref()
+        .map(|annotations| MatcherList::from_annotations(annotations, consts::QUERY_FILTERS_PREFIX))
+        .map(QueryMatchersList);
+
+    let methods = ingress
+        .meta()
+        .annotations
+        .as_ref()
+        .map(|annotations| {
+            value_filters::methods_from_annotations(annotations, consts::METHOD_FILTERS_PREFIX)
+        })
+        .unwrap_or_default();
+
+    let mut rule_filters = ingress
+        .meta()
+        .annotations
+        .as_ref()
+        .and_then(|ann| ann.get(consts::EXTENSION_FILTERS))
+        .map(|raw| filters::parse_extension_refs(raw))
+        .transpose()?
+        .unwrap_or_default();
+    filters::add_removed_request_headers(&mut rule_filters, &ctx.args.strip_request_headers);
+
+    let mut translation = Translation::default();
+
+    let tls_hosts = tls_host_secrets(
+        ingress_spec.tls.as_deref().unwrap_or_default(),
+        ctx.args.tls_secret_conflicts,
+        &mut translation.warnings,
+    );
+
+    let timeouts = route_timeouts(
+        &ctx.args,
+        provider,
+        ingress.meta(),
+        &mut translation.warnings,
+    )?;
+
+    let use_default_gateways = ingress
+        .meta()
+        .annotations
+        .as_ref()
+        .and_then(|ann| ann.get(consts::TCP_USE_DEFAULT_GATEWAYS))
+        .map(|v| v.to_lowercase() == "true")
+        .unwrap_or(ctx.args.tcp_use_default_gateways);
+    // Protocol of non-HTTP rules.
+    let udp = match ingress.annotations().get(consts::PROTOCOL) {
+        None => false,
+        Some(protocol) => match protocol.trim().to_lowercase().as_str() {
+            "tcp" => false,
+            "udp" => true,
+            _ => {
+                return Err(I2GError::ParseError(format!(
+                    "Invalid {} '{protocol}': expected tcp or udp",
+                    consts::PROTOCOL
+                )));
+            }
+        },
+    };
+    let listener_port = ingress
+        .annotations()
+        .get(consts::LISTENER_PORT)
+        .map(|raw| {
+            raw.trim()
+                .parse::<i32>()
+                .ok()
+                .filter(|port| (1..=65535).contains(port))
+                .ok_or_else(|| {
+                    I2GError::ParseError(format!(
+                        "Invalid {} '{raw}': not a port number",
+                        consts::LISTENER_PORT
+                    ))
+                })
+        })
+        .transpose()?;
+    let has_explicit_parent = ingress.meta().annotations.as_ref().is_some_and(|ann| {
+        [
+            consts::GATEWAY_NAME,
+            consts::GATEWAY_NAMESPACE,
+            consts::DESIRED_SECTION,
+        ]
+        .iter()
+        .any(|key| ann.contains_key(*key))
+    });
+
+    let mut retry = ingress
+        .meta()
+        .annotations
+        .as_ref()
+        .and_then(|ann| ann.get(consts::RETRIES))
+        .map(|raw| filters::parse_retry(raw))
+        .transpose()?;
+    if retry.is_some() && !ctx.args.experimental {
+        translation.warn(
+            "Ignoring retries annotation. HTTPRoute retries require --experimental flag."
+                .to_string(),
+        );
+        retry = None;
+    }
+
+    let name_template = ingress
+        .meta()
+        .annotations
+        .as_ref()
+        .and_then(|ann| ann.get(consts::ROUTE_NAME_TEMPLATE))
+        .filter(|template| {
+            match templates::validate(template, templates::ROUTE_NAME_PLACEHOLDERS) {
+                Ok(()) => true,
+                Err(err) => {
+                    translation.warn(format!("Ignoring route name template: {err}"));
+                    false
+                }
+            }
+        })
+        .cloned();
+
+    let default_backend = ingress_spec.default_backend.as_ref();
+
+    let ssl_passthrough = ingress
+        .meta()
+        .annotations
+        .as_ref()
+        .and_then(|ann| provider.annotation(ann, consts::SSL_PASSTHROUGH))
+        .is_some_and(|v| v.to_lowercase() == "true");
+
+    let ssl_redirect = ingress.meta().annotations.as_ref().is_some_and(|ann| {
+        provider
+            .annotation(ann, consts::SSL_REDIRECT)
+            .is_some_and(|v| v.to_lowercase() == "true")
+    });
+
+    let redirect_port = ingress
+        .annotations()
+        .get(consts::REDIRECT_PORT)
+        .map(|raw| {
+            raw.trim()
+                .parse::<i32>()
+                .ok()
+                .filter(|port| (1..=65535).contains(port))
+                .ok_or_else(|| {
+                    I2GError::ParseError(format!(
+                        "Invalid {} '{raw}': port must be between 1 and 65535",
+                        consts::REDIRECT_PORT
+                    ))
+                })
+        })
+        .transpose()?;
+
+    let mut catch_all = CatchAll::default();
+
+    for rule in ingress_rules {
+        // Rules without a host are translated to routes matching all hostnames.
+        let host = match &rule.host {
+            Some(host) => host.as_str(),
+            None if ctx.args.skip_hostless_rules => {
+                translation.skip_rule(
+                    &ctx.metrics,
+                    SkipReason::NoHost,
+                    "Skipping rule without host".to_string(),
+                );
+                continue;
+            }
+            None => "",
+        };
+
+        // The section-name annotation overrides default sections of all route types.
+        // There's no default section of UDPRoutes, and the listener port
+        // replaces the default section of TCPRoutes.
+        let default_section =
+            if ssl_passthrough || (rule.http.is_none() && (udp || listener_port.is_some())) {
+                &None
+            } else if rule.http.is_none() {
+                &ctx.args.default_section_tcp
+            } else if tls_hosts.contains_key(host) {
+                &ctx.args.default_section_https
+            } else {
+                &ctx.args.default_section_http
+            };
+
+        let is_tls_host = !ssl_passthrough && rule.http.is_some() && tls_hosts.contains_key(host);
+        let rule_listener_port = listener_port.filter(|_| !ssl_passthrough && rule.http.is_none());
+        let protocol = if ssl_passthrough {
+            "TLS"
+        } else if rule.http.is_none() && udp {
+            "UDP"
+        } else if rule.http.is_none() {
+            "TCP"
+        } else if is_tls_host {
+            "HTTPS"
+        } else {
+            "HTTP"
+        };
+        let section_name = desired_section_name
+            .clone()
+            .or_else(|| default_section.clone())
+            .or_else(|| {
+                ctx.args
+                    .auto_select_sections
+                    .then(|| {
+                        auto_section(
+                            &ctx,
+                            &gateways,
+                            host,
+                            protocol,
+                            rule_listener_port,
+                            &mut translation,
+                        )
+                    })
+                    .flatten()
+            })
+            .filter(|_| shadow_gateway.is_none());
+        // Without a section, TLS hosts are attached to HTTPS listeners by port.
+        // TCP and UDP rules are attached by the listener port annotation.
+        let parent_port = ctx
+            .args
+            .https_listener_port
+            .filter(|_| is_tls_host && section_name.is_none())
+            .or(rule_listener_port)
+            .filter(|_| shadow_gateway.is_none());
+
+        let canaries = match (&ctx.ingresses.get(), &rule.http) {
+            (Some(ingresses), Some(_)) if !ssl_passthrough => {
+                canary::canaries_for_host(ingresses, ingress, host, &mut translation.warnings)
+            }
+            _ => vec![],
+        };
+
+        let route_info = RouteInputInfo {
+            ingress_name: ingress.name_any(),
+            header_matchers: header_matchers.clone(),
+            query_matchers: query_matchers.clone(),
+            methods: methods.clone(),
+            gateways: gateways.clone(),
+            ingress_meta: ingress.meta(),
+            hostname: host.to_string(),
+            ingress_namespace: ingress_namespace.clone(),
+            backend_namespace: backend_namespace.clone(),
+            section_name,
+            parent_port,
+            filters: rule_filters.clone(),
+            timeouts: timeouts.clone(),
+            use_default_gateways: use_default_gateways && shadow_gateway.is_none(),
+            canaries,
+            retry: retry.clone(),
+            name_template: name_template.clone(),
+        };
+
+        if ssl_passthrough && let Some(http) = &rule.http {
+            if !ctx.args.experimental {
+                translation.skip_rule(&ctx.metrics, SkipReason::ExperimentalDisabled, format!(
+                    "Skipping ssl-passthrough rule for host {host}. In order to migrate it to TLSRoute, please add --experimental flag to i2g-operator."
+                ));
+                continue;
+            }
+            let mut route =
+                match create_tls_route(ctx.clone(), route_info, http, &mut translation.warnings)
+                    .await
+                {
+                    Ok(route) => route,
+                    Err(err) => {
+                        translation
+                            .fail(format!("Failed to create TLSRoute for host {host}: {err}"));
+                        continue;
+                    }
+                };
+            finish_route(&ctx, &mut route, ingress, host)?;
+            translation.tls_routes.push(route);
+        } else if let Some(http) = &rule.http {
+            if default_backend.is_some() && !http.paths.iter().any(is_root_path) {
+                catch_all.add(&route_info);
+            }
+            // Rules without paths only route to the default backend.
+            if http.paths.is_empty() {
+                continue;
+            }
+            let redirect = match (ssl_redirect, is_tls_host) {
+                (true, true) => Some(create_redirect_route(&ctx, &route_info, redirect_port)),
+                (true, false) => {
+                    translation.warn(format!(
+                        "Ignoring ssl-redirect for host {host} which isn't listed in the TLS section"
+                    ));
+                    None
+                }
+                _ => None,
+            };
+            let routes = match create_http_routes(
+                ctx.clone(),
+                route_info,
+                http,
+                provider,
+                &mut translation.warnings,
+            )
+            .await
+            {
+                Ok(routes) => routes,
+                Err(err) => {
+                    translation.fail(format!("Failed to create HTTPRoute for host {host}: {err}"));
+                    continue;
+                }
+            };
+            for mut route in routes.into_iter().chain(redirect) {
+                finish_route(&ctx, &mut route, ingress, host)?;
+                translation.http_routes.push(route);
+            }
+        } else {
+            if !ctx.args.experimental {
+                translation.skip_rule(&ctx.metrics, SkipReason::ExperimentalDisabled, format!(
+                    "Skipping non-http rule for host {host}. In order to migrate it to {protocol}Route, please add --experimental flag to i2g-operator."
+                ));
+                continue;
+            }
+            if use_default_gateways && has_explicit_parent && shadow_gateway.is_none() {
+                translation.skip_rule(
+                    &ctx.metrics,
+                    SkipReason::ConflictingParent,
+                    format!(
+                        "Skipping non-http rule for host {host}: default gateways can't be used together with gateway or section annotations"
+                    ),
+                );
+                continue;
+            }
+            let tcp_backends = ingress
+                .meta()
+                .annotations
+                .as_ref()
+                .and_then(|ann| ann.get(consts::TCP_BACKENDS));
+            let backends = if let Some(raw_backends) = tcp_backends {
+                match parse_backend_list(raw_backends) {
+                    Ok(backends) => backends,
+                    Err(err) => {
+                        translation.skip_rule(
+                            &ctx.metrics,
+                            SkipReason::InvalidBackends,
+                            format!("Skipping rule with invalid {protocol} backends: {err}"),
+                        );
+                        continue;
+                    }
+                }
+            } else {
+                // In case if rule.http is None
+                let Some(backend) = default_backend else {
+                    translation.skip_rule(
+                        &ctx.metrics,
+                        SkipReason::NoDefaultBackend,
+                        "Skipping non-HTTP Ingress rule without default backend".to_string(),
+                    );
+                    continue;
+                };
+                let Some(backend_svc) = &backend.service else {
+                    translation.skip_rule(
+                        &ctx.metrics,
+                        SkipReason::NoService,
+                        "defaultBackend doesn't have a service, skipping.".to_string(),
+                    );
+                    continue;
+                };
+                let Some(svc_port) = &backend_svc.port else {
+                    translation.skip_rule(
+                        &ctx.metrics,
+                        SkipReason::NoServicePort,
+                        "Skipping backend without service port".to_string(),
+                    );
+                    continue;
+                };
+                vec![BackendSpec {
+                    name: backend_svc.name.clone(),
+                    port: svc_port.clone(),
+                    weight: None,
+                }]
+            };
+
+            if udp {
+                let mut route =
+                    match create_l4_route::<UDPRoute>(ctx.clone(), route_info, &backends).await {
+                        Ok(route) => route,
+                        Err(err) => {
+                            translation
+                                .fail(format!("Failed to create UDPRoute for host {host}: {err}"));
+                            continue;
+                        }
+                    };
+                finish_route(&ctx, &mut route, ingress, host)?;
+                translation.udp_routes.push(route);
+                continue;
+            }
+            let mut route = match create_l4_route::<TCPRoute>(ctx.clone(), route_info, &backends)
+                .await
+            {
+                Ok(route) => route,
+                Err(err) => {
+                    translation.fail(format!("Failed to create TCPRoute for host {host}: {err}"));
+                    continue;
+                }
+            };
+
+            finish_route(&ctx, &mut route, ingress, host)?;
+            translation.tcp_routes.push(route);
+        }
+    }
+
+    if let Some(backend) = default_backend {
+        match create_default_backend_routes(&ctx, &catch_all, backend, &mut translation.warnings)
+            .await
+        {
+            Ok(routes) => {
+                for mut route in routes {
+                    finish_route(&ctx, &mut route, ingress, "")?;
+                    translation.http_routes.push(route);
+                }
+            }
+            Err(err) => translation.fail(format!(
+                "Failed to create HTTPRoute for the default backend: {err}"
+            )),
+        }
+    }
+
+    // Redirect rules don't have backends, controller annotations only apply to forwarded requests.
+    let rules = translation
+        .http_routes
+        .iter_mut()
+        .flat_map(|route| route.spec.rules.iter_mut().flatten())
+        .filter(|rule| rule.backend_refs.is_some())
+        .collect::<Vec<_>>();
+    if let Err(err) = provider.apply(
+        ingress,
+        rules,
+        ctx.args.experimental,
+        &mut translation.warnings,
+    ) {
+        translation.fail(format!("Failed to translate controller annotations: {err}"));
+    }
+
+    let consolidate = ingress
+        .annotations()
+        .get(consts::CONSOLIDATE_HOSTS)
+        .is_some_and(|v| v.to_lowercase() == "true");
+    if consolidate {
+        translation.http_routes = merge_default_backend(consolidate_hosts(std::mem::take(
+            &mut translation.http_routes,
+        )));
+    }
+
+    // Templates without `{index}` or `{kind}` may generate the same name for several routes.
+    let duplicates = drop_duplicate_names(&mut translation.http_routes)
+        .into_iter()
+        .chain(drop_duplicate_names(&mut translation.tcp_routes))
+        .chain(drop_duplicate_names(&mut translation.udp_routes))
+        .chain(drop_duplicate_names(&mut translation.tls_routes))
+        .collect::<Vec<_>>();
+    for name in duplicates {
+        translation.fail(format!(
+            "Skipping route {name}: another route with the same name was generated"
+        ));
+    }
+
+    // Routes are still applied, since the gateway may be fixed later.
+    for (kind, name, parents) in route_parents(&translation) {
+        for parent in &parents {
+            if let Some((reason, message)) =
+                attachment_error(&ctx, &kind, &ingress_namespace, parent).await?
+            {
+                ctx.metrics
+                    .unattachable_routes
+                    .with_label_values(&[reason])
+                    .inc();
+                translation.warn(format!("{kind} {name} can't be attached: {message}"));
+            }
+        }
+    }
+
+    if translation.route_count() > 0 {
+        translation.gateways = gateways.clone();
+    }
+
+    if !translation.http_routes.is_empty() && has_tls_backends(provider, ingress, &mut translation)
+    {
+        let spec = ingress.spec.as_ref();
+        let services = spec
+            .into_iter()
+            .flat_map(|spec| spec.rules.iter().flatten())
+            .filter_map(|rule| rule.http.as_ref())
+            .flat_map(|http| &http.paths)
+            .map(|path| &path.backend)
+            .chain(spec.and_then(|spec| spec.default_backend.as_ref()))
+            .filter_map(|backend| backend.service.as_ref())
+            .map(|service| service.name.as_str())
+            .collect::<std::collections::BTreeSet<_>>();
+        // Policies are shared by Ingresses, the operator merges owners when applying them.
+        translation.backend_tls_policies = services
+            .into_iter()
+            .map(|service| {
+                let mut policy = create_backend_tls_policy(ingress, service, &backend_namespace);
+                policy
+                    .meta_mut()
+                    .set_shared_owners(&[utils::shared_owner(ingress)].into());
+                policy
+            })
+            .collect();
+    }
+
+    if backend_namespace != ingress_namespace {
+        for backend_ref in backend_refs_mut(&mut translation) {
+            *backend_ref.namespace = Some(backend_namespace.clone());
+        }
+    }
+    translate_external_name_backends(&ctx, &backend_namespace, &mut translation).await?;
+    // Backends are shared by Ingresses, the operator merges owners when applying them.
+    for backend in &mut translation.envoy_backends {
+        backend
+            .meta_mut()
+            .set_shared_owners(&[utils::shared_owner(ingress)].into());
+    }
+
+    if ctx.args.create_reference_grants && translation.route_count() > 0 {
+        for (gw_name, gw_namespace) in &gateways {
+            if *gw_namespace != ingress_namespace {
+                translation.reference_grants.push(create_reference_grant(
+                    &ctx,
+                    &ingress_namespace,
+                    gw_name,
+                    gw_namespace,
+                ));
+            }
+        }
+        if backend_namespace != ingress_namespace {
+            let grant =
+                create_backend_reference_grant(ingress, &backend_namespace, &mut translation);
+            translation.reference_grants.push(grant);
+        }
+        // Grants are shared by Ingresses, the operator merges owners when applying them.
+        for grant in &mut translation.reference_grants {
+            grant
+                .meta_mut()
+                .set_shared_owners(&[utils::shared_owner(ingress)].into());
+        }
+    }
+
+    Ok(translation)
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use gateway_api::apis::experimental::httproutes::HTTPRouteRulesFiltersType;
+    use k8s_openapi::api::networking::v1::HTTPIngressPath;
+    use rstest::rstest;
+
+    use super::*;
+
+    /// Offline context with the default gateway `gw` and extra arguments.
+    fn offline_ctx(args: &[&str]) -> ctx::Context {
+        let mut argv = vec!["i2g-operator", "--default-gateway-name", "gw"];
+        argv.extend(args);
+        ctx::Context::offline(args::I2GArgs::parse_from(argv), vec![]).unwrap()
+    }
+
+    /// Translate the Ingress offline, see [`offline_ctx`].
+    async fn translate_yaml(args: &[&str], ingress: &str) -> Translation {
+        let ingress: Ingress = serde_yaml::from_str(ingress).unwrap();
+        translate(&ingress, Arc::new(offline_ctx(args)))
+            .await
+            .unwrap()
+    }
+
+    fn rules(route: &HTTPRoute) -> &[HTTPRouteRules] {
+        route.spec.rules.as_deref().unwrap_or_default()
+    }
+
+    const SIMPLE_INGRESS: &str = r#"
+apiVersion: networking.k8s.io/v1
+kind: Ingress
+metadata: {name: web, namespace: ns}
+spec:
+  rules:
+  - host: "*.example.com"
+    http:
+      paths:
+      - path: /
+        pathType: Prefix
+        backend: {service: {name: web, port: {number: 80}}}
+"#;
+
+    #[rstest]
+    #[case("example.com", HTTPRouteRulesMatchesHeadersType::Exact, "example.com")]
+    #[case(
+        "*.example.com",
+        HTTPRouteRulesMatchesHeadersType::RegularExpression,
+        "^[^.]+\\.example\\.com$"
+    )]
+    fn host_header(
+        #[case] hostname: &str,
+        #[case] match_type: HTTPRouteRulesMatchesHeadersType,
+        #[case] value: &str,
+    ) {
+        let header = host_header_match(hostname);
+        assert_eq!(header.name, "Host");
+        assert_eq!(header.r#type, Some(match_type));
+        assert_eq!(header.value, value);
+    }
+
+    #[tokio::test]
+    async fn host_as_header_match() {
+        let translation = translate_yaml(&["--host-as-header-match"], SIMPLE_INGRESS).await;
+        let [route] = translation.http_routes.as_slice() else {
+            panic!("expected a single route");
+        };
+        assert_eq!(route.spec.hostnames, None);
+        assert!(!rules(route).is_empty());
+        for rule in rules(route) {
+            for matches in rule.matches.iter().flatten() {
+                assert_eq!(
+                    matches.headers.as_deref(),
+                    Some([host_header_match("*.example.com")].as_slice())
+                );
+            }
+        }
+
+        let translation = translate_yaml(&[], SIMPLE_INGRESS).await;
+        let route = &translation.http_routes[0];
+        assert_eq!(
+            route.spec.hostnames,
+            Some(vec!["*.example.com".to_string()])
+        );
+    }
+
+    #[tokio::test]
+    async fn path_type_override() {
+        let ingress = r#"
+apiVersion: networking.k8s.io/v1
+kind: Ingress
+metadata:
+  name: web
+  namespace: ns
+  annotations: {i2g-operator/path-type: "/exact=Exact,/re/.*=RegularExpression"}
+spec:
+  rules:
+  - host: example.com
+    http:
+      paths:
+      - path: /exact
+        pathType: Prefix
+        backend: {service: {name: web, port: {number: 80}}}
+      - path: /re/.*
+        pathType: ImplementationSpecific
+        backend: {service: {name: web, port: {number: 80}}}
+      - path: /prefix
+        pathType: Prefix
+        backend: {service: {name: web, port: {number: 80}}}
+"#;
+        let translation = translate_yaml(&[], ingress).await;
+        let mut path_types = translation
+            .http_routes
+            .iter()
+            .flat_map(rules)
+            .flat_map(|rule| rule.matches.iter().flatten())
+            .filter_map(|matches| matches.path.as_ref())
+            .map(|path| (path.value.clone().unwrap(), path.r#type.clone().unwrap()))
+            .collect::<Vec<_>>();
+        path_types.sort_by(|(path, _), (path2, _)| path.cmp(path2));
+        assert_eq!(
+            path_types,
+            vec![
+                ("/exact".to_string(), HTTPRouteRulesMatchesPathType::Exact),
+                (
+                    "/prefix".to_string(),
+                    HTTPRouteRulesMatchesPathType::PathPrefix
+                ),
+                (
+                    "/re/.*".to_string(),
+                    HTTPRouteRulesMatchesPathType::RegularExpression
+                ),
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case(&[], None, None, None)]
+    #[case(
+        &["--default-request-timeout", "30s", "--default-backend-request-timeout", "10s"],
+        None,
+        Some("30s"),
+        Some("10s")
+    )]
+    #[case(
+        &["--default-request-timeout", "30s", "--default-backend-request-timeout", "10s"],
+        Some("5s"),
+        Some("5s"),
+        None
+    )]
+    #[case(&["--default-backend-request-timeout", "10s"], None, None, Some("10s"))]
+    #[tokio::test]
+    async fn default_timeouts(
+        #[case] args: &[&str],
+        #[case] annotation: Option<&str>,
+        #[case] request: Option<&str>,
+        #[case] backend_request: Option<&str>,
+    ) {
+        let mut ingress = serde_yaml::from_str::<Ingress>(SIMPLE_INGRESS).unwrap();
+        if let Some(timeout) = annotation {
+            ingress
+                .annotations_mut()
+                .insert(consts::REQUEST_TIMEOUT.to_string(), timeout.to_string());
+        }
+        let ingress = serde_yaml::to_string(&ingress).unwrap();
+        let translation = translate_yaml(args, &ingress).await;
+        let timeouts = rules(&translation.http_routes[0])[0].timeouts.clone();
+        let parse =
+            |raw: Option<&str>| raw.map(|raw| raw.parse::<gateway_api::Duration>().unwrap());
+        assert_eq!(
+            timeouts
+                .as_ref()
+                .and_then(|timeouts| timeouts.request.as_deref())
+                .map(|raw| raw.parse::<gateway_api::Duration>().unwrap()),
+            parse(request)
+        );
+        assert_eq!(
+            timeouts
+                .as_ref()
+                .and_then(|timeouts| timeouts.backend_request.as_deref())
+                .map(|raw| raw.parse::<gateway_api::Duration>().unwrap()),
+            parse(backend_request)
+        );
+        // Dropping the backend timeout is reported.
+        assert_eq!(
+            translation.warnings.len(),
+            usize::from(annotation.is_some())
+        );
+    }
+
+    #[tokio::test]
+    async fn namespace_label_selector() {
+        let mut ctx = offline_ctx(&[]);
+        ctx.namespaces = Some(store(&["{metadata: {name: enabled}}"]));
+
+        let mut ingress = serde_yaml::from_str::<Ingress>(SIMPLE_INGRESS).unwrap();
+        let reason = skip_reason(&ingress, &ctx).await.unwrap();
+        assert_eq!(
+            reason.map(|(reason, _)| reason),
+            Some(SkipReason::NamespaceDisabled)
+        );
+
+        ingress.metadata.namespace = Some("enabled".to_string());
+        assert!(skip_reason(&ingress, &ctx).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn route_annotation_templates() {
+        let translation = translate_yaml(
+            &[
+                "--route-annotation",
+                "example.com/source={namespace}/{ingress}:{kind}",
+            ],
+            SIMPLE_INGRESS,
+        )
+        .await;
+        let route = &translation.http_routes[0];
+        assert_eq!(
+            route
+                .annotations()
+                .get("example.com/source")
+                .map(String::as_str),
+            Some("ns/web:HTTPRoute")
+        );
+    }
+
+    const TCP_INGRESS: &str = r#"
+apiVersion: networking.k8s.io/v1
+kind: Ingress
+metadata: {name: db, namespace: ns}
+spec:
+  defaultBackend: {service: {name: db, port: {number: 5432}}}
+  rules:
+  - host: db.example.com
+"#;
+
+    #[rstest]
+    #[case(&["--experimental"], None, false)]
+    #[case(&["--experimental", "--tcp-use-default-gateways"], None, true)]
+    #[case(&["--experimental", "--tcp-use-default-gateways"], Some("false"), false)]
+    #[case(&["--experimental"], Some("true"), true)]
+    #[tokio::test]
+    async fn tcp_default_gateways(
+        #[case] args: &[&str],
+        #[case] annotation: Option<&str>,
+        #[case] use_default: bool,
+    ) {
+        let mut ingress = serde_yaml::from_str::<Ingress>(TCP_INGRESS).unwrap();
+        if let Some(value) = annotation {
+            ingress.annotations_mut().insert(
+                consts::TCP_USE_DEFAULT_GATEWAYS.to_string(),
+                value.to_string(),
+            );
+        }
+        let translation = translate_yaml(args, &serde_yaml::to_string(&ingress).unwrap()).await;
+        let [route] = translation.tcp_routes.as_slice() else {
+            panic!("expected a single TCPRoute");
+        };
+        if use_default {
+            assert_eq!(
+                route.spec.use_default_gateways,
+                Some(TCPRouteUseDefaultGateways::All)
+            );
+            assert_eq!(route.spec.parent_refs, None);
+        } else {
+            assert_eq!(route.spec.use_default_gateways, None);
+            let parents = route.spec.parent_refs.as_deref().unwrap_or_default();
+            assert_eq!(parents.len(), 1);
+            assert_eq!(parents[0].name, "gw");
+        }
+    }
+
+    #[tokio::test]
+    async fn tcp_and_udp_routes_match() {
+        let tcp = translate_yaml(&["--experimental"], TCP_INGRESS).await;
+        let mut ingress = serde_yaml::from_str::<Ingress>(TCP_INGRESS).unwrap();
+        ingress
+            .annotations_mut()
+            .insert(consts::PROTOCOL.to_string(), "udp".to_string());
+        let udp = translate_yaml(
+            &["--experimental"],
+            &serde_yaml::to_string(&ingress).unwrap(),
+        )
+        .await;
+        let (tcp_parents, udp_parents) = (route_parents(&tcp), route_parents(&udp));
+        let [(tcp_kind, tcp_name, tcp_refs)] = tcp_parents.as_slice() else {
+            panic!("expected a single TCPRoute: {tcp_parents:?}");
+        };
+        let [(udp_kind, udp_name, udp_refs)] = udp_parents.as_slice() else {
+            panic!("expected a single UDPRoute: {udp_parents:?}");
+        };
+        assert_eq!(
+            (tcp_kind.as_str(), udp_kind.as_str()),
+            ("TCPRoute", "UDPRoute")
+        );
+        assert_eq!(udp_name, &tcp_name.replace("tcp", "udp"));
+        assert_eq!(udp_refs, tcp_refs);
+        let tcp_backends = &tcp.tcp_routes[0].spec.rules[0].backend_refs;
+        let udp_backends = &udp.udp_routes[0].spec.rules[0].backend_refs;
+        assert_eq!(
+            (&udp_backends[0].name, udp_backends[0].port),
+            (&tcp_backends[0].name, tcp_backends[0].port)
+        );
+    }
+
+    fn backend_names(translation: &Translation) -> Vec<(String, Option<i32>)> {
+        translation
+            .http_routes
+            .iter()
+            .flat_map(rules)
+            .flat_map(|rule| rule.backend_refs.iter().flatten())
+            .map(|backend| (backend.name.clone(), backend.port))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn maintenance_mode() {
+        let mut ingress = serde_yaml::from_str::<Ingress>(SIMPLE_INGRESS).unwrap();
+        ingress
+            .annotations_mut()
+            .insert(consts::MAINTENANCE.to_string(), "true".to_string());
+        let translation = translate_yaml(&[], &serde_yaml::to_string(&ingress).unwrap()).await;
+        assert!(translation.failed);
+        assert!(translation.http_routes.is_empty());
+
+        ingress.annotations_mut().insert(
+            consts::MAINTENANCE_BACKEND.to_string(),
+            "maintenance:8080".to_string(),
+        );
+        let translation = translate_yaml(&[], &serde_yaml::to_string(&ingress).unwrap()).await;
+        assert_eq!(
+            backend_names(&translation),
+            vec![("maintenance".to_string(), Some(8080))]
+        );
+
+        ingress
+            .annotations_mut()
+            .insert(consts::MAINTENANCE.to_string(), "false".to_string());
+        let translation = translate_yaml(&[], &serde_yaml::to_string(&ingress).unwrap()).await;
+        assert_eq!(
+            backend_names(&translation),
+            vec![("web".to_string(), Some(80))]
+        );
+    }
+
+    fn store<K>(objects: &[&str]) -> kube::runtime::reflector::Store<K>
+    where
+        K: Resource<DynamicType = ()> + Clone + serde::de::DeserializeOwned + 'static,
+    {
+        let (reader, mut writer) = kube::runtime::reflector::store::<K>();
+        writer.apply_watcher_event(&kube::runtime::watcher::Event::Init);
+        for object in objects {
+            let object = serde_yaml::from_str::<K>(object).unwrap();
+            writer.apply_watcher_event(&kube::runtime::watcher::Event::InitApply(object));
+        }
+        writer.apply_watcher_event(&kube::runtime::watcher::Event::InitDone);
+        reader
+    }
+
+    /// Context resolving named ports from cached Services instead of the static port map.
+    fn ctx_with_services(args: &[&str], services: &[&str]) -> ctx::Context {
+        let mut ctx = offline_ctx(args);
+        ctx.offline_ports = None;
+        ctx.services = Some(store(services));
+        ctx.namespaces = Some(store(&["{metadata: {name: ns}}"]));
+        ctx
+    }
+
+    #[rstest]
+    #[case(&[], 1, false)]
+    #[case(&["--strict-service-ports"], 0, true)]
+    #[tokio::test]
+    async fn service_without_ports(
+        #[case] args: &[&str],
+        #[case] rule_count: usize,
+        #[case] failed: bool,
+    ) {
+        let ingress = r#"
+apiVersion: networking.k8s.io/v1
+kind: Ingress
+metadata: {name: web, namespace: ns}
+spec:
+  rules:
+  - host: example.com
+    http:
+      paths:
+      - path: /empty
+        pathType: Prefix
+        backend: {service: {name: empty, port: {name: http}}}
+      - path: /api
+        pathType: Prefix
+        backend: {service: {name: api, port: {name: http}}}
+"#;
+        let ctx = ctx_with_services(
+            args,
+            &[
+                "{metadata: {name: empty, namespace: ns}, spec: {ports: []}}",
+                "{metadata: {name: api, namespace: ns}, spec: {ports: [{name: http, port: 8080}]}}",
+            ],
+        );
+        let ingress = serde_yaml::from_str::<Ingress>(ingress).unwrap();
+        let ctx = Arc::new(ctx);
+        let translation = translate(&ingress, ctx.clone()).await.unwrap();
+        assert_eq!(
+            translation.http_routes.iter().flat_map(rules).count(),
+            rule_count
+        );
+        assert_eq!(translation.failed, failed);
+        if rule_count > 0 {
+            assert_eq!(
+                backend_names(&translation),
+                vec![("api".to_string(), Some(8080))]
+            );
+        }
+        assert_eq!(
+            ctx.metrics
+                .skipped
+                .with_label_values(&[SkipReason::ServiceWithoutPorts.as_str()])
+                .get(),
+            1
+        );
+    }
+
+    const SECTION_ARGS: &[&str] = &[
+        "--experimental",
+        "--default-section-http",
+        "web",
+        "--default-section-https",
+        "websecure",
+        "--default-section-tcp",
+        "tcp",
+    ];
+
+    /// Section names of parents of HTTPRoutes by their first hostname.
+    fn http_sections(
+        translation: &Translation,
+    ) -> std::collections::BTreeMap<String, Vec<Option<String>>> {
+        translation
+            .http_routes
+            .iter()
+            .map(|route| {
+                let host = route
+                    .spec
+                    .hostnames
+                    .as_ref()
+                    .and_then(|hostnames| hostnames.first().cloned())
+                    .unwrap_or_default();
+                let sections = route
+                    .spec
+                    .parent_refs
+                    .iter()
+                    .flatten()
+                    .map(|parent| parent.section_name.clone())
+                    .collect();
+                (host, sections)
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn default_sections() {
+        let ingress = r#"
+apiVersion: networking.k8s.io/v1
+kind: Ingress
+metadata: {name: web, namespace: ns}
+spec:
+  tls:
+  - hosts: [secure.example.com]
+    secretName: cert
+  rules:
+  - host: plain.example.com
+    http:
+      paths:
+      - path: /
+        pathType: Prefix
+        backend: {service: {name: web, port: {number: 80}}}
+  - host: secure.example.com
+    http:
+      paths:
+      - path: /
+        pathType: Prefix
+        backend: {service: {name: web, port: {number: 80}}}
+"#;
+        let translation = translate_yaml(SECTION_ARGS, ingress).await;
+        let sections = http_sections(&translation);
+        assert_eq!(
+            sections.get("plain.example.com"),
+            Some(&vec![Some("web".to_string())])
+        );
+        assert_eq!(
+            sections.get("secure.example.com"),
+            Some(&vec![Some("websecure".to_string())])
+        );
+
+        let translation = translate_yaml(SECTION_ARGS, TCP_INGRESS).await;
+        let parents = translation.tcp_routes[0].spec.parent_refs.clone().unwrap();
+        assert_eq!(parents[0].section_name.as_deref(), Some("tcp"));
+
+        // The annotation overrides default sections.
+        let mut ingress = serde_yaml::from_str::<Ingress>(ingress).unwrap();
+        ingress
+            .annotations_mut()
+            .insert(consts::DESIRED_SECTION.to_string(), "custom".to_string());
+        let translation =
+            translate_yaml(SECTION_ARGS, &serde_yaml::to_string(&ingress).unwrap()).await;
+        for sections in http_sections(&translation).values() {
+            assert_eq!(sections, &vec![Some("custom".to_string())]);
+        }
+    }
+
+    #[tokio::test]
+    async fn shadow_apply() {
+        let translation = translate_yaml(
+            &[
+                "--shadow-prefix",
+                "shadow-",
+                "--shadow-gateway-name",
+                "shadow-gw",
+                "--shadow-gateway-namespace",
+                "infra",
+            ],
+            SIMPLE_INGRESS,
+        )
+        .await;
+        let plain = translate_yaml(&[], SIMPLE_INGRESS).await;
+        let [route] = translation.http_routes.as_slice() else {
+            panic!("expected a single route");
+        };
+        assert_eq!(
+            route.name_any(),
+            format!("shadow-{}", plain.http_routes[0].name_any())
+        );
+        assert_eq!(
+            route.labels().get(consts::SHADOW_LABEL).map(String::as_str),
+            Some("true")
+        );
+        let parents = route.spec.parent_refs.as_deref().unwrap_or_default();
+        assert_eq!(parents.len(), 1);
+        assert_eq!(parents[0].name, "shadow-gw");
+        assert_eq!(parents[0].namespace.as_deref(), Some("infra"));
+        assert_eq!(
+            translation.gateways,
+            vec![("shadow-gw".to_string(), "infra".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn weighted_tcp_backends() {
+        let mut ingress = serde_yaml::from_str::<Ingress>(TCP_INGRESS).unwrap();
+        ingress.annotations_mut().insert(
+            consts::TCP_BACKENDS.to_string(),
+            "primary:5432@90, replica:5433@10".to_string(),
+        );
+        let translation = translate_yaml(
+            &["--experimental"],
+            &serde_yaml::to_string(&ingress).unwrap(),
+        )
+        .await;
+        let [route] = translation.tcp_routes.as_slice() else {
+            panic!("expected a single TCPRoute");
+        };
+        let backends = route.spec.rules[0]
+            .backend_refs
+            .iter()
+            .map(|backend| (backend.name.as_str(), backend.port, backend.weight))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            backends,
+            vec![
+                ("primary", Some(5432), Some(90)),
+                ("replica", Some(5433), Some(10))
+            ]
+        );
+
+        ingress
+            .annotations_mut()
+            .insert(consts::TCP_BACKENDS.to_string(), "primary:0".to_string());
+        let translation = translate_yaml(
+            &["--experimental"],
+            &serde_yaml::to_string(&ingress).unwrap(),
+        )
+        .await;
+        assert!(translation.tcp_routes.is_empty());
+    }
+
+    #[rstest]
+    #[case("{defaultBackend: {service: {name: web, port: {number: 80}}}}")]
+    #[case("{defaultBackend: {service: {name: web, port: {number: 80}}}, rules: []}")]
+    #[tokio::test]
+    async fn default_backend_without_rules(#[case] spec: &str) {
+        let ingress = format!(
+            "{{apiVersion: networking.k8s.io/v1, kind: Ingress, metadata: {{name: web, namespace: ns}}, spec: {spec}}}"
+        );
+        let translation = translate_yaml(&[], &ingress).await;
+        assert!(!translation.failed);
+        let [route] = translation.http_routes.as_slice() else {
+            panic!("expected a single default backend route");
+        };
+        assert_eq!(route.spec.hostnames, None);
+        assert_eq!(
+            backend_names(&translation),
+            vec![("web".to_string(), Some(80))]
+        );
+
+        let translation =
+            translate_yaml(&[], "{metadata: {name: web, namespace: ns}, spec: {}}").await;
+        assert!(translation.http_routes.is_empty());
+        assert!(!translation.failed);
+    }
+
+    /// Ingress with a default backend and an `/api` path for each of the hosts.
+    fn hosts_with_default_backend(hosts: &[String]) -> String {
+        let rules = hosts
+            .iter()
+            .map(|host| {
+                format!(
+                    "{{host: {host}, http: {{paths: [{{path: /api, pathType: Prefix, backend: {{service: {{name: api, port: {{number: 80}}}}}}}}]}}}}"
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!(
+            "{{metadata: {{name: web, namespace: ns}}, spec: {{defaultBackend: {{service: {{name: web, port: {{number: 80}}}}}}, rules: [{rules}]}}}}"
+        )
+    }
+
+    #[tokio::test]
+    async fn default_backend_hostname_limit() {
+        let mut hosts = (0..20)
+            .map(|index| format!("host-{index}.example.com"))
+            .collect::<Vec<_>>();
+        // Hosts listed in several rules are only matched once.
+        hosts.push("host-0.example.com".to_string());
+        let translation = translate_yaml(&[], &hosts_with_default_backend(&hosts)).await;
+        let default_routes = translation
+            .http_routes
+            .iter()
+            .filter(|route| route.name_any().contains("default-backend"))
+            .collect::<Vec<_>>();
+        let hostnames = default_routes
+            .iter()
+            .map(|route| route.spec.hostnames.clone().unwrap_or_default())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            hostnames.iter().map(Vec::len).collect::<Vec<_>>(),
+            vec![16, 4]
+        );
+        assert_eq!(hostnames.concat(), hosts[..20].to_vec(),);
+        let names = default_routes
+            .iter()
+            .map(|route| route.name_any())
+            .collect::<HashSet<_>>();
+        assert_eq!(names.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn default_backend_in_consolidated_routes() {
+        let ingress = r#"
+apiVersion: networking.k8s.io/v1
+kind: Ingress
+metadata:
+  name: web
+  namespace: ns
+  annotations: {i2g-operator/consolidate-hosts: "true"}
+spec:
+  defaultBackend: {service: {name: fallback, port: {number: 80}}}
+  rules:
+  - host: a.example.com
+    http:
+      paths:
+      - {path: /api, pathType: Prefix, backend: {service: {name: api, port: {number: 80}}}}
+  - host: b.example.com
+    http:
+      paths:
+      - {path: /api, pathType: Prefix, backend: {service: {name: api, port: {number: 80}}}}
+  - host: root.example.com
+    http:
+      paths:
+      - {path: /, pathType: Prefix, backend: {service: {name: web, port: {number: 80}}}}
+"#;
+        let translation = translate_yaml(&[], ingress).await;
+        let routes = translation
+            .http_routes
+            .iter()
+            .map(|route| {
+                let rules = rules(route)
+                    .iter()
+                    .map(|rule| {
+                        let backend = &rule.backend_refs.as_ref().unwrap()[0];
+                        let path = rule.matches.as_ref().unwrap()[0].path.clone().unwrap();
+                        (backend.name.clone(), path.value.unwrap())
+                    })
+                    .collect::<Vec<_>>();
+                (route.spec.hostnames.clone().unwrap(), rules)
+            })
+            .collect::<Vec<_>>();
+        let strings = |items: &[&str]| {
+            items
+                .iter()
+                .map(|item| item.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            routes,
+            vec![
+                (
+                    strings(&["a.example.com", "b.example.com"]),
+                    vec![
+                        ("api".to_string(), "/api".to_string()),
+                        ("fallback".to_string(), "/".to_string())
+                    ]
+                ),
+                (
+                    strings(&["root.example.com"]),
+                    vec![("web".to_string(), "/".to_string())]
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn default_backend_without_room_in_consolidated_route() {
+        let hosts = ["a.example.com".to_string(), "b.example.com".to_string()];
+        let mut ingress =
+            serde_yaml::from_str::<Ingress>(&hosts_with_default_backend(&hosts)).unwrap();
+        ingress
+            .annotations_mut()
+            .insert(consts::CONSOLIDATE_HOSTS.to_string(), "true".to_string());
+        for rule in ingress.spec.as_mut().unwrap().rules.iter_mut().flatten() {
+            let http = rule.http.as_mut().unwrap();
+            let path = http.paths[0].clone();
+            http.paths = (0..MAX_ROUTE_RULES)
+                .map(|index| HTTPIngressPath {
+                    path: Some(format!("/api-{index}")),
+                    ..path.clone()
+                })
+                .collect();
+        }
+        let translation = translate_yaml(&[], &serde_yaml::to_string(&ingress).unwrap()).await;
+        let [route, default_route] = translation.http_routes.as_slice() else {
+            panic!("expected a consolidated route and a default backend route");
+        };
+        assert_eq!(rules(route).len(), MAX_ROUTE_RULES);
+        assert_eq!(route.spec.hostnames.as_deref(), Some(hosts.as_slice()));
+        assert_eq!(
+            default_route.spec.hostnames.as_deref(),
+            Some(hosts.as_slice())
+        );
+        assert_eq!(
+            rules(default_route)[0].name.as_deref(),
+            Some(DEFAULT_BACKEND_RULE)
+        );
+    }
+
+    #[tokio::test]
+    async fn mixed_path_types() {
+        let ingress = r#"
+apiVersion: networking.k8s.io/v1
+kind: Ingress
+metadata: {name: web, namespace: ns}
+spec:
+  rules:
+  - host: example.com
+    http:
+      paths:
+      - {path: /foo, pathType: Exact, backend: {service: {name: web, port: {number: 80}}}}
+      - {path: /bar, pathType: Prefix, backend: {service: {name: web, port: {number: 80}}}}
+      - {path: /foo, pathType: Prefix, backend: {service: {name: web, port: {number: 80}}}}
+      - {path: /foo, pathType: Prefix, backend: {service: {name: api, port: {number: 80}}}}
+      - {path: /foo/, pathType: Prefix, backend: {service: {name: web, port: {number: 80}}}}
+"#;
+        let translation = translate_yaml(&[], ingress).await;
+        let rules = translation
+            .http_routes
+            .iter()
+            .flat_map(rules)
+            .map(|rule| {
+                let service = rule.backend_refs.as_ref().unwrap()[0].name.clone();
+                let paths = rule
+                    .matches
+                    .iter()
+                    .flatten()
+                    .filter_map(|rule_match| rule_match.path.as_ref())
+                    .map(|path| (path.value.clone().unwrap(), path.r#type.clone().unwrap()))
+                    .collect::<Vec<_>>();
+                (rule.name.clone().unwrap(), service, paths)
+            })
+            .collect::<Vec<_>>();
+        use HTTPRouteRulesMatchesPathType::{Exact, PathPrefix};
+        // Exact and Prefix paths `/foo` to the same backend share a rule,
+        // other backends and paths keep their own rules.
+        let paths = rules
+            .iter()
+            .map(|(_, service, paths)| (service.as_str(), paths.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            vec![
+                (
+                    "web",
+                    vec![
+                        ("/foo".to_string(), Exact),
+                        ("/foo".to_string(), PathPrefix)
+                    ]
+                ),
+                ("web", vec![("/bar".to_string(), PathPrefix)]),
+                ("api", vec![("/foo".to_string(), PathPrefix)]),
+                ("web", vec![("/foo/".to_string(), PathPrefix)]),
+            ]
+        );
+        // `/foo` and `/foo/` are sanitized to the same rule name.
+        let names = rules.iter().map(|(name, ..)| name).collect::<HashSet<_>>();
+        assert_eq!(names.len(), rules.len(), "{rules:?}");
+    }
+
+    const TLS_INGRESS: &str = r#"
+apiVersion: networking.k8s.io/v1
+kind: Ingress
+metadata:
+  name: web
+  namespace: ns
+  annotations: {i2g-operator/ssl-redirect: "true"}
+spec:
+  tls:
+  - hosts: [example.com]
+    secretName: cert
+  rules:
+  - host: example.com
+    http:
+      paths:
+      - {path: /, pathType: Prefix, backend: {service: {name: web, port: {number: 80}}}}
+"#;
+
+    /// Ports of RequestRedirect filters of the generated routes.
+    fn redirect_ports(translation: &Translation) -> Vec<Option<i32>> {
+        translation
+            .http_routes
+            .iter()
+            .flat_map(rules)
+            .flat_map(|rule| rule.filters.iter().flatten())
+            .filter_map(|filter| filter.request_redirect.as_ref())
+            .map(|redirect| redirect.port)
+            .collect()
+    }
+
+    #[rstest]
+    #[case(None, vec![None])]
+    #[case(Some("8443"), vec![Some(8443)])]
+    #[case(Some(" 443 "), vec![Some(443)])]
+    #[tokio::test]
+    async fn redirect_port(#[case] port: Option<&str>, #[case] expected: Vec<Option<i32>>) {
+        let mut ingress = serde_yaml::from_str::<Ingress>(TLS_INGRESS).unwrap();
+        if let Some(port) = port {
+            ingress
+                .annotations_mut()
+                .insert(consts::REDIRECT_PORT.to_string(), port.to_string());
+        }
+        let translation = translate_yaml(&[], &serde_yaml::to_string(&ingress).unwrap()).await;
+        assert_eq!(redirect_ports(&translation), expected);
+    }
+
+    #[rstest]
+    #[case("0")]
+    #[case("-1")]
+    #[case("65536")]
+    #[case("https")]
+    #[tokio::test]
+    async fn invalid_redirect_port(#[case] port: &str) {
+        let mut ingress = serde_yaml::from_str::<Ingress>(TLS_INGRESS).unwrap();
+        ingress
+            .annotations_mut()
+            .insert(consts::REDIRECT_PORT.to_string(), port.to_string());
+        let result = translate(&ingress, Arc::new(offline_ctx(&[]))).await;
+        assert!(matches!(result, Err(I2GError::ParseError(_))));
+    }
+
+    /// Filter types and headers set by a rule.
+    type RuleFilters = (Vec<HTTPRouteRulesFiltersType>, Vec<(String, String)>);
+
+    /// Types of filters and headers set by the rules of the generated routes.
+    fn filters_and_set_headers(translation: &Translation) -> Vec<RuleFilters> {
+        translation
+            .http_routes
+            .iter()
+            .flat_map(rules)
+            .map(|rule| {
+                let filters = rule.filters.as_deref().unwrap_or_default();
+                let types = filters.iter().map(|filter| filter.r#type.clone()).collect();
+                let set = filters
+                    .iter()
+                    .filter_map(|filter| filter.request_header_modifier.as_ref())
+                    .flat_map(|modifier| modifier.set.iter().flatten())
+                    .map(|header| (header.name.clone(), header.value.clone()))
+                    .collect();
+                (types, set)
+            })
+            .collect()
+    }
+
+    #[rstest]
+    #[case::no_rewrite(&["--original-path-header", "X-Original-Path"], None, vec![])]
+    #[case::no_header(&[], Some("/v2"), vec![HTTPRouteRulesFiltersType::UrlRewrite])]
+    #[case::rewrite(
+        &["--original-path-header", "X-Original-Path", "--strip-request-headers", "X-Real-IP"],
+        Some("/v2"),
+        vec![
+            HTTPRouteRulesFiltersType::RequestHeaderModifier,
+            HTTPRouteRulesFiltersType::UrlRewrite,
+        ],
+    )]
+    #[tokio::test]
+    async fn original_path_header(
+        #[case] args: &[&str],
+        #[case] rewrite: Option<&str>,
+        #[case] expected_types: Vec<HTTPRouteRulesFiltersType>,
+    ) {
+        let mut ingress = serde_yaml::from_str::<Ingress>(SIMPLE_INGRESS).unwrap();
+        if let Some(rewrite) = rewrite {
+            ingress
+                .annotations_mut()
+                .insert(consts::REWRITE_PREFIX.to_string(), rewrite.to_string());
+        }
+        let translation = translate_yaml(args, &serde_yaml::to_string(&ingress).unwrap()).await;
+        let expected_set = if rewrite.is_some() && !args.is_empty() {
+            vec![("X-Original-Path".to_string(), "/".to_string())]
+        } else {
+            vec![]
+        };
+        let rules = filters_and_set_headers(&translation);
+        assert!(!rules.is_empty());
+        for (types, set) in rules {
+            assert_eq!(types, expected_types);
+            assert_eq!(set, expected_set);
+        }
+    }
+
+    #[rstest]
+    #[case(&[], "web")]
+    #[case(&["--tls-secret-conflicts", "first"], "websecure")]
+    #[case(&["--tls-secret-conflicts", "last"], "websecure")]
+    #[tokio::test]
+    async fn tls_secret_conflicts(#[case] args: &[&str], #[case] section: &str) {
+        let ingress = r#"
+apiVersion: networking.k8s.io/v1
+kind: Ingress
+metadata: {name: web, namespace: ns}
+spec:
+  tls:
+  - {hosts: [example.com], secretName: first}
+  - {hosts: [example.com], secretName: second}
+  rules:
+  - host: example.com
+    http:
+      paths:
+      - {path: /, pathType: Prefix, backend: {service: {name: web, port: {number: 80}}}}
+"#;
+        let args = [SECTION_ARGS, args].concat();
+        let translation = translate_yaml(&args, ingress).await;
+        assert_eq!(
+            http_sections(&translation).get("example.com"),
+            Some(&vec![Some(section.to_string())])
+        );
+        assert!(
+            translation
+                .warnings
+                .iter()
+                .any(|warning| warning.contains("different secrets (first, second)"))
+        );
+    }
+
+    /// Namespaces, names and owners of generated ReferenceGrants.
+    fn grant_owners(translation: &Translation) -> Vec<(String, String, String)> {
+        translation
+            .reference_grants
+            .iter()
+            .map(|grant| {
+                assert_eq!(
+                    grant
+                        .labels()
+                        .get(consts::MANAGED_BY_LABEL)
+                        .map(String::as_str),
+                    Some(consts::MANAGED_BY_VALUE)
+                );
+                (
+                    grant.namespace().unwrap_or_default(),
+                    grant.name_any(),
+                    grant.annotations()[consts::OWNER_INGRESSES].clone(),
+                )
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn reference_grant_owners() {
+        let args = [
+            "--create-reference-grants",
+            "--default-gateway-namespace",
+            "infra",
+        ];
+        let translation = translate_yaml(&args, SIMPLE_INGRESS).await;
+        assert_eq!(
+            grant_owners(&translation),
+            vec![(
+                "infra".to_string(),
+                "i2g-ns-gw".to_string(),
+                "ns/web".to_string()
+            )]
+        );
+
+        // Grants aren't needed without cross-namespace references.
+        let translation = translate_yaml(
+            &[
+                "--create-reference-grants",
+                "--default-gateway-namespace",
+                "ns",
+            ],
+            SIMPLE_INGRESS,
+        )
+        .await;
+        assert_eq!(grant_owners(&translation), vec![]);
+    }
+
+    #[tokio::test]
+    async fn backend_reference_grant_owners() {
+        let args = [
+            "--create-reference-grants",
+            "--default-gateway-namespace",
+            "ns",
+        ];
+        let mut ingress = serde_yaml::from_str::<Ingress>(SIMPLE_INGRESS).unwrap();
+        ingress
+            .annotations_mut()
+            .insert(consts::BACKEND_NAMESPACE.to_string(), "shared".to_string());
+        let translation = translate_yaml(&args, &serde_yaml::to_string(&ingress).unwrap()).await;
+        assert_eq!(
+            grant_owners(&translation),
+            vec![(
+                "shared".to_string(),
+                "i2g-ns-web-backends".to_string(),
+                "ns/web".to_string()
+            )]
+        );
+
+        // Without the annotation the grant isn't desired anymore, so it's released.
+        let translation = translate_yaml(&args, SIMPLE_INGRESS).await;
+        assert_eq!(grant_owners(&translation), vec![]);
+    }
+
+    #[tokio::test]
+    async fn envoy_backend_owners() {
+        let ctx = ctx_with_services(
+            &["--external-name-backends", "envoy-backend"],
+            &[
+                "{metadata: {name: web, namespace: ns}, spec: {type: ExternalName, externalName: web.example.org}}",
+            ],
+        );
+        let ingress = serde_yaml::from_str::<Ingress>(SIMPLE_INGRESS).unwrap();
+        let translation = translate(&ingress, Arc::new(ctx)).await.unwrap();
+        let backends = translation
+            .envoy_backends
+            .iter()
+            .map(|backend| {
+                (
+                    backend.namespace().unwrap_or_default(),
+                    backend.name_any(),
+                    backend.annotations()[consts::OWNER_INGRESSES].clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            backends,
+            vec![(
+                "ns".to_string(),
+                "web-80-external".to_string(),
+                "ns/web".to_string()
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn backend_tls_policy_owners() {
+        let mut ingress = serde_yaml::from_str::<Ingress>(SIMPLE_INGRESS).unwrap();
+        ingress
+            .annotations_mut()
+            .insert(consts::BACKEND_PROTOCOL.to_string(), "HTTPS".to_string());
+        let translation = translate_yaml(&[], &serde_yaml::to_string(&ingress).unwrap()).await;
+        let policies = translation
+            .backend_tls_policies
+            .iter()
+            .map(|policy| {
+                (
+                    policy.name_any(),
+                    policy.annotations()[consts::OWNER_INGRESSES].clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            policies,
+            vec![("web-backend-tls".to_string(), "ns/web".to_string())]
+        );
+
+        // Policies aren't desired once backends are reached over HTTP, so they're released.
+        let translation = translate_yaml(&[], SIMPLE_INGRESS).await;
+        assert!(translation.backend_tls_policies.is_empty());
+    }
+}
@@ -1,11 +1,39 @@
-use k8s_openapi::apimachinery::pkg::apis::meta::v1::OwnerReference;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    str::FromStr,
+};
+
+use k8s_openapi::{
+    api::networking::v1::{Ingress, IngressTLS},
+    apimachinery::pkg::apis::meta::v1::OwnerReference,
+};
 use kube::{ResourceExt, api::ObjectMeta};
 
+use crate::{
+    args::{PathNormalization, TlsSecretConflicts},
+    consts,
+    err::I2GError,
+};
+
 pub trait ObjectMetaI2GExt: Default {
     fn add_owner<T>(&mut self, owner: &T)
     where
         T: kube::Resource<DynamicType = ()>,
         T::DynamicType: Eq + std::hash::Hash + Clone;
+
+    fn add_managed_by_label(&mut self);
+
+    fn add_annotation(&mut self, key: &str, value: String);
+
+    /// Ingresses owning an object shared by Ingresses, as `namespace/name`.
+    fn shared_owners(&self) -> BTreeSet<String>;
+
+    fn set_shared_owners(&mut self, owners: &BTreeSet<String>);
+
+    /// Owners left after the Ingress releases the shared object,
+    /// None if the Ingress doesn't own it.
+    fn release_shared_owner(&self, owner: &str) -> Option<BTreeSet<String>>;
 }
 
 impl ObjectMetaI2GExt for ObjectMeta {
@@ -32,6 +60,85 @@ impl ObjectMetaI2GExt for ObjectMeta {
         owners.push(owner);
         self.owner_references = Some(owners);
     }
+
+    fn add_managed_by_label(&mut self) {
+        self.labels.get_or_insert_default().insert(
+            consts::MANAGED_BY_LABEL.to_string(),
+            consts::MANAGED_BY_VALUE.to_string(),
+        );
+    }
+
+    fn add_annotation(&mut self, key: &str, value: String) {
+        self.annotations
+            .get_or_insert_default()
+            .insert(key.to_string(), value);
+    }
+
+    fn shared_owners(&self) -> BTreeSet<String> {
+        self.annotations
+            .as_ref()
+            .and_then(|annotations| annotations.get(consts::OWNER_INGRESSES))
+            .into_iter()
+            .flat_map(|owners| owners.split(','))
+            .map(str::trim)
+            .filter(|owner| !owner.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn set_shared_owners(&mut self, owners: &BTreeSet<String>) {
+        let owners = owners.iter().cloned().collect::<Vec<_>>().join(",");
+        self.add_annotation(consts::OWNER_INGRESSES, owners);
+    }
+
+    fn release_shared_owner(&self, owner: &str) -> Option<BTreeSet<String>> {
+        let mut owners = self.shared_owners();
+        owners.remove(owner).then_some(owners)
+    }
+}
+
+/// Owner of objects shared by Ingresses, see [`ObjectMetaI2GExt::shared_owners`].
+pub fn shared_owner(ingress: &Ingress) -> String {
+    format!(
+        "{}/{}",
+        ingress.namespace().unwrap_or_default(),
+        ingress.name_any()
+    )
+}
+
+/// Entry of a shared object in the shared objects annotation of its Ingresses.
+pub fn shared_object<K>(obj: &K) -> String
+where
+    K: kube::Resource<DynamicType = ()>,
+{
+    format!(
+        "{}:{}/{}",
+        K::kind(&()),
+        obj.namespace().unwrap_or_default(),
+        obj.name_any()
+    )
+}
+
+/// Shared objects owned by the Ingress, see [`shared_object`].
+pub fn shared_objects(ingress: &Ingress) -> BTreeSet<String> {
+    ingress
+        .annotations()
+        .get(consts::SHARED_OBJECTS)
+        .into_iter()
+        .flat_map(|objects| objects.split(','))
+        .map(str::trim)
+        .filter(|object| !object.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Class of the Ingress from `spec.ingressClassName` or the legacy annotation.
+pub fn ingress_class(ingress: &Ingress) -> Option<&String> {
+    ingress
+        .spec
+        .as_ref()
+        .and_then(|spec| spec.ingress_class_name.as_ref())
+        .or_else(|| ingress.annotations().get(consts::LEGACY_INGRESS_CLASS))
 }
 
 pub fn sanitize_hostname(hostname: &str) -> String {
@@ -46,3 +153,434 @@ pub fn sanitize_hostname(hostname: &str) -> String {
     }
     res.to_string()
 }
+
+/// Check whether the string is a valid qualified name
+/// as used in label and annotation keys: `[prefix/]name`.
+pub fn is_qualified_name(key: &str) -> bool {
+    let (prefix, name) = match key.split_once('/') {
+        Some((prefix, name)) => (Some(prefix), name),
+        None => (None, key),
+    };
+    if let Some(prefix) = prefix {
+        let valid_prefix = !prefix.is_empty()
+            && prefix.len() <= 253
+            && prefix.split('.').all(|part| {
+                !part.is_empty()
+                    && part
+                        .chars()
+                        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+                    && !part.starts_with('-')
+                    && !part.ends_with('-')
+            });
+        if !valid_prefix {
+            return false;
+        }
+    }
+    !name.is_empty()
+        && name.len() <= 63
+        && name.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && name.ends_with(|c: char| c.is_ascii_alphanumeric())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Pattern of label or annotation keys.
+/// Either a glob with `*` wildcards (`team-*`) or a regular expression
+/// between slashes (`/^app\.kubernetes\.io/.+$/`).
+#[derive(Debug, Clone)]
+pub struct KeyPattern(regex::Regex);
+
+impl KeyPattern {
+    pub fn matches(&self, key: &str) -> bool {
+        self.0.is_match(key)
+    }
+}
+
+impl FromStr for KeyPattern {
+    type Err = I2GError;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let pattern = match raw
+            .strip_prefix('/')
+            .and_then(|rest| rest.strip_suffix('/'))
+        {
+            Some(regex) => regex.to_string(),
+            None => format!(
+                "^{}$",
+                raw.split('*')
+                    .map(regex::escape)
+                    .collect::<Vec<_>>()
+                    .join(".*")
+            ),
+        };
+        regex::Regex::new(&pattern)
+            .map(KeyPattern)
+            .map_err(|err| I2GError::ParseError(format!("Invalid key pattern '{raw}': {err}")))
+    }
+}
+
+/// Gateway of Ingresses with the class, in format `class=name` or `class=namespace/name`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassGateway {
+    pub class: String,
+    /// Gateway in the format of the gateway name annotation.
+    pub gateway: String,
+}
+
+impl FromStr for ClassGateway {
+    type Err = I2GError;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            I2GError::ParseError(format!(
+                "Class gateway '{raw}' should be in format class=name or class=namespace/name"
+            ))
+        };
+        let (class, gateway) = raw.trim().split_once('=').ok_or_else(invalid)?;
+        let valid_gateway = match gateway.split_once('/') {
+            Some((namespace, name)) => !namespace.is_empty() && !name.is_empty(),
+            None => !gateway.is_empty(),
+        };
+        if class.is_empty() || !valid_gateway {
+            return Err(invalid());
+        }
+        Ok(ClassGateway {
+            class: class.to_string(),
+            gateway: gateway.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum LabelRequirement {
+    Equals(String, String),
+    NotEquals(String, String),
+    Exists(String),
+}
+
+/// Equality-based label selector, e.g. `app=edge,tier!=internal,public`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabelSelector(Vec<LabelRequirement>);
+
+impl LabelSelector {
+    pub fn matches(&self, labels: &BTreeMap<String, String>) -> bool {
+        self.0.iter().all(|requirement| match requirement {
+            LabelRequirement::Equals(key, value) => labels.get(key) == Some(value),
+            LabelRequirement::NotEquals(key, value) => labels.get(key) != Some(value),
+            LabelRequirement::Exists(key) => labels.contains_key(key),
+        })
+    }
+}
+
+impl FromStr for LabelSelector {
+    type Err = I2GError;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let mut requirements = vec![];
+        for item in raw
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+        {
+            let requirement = if let Some((key, value)) = item.split_once("!=") {
+                LabelRequirement::NotEquals(key.trim().to_string(), value.trim().to_string())
+            } else if let Some((key, value)) = item.split_once('=') {
+                let value = value.strip_prefix('=').unwrap_or(value);
+                LabelRequirement::Equals(key.trim().to_string(), value.trim().to_string())
+            } else {
+                LabelRequirement::Exists(item.to_string())
+            };
+            let (LabelRequirement::Equals(key, _)
+            | LabelRequirement::NotEquals(key, _)
+            | LabelRequirement::Exists(key)) = &requirement;
+            if !is_qualified_name(key) {
+                return Err(I2GError::ParseError(format!(
+                    "'{key}' in label selector '{raw}' is not a valid label key"
+                )));
+            }
+            requirements.push(requirement);
+        }
+        if requirements.is_empty() {
+            return Err(I2GError::ParseError("Label selector is empty".to_string()));
+        }
+        Ok(LabelSelector(requirements))
+    }
+}
+
+impl fmt::Display for LabelSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let requirements = self
+            .0
+            .iter()
+            .map(|requirement| match requirement {
+                LabelRequirement::Equals(key, value) => format!("{key}={value}"),
+                LabelRequirement::NotEquals(key, value) => format!("{key}!={value}"),
+                LabelRequirement::Exists(key) => key.clone(),
+            })
+            .collect::<Vec<_>>();
+        write!(f, "{}", requirements.join(","))
+    }
+}
+
+/// Copy entries with keys matching any of the patterns.
+pub fn matching_entries(
+    source: Option<&BTreeMap<String, String>>,
+    patterns: &[KeyPattern],
+) -> BTreeMap<String, String> {
+    source
+        .into_iter()
+        .flatten()
+        .filter(|(key, _)| patterns.iter().any(|pattern| pattern.matches(key)))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()
+}
+
+/// Normalize path of an Ingress rule according to the selected mode.
+pub fn normalize_path(path: &str, mode: PathNormalization) -> String {
+    if mode == PathNormalization::None {
+        return path.to_string();
+    }
+    let mut normalized = String::with_capacity(path.len());
+    for c in path.chars() {
+        if c == '/' && normalized.ends_with('/') {
+            continue;
+        }
+        normalized.push(c);
+    }
+    if mode == PathNormalization::CollapseTrim && normalized.len() > 1 && normalized.ends_with('/')
+    {
+        normalized.pop();
+    }
+    normalized
+}
+
+/// Secrets of hosts listed in TLS sections of an Ingress, None if a section doesn't have a secret.
+/// Hosts listed in several sections with different secrets are resolved according to `conflicts`,
+/// a warning is added for each of them.
+pub fn tls_host_secrets<'a>(
+    tls: &'a [IngressTLS],
+    conflicts: TlsSecretConflicts,
+    warnings: &mut Vec<String>,
+) -> BTreeMap<&'a str, Option<&'a str>> {
+    let mut secrets = BTreeMap::<&str, Vec<Option<&str>>>::new();
+    for section in tls {
+        for host in section.hosts.iter().flatten() {
+            let host_secrets = secrets.entry(host).or_default();
+            let secret = section.secret_name.as_deref();
+            if !host_secrets.contains(&secret) {
+                host_secrets.push(secret);
+            }
+        }
+    }
+    secrets
+        .into_iter()
+        .filter_map(|(host, host_secrets)| {
+            let secret = match host_secrets.as_slice() {
+                [secret] => return Some((host, *secret)),
+                _ if conflicts == TlsSecretConflicts::Skip => None,
+                [first, ..] if conflicts == TlsSecretConflicts::First => Some(*first),
+                [.., last] => Some(*last),
+                [] => None,
+            };
+            let names = host_secrets
+                .iter()
+                .map(|secret| secret.unwrap_or("<none>"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            warnings.push(match secret {
+                Some(secret) => format!(
+                    "Host {host} is listed in TLS sections with different secrets ({names}), secret {} is used",
+                    secret.unwrap_or("<none>")
+                ),
+                None => format!(
+                    "Host {host} is listed in TLS sections with different secrets ({names}), TLS is skipped for the host"
+                ),
+            });
+            secret.map(|secret| (host, secret))
+        })
+        .collect()
+}
+
+/// Prefix matched by a catch-all path like `/api/*` or `*`.
+/// Returns None if the path isn't a wildcard path.
+pub fn wildcard_prefix(path: &str) -> Option<String> {
+    if path == "*" {
+        return Some("/".to_string());
+    }
+    let prefix = path.strip_suffix("/*")?;
+    if prefix.is_empty() {
+        return Some("/".to_string());
+    }
+    Some(prefix.to_string())
+}
+
+/// Whether every value of `desired` is present in `live`. Objects may have
+/// additional keys in `live`, e.g. defaults set by the API server,
+/// arrays must have the same length with matching items.
+pub fn is_json_subset(desired: &serde_json::Value, live: &serde_json::Value) -> bool {
+    use serde_json::Value;
+    match (desired, live) {
+        (Value::Object(desired), Value::Object(live)) => desired.iter().all(|(key, value)| {
+            live.get(key)
+                .is_some_and(|live_value| is_json_subset(value, live_value))
+        }),
+        (Value::Array(desired), Value::Array(live)) => {
+            desired.len() == live.len()
+                && desired
+                    .iter()
+                    .zip(live)
+                    .all(|(desired, live)| is_json_subset(desired, live))
+        }
+        _ => desired == live,
+    }
+}
+
+/// Whether `version` is newer than `current`.
+/// Versions are compared by their numeric `major.minor.patch` parts,
+/// pre-release suffixes are ignored. Unparsable versions are never newer.
+pub fn is_newer_version(version: &str, current: &str) -> bool {
+    fn parse(version: &str) -> Option<Vec<u64>> {
+        version
+            .trim_start_matches('v')
+            .split(['-', '+'])
+            .next()?
+            .split('.')
+            .map(|part| part.parse::<u64>().ok())
+            .collect()
+    }
+    match (parse(version), parse(current)) {
+        (Some(version), Some(current)) => version > current,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("//foo//bar/", PathNormalization::None, "//foo//bar/")]
+    #[case("//foo//bar/", PathNormalization::Collapse, "/foo/bar/")]
+    #[case("//foo//bar/", PathNormalization::CollapseTrim, "/foo/bar")]
+    #[case("/", PathNormalization::CollapseTrim, "/")]
+    #[case("///", PathNormalization::CollapseTrim, "/")]
+    #[case("/foo", PathNormalization::Collapse, "/foo")]
+    fn normalized_paths(
+        #[case] path: &str,
+        #[case] mode: PathNormalization,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(normalize_path(path, mode), expected);
+    }
+
+    #[rstest]
+    #[case("*", Some("/"))]
+    #[case("/*", Some("/"))]
+    #[case("/api/*", Some("/api"))]
+    #[case("/api/v1/*", Some("/api/v1"))]
+    #[case("/api", None)]
+    #[case("/api*", None)]
+    #[case("/api/*/v1", None)]
+    fn wildcard_prefixes(#[case] path: &str, #[case] expected: Option<&str>) {
+        assert_eq!(wildcard_prefix(path).as_deref(), expected);
+    }
+
+    #[rstest]
+    #[case("0.2.0", "0.1.0", true)]
+    #[case("v1.0.0", "0.9.9", true)]
+    #[case("0.10.0", "0.9.0", true)]
+    #[case("0.1.1-rc1", "0.1.0", true)]
+    #[case("0.1.0", "0.1.0", false)]
+    #[case("0.1.0-rc1", "0.1.0", false)]
+    #[case("0.1.0", "0.2.0", false)]
+    #[case("latest", "0.1.0", false)]
+    #[case("0.2.0", "dev", false)]
+    fn newer_versions(#[case] version: &str, #[case] current: &str, #[case] expected: bool) {
+        assert_eq!(is_newer_version(version, current), expected);
+    }
+
+    fn tls_section(hosts: &[&str], secret: Option<&str>) -> IngressTLS {
+        IngressTLS {
+            hosts: Some(hosts.iter().map(ToString::to_string).collect()),
+            secret_name: secret.map(str::to_string),
+        }
+    }
+
+    #[rstest]
+    #[case(TlsSecretConflicts::Skip, vec![("a.com", Some("a")), ("c.com", None)])]
+    #[case(
+        TlsSecretConflicts::First,
+        vec![("a.com", Some("a")), ("b.com", Some("a")), ("c.com", None)]
+    )]
+    #[case(
+        TlsSecretConflicts::Last,
+        vec![("a.com", Some("a")), ("b.com", Some("b")), ("c.com", None)]
+    )]
+    fn tls_secret_conflicts(
+        #[case] conflicts: TlsSecretConflicts,
+        #[case] expected: Vec<(&str, Option<&str>)>,
+    ) {
+        let tls = [
+            tls_section(&["a.com", "b.com"], Some("a")),
+            tls_section(&["b.com"], Some("b")),
+            // The same secret listed again isn't a conflict.
+            tls_section(&["a.com"], Some("a")),
+            tls_section(&["c.com"], None),
+        ];
+        let mut warnings = vec![];
+        let secrets = tls_host_secrets(&tls, conflicts, &mut warnings);
+        assert_eq!(secrets.into_iter().collect::<Vec<_>>(), expected);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("b.com"));
+    }
+
+    #[test]
+    fn shared_owners_lifecycle() {
+        let owned_by = |owner: &str| {
+            let mut meta = ObjectMeta::default();
+            meta.set_shared_owners(&BTreeSet::from([owner.to_string()]));
+            meta
+        };
+        // Both Ingresses apply the grant, the second one keeps the first owner.
+        let mut live = owned_by("ns/a");
+        let mut owners = live.shared_owners();
+        owners.extend(owned_by("ns/b").shared_owners());
+        live.set_shared_owners(&owners);
+        assert_eq!(
+            live.annotations.as_ref().unwrap()[consts::OWNER_INGRESSES],
+            "ns/a,ns/b"
+        );
+
+        // Releasing by an Ingress which doesn't own the grant changes nothing.
+        assert_eq!(live.release_shared_owner("other/a"), None);
+        assert_eq!(ObjectMeta::default().release_shared_owner("ns/a"), None);
+
+        // The grant is kept while the second Ingress needs it.
+        let remaining = live.release_shared_owner("ns/a").unwrap();
+        assert_eq!(remaining, BTreeSet::from(["ns/b".to_string()]));
+        live.set_shared_owners(&remaining);
+
+        // The last owner releases it, so it's deleted.
+        assert_eq!(live.release_shared_owner("ns/b"), Some(BTreeSet::new()));
+    }
+
+    #[test]
+    fn shared_objects_annotation() {
+        use gateway_api::referencegrants::ReferenceGrant;
+        let mut ingress = Ingress::default();
+        assert!(shared_objects(&ingress).is_empty());
+        let mut grant = ReferenceGrant::default();
+        grant.metadata.name = Some("web".to_string());
+        grant.metadata.namespace = Some("infra".to_string());
+        ingress.annotations_mut().insert(
+            consts::SHARED_OBJECTS.to_string(),
+            format!("{}, Backend:ns/web-80-external", shared_object(&grant)),
+        );
+        assert_eq!(
+            shared_objects(&ingress).into_iter().collect::<Vec<_>>(),
+            vec!["Backend:ns/web-80-external", "ReferenceGrant:infra/web"]
+        );
+    }
+}
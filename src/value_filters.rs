@@ -1,11 +1,9 @@
-use std::{
-    collections::{BTreeMap, HashMap},
-    str::FromStr,
-};
+use std::{collections::BTreeMap, str::FromStr};
 
-use gateway_api::httproutes::{
-    HTTPRouteRulesMatchesHeaders, HTTPRouteRulesMatchesHeadersType,
-    HTTPRouteRulesMatchesQueryParams, HTTPRouteRulesMatchesQueryParamsType,
+use gateway_api::apis::experimental::httproutes::{
+    HTTPRouteRulesMatchesHeaders, HTTPRouteRulesMatchesHeadersType, HTTPRouteRulesMatchesMethod,
+    HTTPRouteRulesMatchesPathType, HTTPRouteRulesMatchesQueryParams,
+    HTTPRouteRulesMatchesQueryParamsType,
 };
 
 use crate::err::I2GError;
@@ -53,7 +51,7 @@ pub struct QueryMatchersList(pub MatcherList);
 
 impl MatcherList {
     pub fn from_annotations(annotations: &BTreeMap<String, String>, prefix: &str) -> Self {
-        let mut rules = Vec::<(i32, MatchRule)>::new();
+        let mut rules = Vec::<(i32, &str, MatchRule)>::new();
         for (name, value) in annotations
             .iter()
             .filter(|(key, _)| key.starts_with(prefix))
@@ -63,26 +61,44 @@ impl MatcherList {
             };
             match MatchRule::from_str(value) {
                 Ok(rule) => {
-                    rules.push((weight, rule));
+                    rules.push((weight, name, rule));
                 }
                 Err(err) => {
                     tracing::error!("Failed to parse rule from annotation '{name}': {err}");
                 }
             }
         }
-        rules.sort_by(|(weight, _), (weight2, _)| weight.cmp(weight2));
-        Self(rules.into_iter().map(|(_, rule)| rule).collect())
+        // Annotations with the same weight are ordered by their names,
+        // so the resulting order doesn't depend on how they were parsed.
+        rules.sort_by(|(weight, name, _), (weight2, name2, _)| {
+            weight.cmp(weight2).then_with(|| name.cmp(name2))
+        });
+        for pair in rules.windows(2) {
+            if pair[0].0 == pair[1].0 {
+                tracing::warn!(
+                    "Annotations '{}' and '{}' have the same weight {}, ordering them by name",
+                    pair[0].1,
+                    pair[1].1,
+                    pair[0].0
+                );
+            }
+        }
+        Self(rules.into_iter().map(|(_, _, rule)| rule).collect())
     }
 
+    /// Group rules by key, keeping the order in which keys first appear.
     pub fn make_groups(&self) -> Vec<Vec<MatchRule>> {
-        let mut groups = HashMap::<String, Vec<MatchRule>>::new();
+        let mut groups = Vec::<(String, Vec<MatchRule>)>::new();
         for header_matcher in &self.0 {
-            let entry = groups
-                .entry(header_matcher.key.clone())
-                .or_insert_with(|| vec![]);
-            entry.push(header_matcher.clone());
+            match groups
+                .iter_mut()
+                .find(|(key, _)| key == &header_matcher.key)
+            {
+                Some((_, group)) => group.push(header_matcher.clone()),
+                None => groups.push((header_matcher.key.clone(), vec![header_matcher.clone()])),
+            }
         }
-        groups.into_values().collect()
+        groups.into_iter().map(|(_, group)| group).collect()
     }
 
     pub fn catesian_product(&self) -> Vec<Vec<MatchRule>> {
@@ -151,67 +167,249 @@ impl FromStr for MatchRule {
                     match_type = MatchType::RegularExpression;
                     key = key.strip_suffix('~').unwrap();
                 }
-                return Ok(MatchRule {
+                Ok(MatchRule {
                     key: key.to_string(),
                     value: value.to_string(),
                     match_type,
-                });
+                })
+            }
+            _ => Err(anyhow::anyhow!("Invalid rule found '{rule}'").into()),
+        }
+    }
+}
+
+fn parse_method(raw: &str) -> Result<HTTPRouteRulesMatchesMethod, I2GError> {
+    match raw.trim().to_uppercase().as_str() {
+        "GET" => Ok(HTTPRouteRulesMatchesMethod::Get),
+        "HEAD" => Ok(HTTPRouteRulesMatchesMethod::Head),
+        "POST" => Ok(HTTPRouteRulesMatchesMethod::Post),
+        "PUT" => Ok(HTTPRouteRulesMatchesMethod::Put),
+        "DELETE" => Ok(HTTPRouteRulesMatchesMethod::Delete),
+        "CONNECT" => Ok(HTTPRouteRulesMatchesMethod::Connect),
+        "OPTIONS" => Ok(HTTPRouteRulesMatchesMethod::Options),
+        "TRACE" => Ok(HTTPRouteRulesMatchesMethod::Trace),
+        "PATCH" => Ok(HTTPRouteRulesMatchesMethod::Patch),
+        other => Err(I2GError::ParseError(format!(
+            "Unknown HTTP method '{other}'"
+        ))),
+    }
+}
+
+/// Parse HTTP methods from annotations with the prefix.
+/// Values are `|`-separated methods, e.g. `GET|POST`.
+/// Annotations are ordered by the number after the prefix, like header and query matchers.
+pub fn methods_from_annotations(
+    annotations: &BTreeMap<String, String>,
+    prefix: &str,
+) -> Vec<HTTPRouteRulesMatchesMethod> {
+    let mut weighted = Vec::<(i32, &str, &str)>::new();
+    for (name, value) in annotations
+        .iter()
+        .filter(|(key, _)| key.starts_with(prefix))
+    {
+        let Some(weight) = name.split("/").last().and_then(|value| value.parse().ok()) else {
+            continue;
+        };
+        weighted.push((weight, name, value));
+    }
+    weighted.sort_by(|(weight, name, _), (weight2, name2, _)| {
+        weight.cmp(weight2).then_with(|| name.cmp(name2))
+    });
+    let mut methods = vec![];
+    for (_, name, value) in weighted {
+        for raw in value.split('|').filter(|raw| !raw.trim().is_empty()) {
+            match parse_method(raw) {
+                Ok(method) if !methods.contains(&method) => methods.push(method),
+                Ok(_) => {}
+                Err(err) => {
+                    tracing::error!("Failed to parse method from annotation '{name}': {err}");
+                }
             }
-            _ => return Err(anyhow::anyhow!("Invalid rule found '{rule}'").into()),
         }
     }
+    methods
+}
+
+/// Parse path type overrides.
+/// The string should be in the following format:
+/// `/path=Type,/other=Type`, where type is `Exact`, `Prefix` or `RegularExpression`.
+/// Commas which don't follow a type belong to the path, e.g. `/v{1,3}=RegularExpression`.
+/// If the value has several lines, entries are separated by newlines only.
+pub fn parse_path_types(
+    raw: &str,
+) -> Result<BTreeMap<String, HTTPRouteRulesMatchesPathType>, I2GError> {
+    let separator = if raw.contains('\n') { '\n' } else { ',' };
+    let mut path_types = BTreeMap::new();
+    let mut entry = String::new();
+    for part in raw.split(separator) {
+        entry.push_str(part);
+        let item = entry.trim();
+        let typed = item
+            .rsplit_once('=')
+            .map(|(_, path_type)| path_type.trim())
+            .is_some_and(|path_type| {
+                !path_type.is_empty() && path_type.chars().all(|c| c.is_ascii_alphabetic())
+            });
+        if separator == ',' && !item.is_empty() && !typed {
+            // The comma is a part of the path.
+            entry.push(separator);
+            continue;
+        }
+        if !item.is_empty() {
+            let (path, path_type) = parse_path_type(item)?;
+            path_types.insert(path, path_type);
+        }
+        entry.clear();
+    }
+    let rest = entry.trim().trim_end_matches(separator);
+    if !rest.is_empty() {
+        parse_path_type(rest)?;
+    }
+    Ok(path_types)
 }
 
-// #[cfg(test)]
-// mod tests {
-//     use std::str::FromStr;
-//
-//     use crate::value_filters::MatcherList;
-//
-//     use super::MatchRule;
-//     use rstest::rstest;
-//
-//     #[rstest]
-//     #[case("env=prod", MatchRule::Equal("env".to_string(), "prod".to_string()))]
-//     #[case("env~=prod", MatchRule::RegularExpression("env".to_string(), "prod".to_string()))]
-//     fn test_rules(#[case] raw: &str, #[case] expected: MatchRule) {
-//         let rule = MatchRule::from_str(raw).unwrap();
-//         assert_eq!(rule, expected);
-//     }
-//
-//     #[rstest]
-//     #[case(
-//         "headers/1: env=prod\nheaders/2: env~=dev",
-//         MatcherList(vec![
-//             MatchRule::Equal("env".to_string(), "prod".to_string()),
-//             MatchRule::RegularExpression("env".to_string(), "dev".to_string())
-//         ])
-//     )]
-//     #[case(
-//         "headers/2: env=prod\nheaders/1: env~=dev",
-//         MatcherList(vec![
-//             MatchRule::RegularExpression("env".to_string(), "dev".to_string()),
-//             MatchRule::Equal("env".to_string(), "prod".to_string()),
-//         ])
-//     )]
-//     #[case(
-//         "headers/2: invalid\nheaders/1: env=dev",
-//         MatcherList(vec![
-//             MatchRule::Equal("env".to_string(), "dev".to_string()),
-//         ])
-//     )]
-//     fn from_annotations(#[case] annotations: &str, #[case] expected: MatcherList) {
-//         let annotations_map = annotations
-//             .lines()
-//             .filter_map(|line| {
-//                 let parts = line.splitn(2, ": ").collect::<Vec<_>>();
-//                 if parts.len() != 2 {
-//                     return None;
-//                 }
-//                 Some((parts[0].to_string(), parts[1].to_string()))
-//             })
-//             .collect::<std::collections::BTreeMap<_, _>>();
-//         let matcher_list = MatcherList::from_annotations(&annotations_map, "headers/");
-//         assert_eq!(matcher_list, expected);
-//     }
-// }
+fn parse_path_type(item: &str) -> Result<(String, HTTPRouteRulesMatchesPathType), I2GError> {
+    let Some((path, path_type)) = item.rsplit_once('=') else {
+        return Err(I2GError::ParseError(format!(
+            "Path type override '{item}' should be in format path=Type"
+        )));
+    };
+    let path_type = match path_type.trim() {
+        "Exact" => HTTPRouteRulesMatchesPathType::Exact,
+        "Prefix" | "PathPrefix" => HTTPRouteRulesMatchesPathType::PathPrefix,
+        "RegularExpression" => HTTPRouteRulesMatchesPathType::RegularExpression,
+        other => {
+            return Err(I2GError::ParseError(format!(
+                "Unknown path type '{other}' for path '{path}'"
+            )));
+        }
+    };
+    Ok((path.trim().to_string(), path_type))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::{
+        HTTPRouteRulesMatchesPathType, MatchRule, MatchType, MatcherList, parse_path_types,
+    };
+    use rstest::rstest;
+
+    fn equal(key: &str, value: &str) -> MatchRule {
+        MatchRule {
+            key: key.to_string(),
+            value: value.to_string(),
+            match_type: MatchType::Equal,
+        }
+    }
+
+    fn regex(key: &str, value: &str) -> MatchRule {
+        MatchRule {
+            key: key.to_string(),
+            value: value.to_string(),
+            match_type: MatchType::RegularExpression,
+        }
+    }
+
+    #[rstest]
+    #[case("env=prod", equal("env", "prod"))]
+    #[case("env~=prod", regex("env", "prod"))]
+    #[case("env=a=b", equal("env", "a=b"))]
+    fn test_rules(#[case] raw: &str, #[case] expected: MatchRule) {
+        let rule = MatchRule::from_str(raw).unwrap();
+        assert_eq!(rule, expected);
+    }
+
+    #[rstest]
+    #[case(
+        "headers/1: env=prod\nheaders/2: env~=dev",
+        MatcherList(vec![equal("env", "prod"), regex("env", "dev")])
+    )]
+    #[case(
+        "headers/2: env=prod\nheaders/1: env~=dev",
+        MatcherList(vec![regex("env", "dev"), equal("env", "prod")])
+    )]
+    #[case(
+        "headers/2: invalid\nheaders/1: env=dev",
+        MatcherList(vec![equal("env", "dev")])
+    )]
+    #[case(
+        "headers/b/1: b=2\nheaders/a/1: a=1\nheaders/0: c=3",
+        MatcherList(vec![equal("c", "3"), equal("a", "1"), equal("b", "2")])
+    )]
+    #[case(
+        "headers/x: env=prod\nother/1: env=dev",
+        MatcherList(vec![])
+    )]
+    fn from_annotations(#[case] annotations: &str, #[case] expected: MatcherList) {
+        let annotations_map = annotations
+            .lines()
+            .filter_map(|line| {
+                let (key, value) = line.split_once(": ")?;
+                Some((key.to_string(), value.to_string()))
+            })
+            .collect::<std::collections::BTreeMap<_, _>>();
+        let matcher_list = MatcherList::from_annotations(&annotations_map, "headers/");
+        assert_eq!(matcher_list, expected);
+    }
+
+    #[test]
+    fn path_types() {
+        let path_types = parse_path_types(
+            " /exact=Exact, /api=Prefix,/legacy=PathPrefix,/re/[a=b]+=RegularExpression,",
+        )
+        .unwrap();
+        assert_eq!(
+            path_types.into_iter().collect::<Vec<_>>(),
+            vec![
+                (
+                    "/api".to_string(),
+                    HTTPRouteRulesMatchesPathType::PathPrefix
+                ),
+                ("/exact".to_string(), HTTPRouteRulesMatchesPathType::Exact),
+                (
+                    "/legacy".to_string(),
+                    HTTPRouteRulesMatchesPathType::PathPrefix
+                ),
+                (
+                    "/re/[a=b]+".to_string(),
+                    HTTPRouteRulesMatchesPathType::RegularExpression
+                ),
+            ]
+        );
+        assert!(parse_path_types("").unwrap().is_empty());
+    }
+
+    #[rstest]
+    #[case::comma_in_regex(
+        "/v{1,3}=RegularExpression,/api=Exact",
+        &[("/api", "Exact"), ("/v{1,3}", "RegularExpression")]
+    )]
+    #[case::trailing_comma_in_regex("/a{2,}=RegularExpression", &[("/a{2,}", "RegularExpression")])]
+    #[case::newlines(
+        "/v{1,3}=RegularExpression\n/a,b=Exact\n",
+        &[("/a,b", "Exact"), ("/v{1,3}", "RegularExpression")]
+    )]
+    fn path_types_with_commas(#[case] raw: &str, #[case] expected: &[(&str, &str)]) {
+        let path_types = parse_path_types(raw).unwrap();
+        let path_types = path_types
+            .iter()
+            .map(|(path, path_type)| (path.as_str(), format!("{path_type:?}")))
+            .collect::<Vec<_>>();
+        let expected = expected
+            .iter()
+            .map(|(path, path_type)| (*path, path_type.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(path_types, expected);
+    }
+
+    #[rstest]
+    #[case("/api")]
+    #[case("/api=Glob")]
+    #[case("/v{1,3}=Glob,/api=Exact")]
+    #[case("/api=Exact,/other")]
+    fn invalid_path_types(#[case] raw: &str) {
+        assert!(parse_path_types(raw).is_err());
+    }
+}